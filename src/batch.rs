@@ -0,0 +1,70 @@
+//! Batches of independent systems
+//!
+//! factorize_batch and solve_batch factorize (and solve) many
+//! independent systems on the rayon thread pool. Each system gets its
+//! own SuperLUStat, its own workspace (allocated by the driver, and
+//! recorded in its own GlobalLU_t) and its own copy of the options,
+//! so nothing is shared between the threads except the tuning
+//! parameters, which are only read. To use a pool other than the
+//! global one, call these functions inside ThreadPool::install.
+//!
+//! # Thread safety
+//!
+//! The complete LU routines in SuperLU keep no global state, so the
+//! types in this crate can be used from several threads:
+//!
+//! * Matrices (SuperMatrix, including the borrowed and transposed
+//!   views), Options, IluOptions and SuperLUStat are Send and Sync.
+//! * Factorization, LuFactorization, IncompleteLu, SymbolicLu and
+//!   PermutedMatrix are Send and Sync. Their solves take &self and
+//!   use their own statistics, so one factorization can be shared by
+//!   threads solving with different right-hand sides.
+//! * The tuning parameters are global, and are behind a lock (see
+//!   set_tuning_params).
+//! * The incomplete LU routines use a static variable, so
+//!   IncompleteLu::new holds a global lock while it factorizes.
+
+use rayon::prelude::*;
+
+use crate::error::SolveError;
+use crate::lu_factorization::{factorize, LuFactorization};
+use crate::options::Options;
+use crate::simple_driver::{solve, SystemMatrix};
+use crate::super_matrix::DenseMatrix;
+use crate::value_type::ValueType;
+
+/// Factorize each matrix for repeated solves (see factorize), in
+/// parallel
+///
+/// The results are in the same order as the matrices, and a failure
+/// only affects its own system.
+pub fn factorize_batch<T, A>(systems: &[A], options: &Options)
+			     -> Vec<Result<LuFactorization<T>, SolveError>>
+where
+    T: ValueType,
+    A: SystemMatrix<T> + Sync,
+{
+    systems.par_iter().map(|a| factorize(a, options)).collect()
+}
+
+/// Solve A x = b for each matrix A in systems and the matching
+/// right-hand side b in rhs (using the simple driver), in parallel
+///
+/// Each b is overwritten with the solution, and the factors are
+/// freed as soon as the system is solved. The results are in the same
+/// order as the systems; if a system fails, its right-hand side is
+/// left unchanged.
+pub fn solve_batch<T, A, B>(systems: &[A], rhs: &mut [B], options: &Options)
+			    -> Vec<Result<(), SolveError>>
+where
+    T: ValueType,
+    A: SystemMatrix<T> + Sync,
+    B: AsMut<[T]> + Send,
+{
+    assert_eq!(systems.len(), rhs.len(), "there must be one right-hand side for each system");
+    systems.par_iter().zip(rhs.par_iter_mut()).map(|(a, b)| {
+	let b = b.as_mut();
+	let mut b = DenseMatrix::from_slice(b.len(), 1, b);
+	solve(a, &mut b, options).map(|_| ())
+    }).collect()
+}
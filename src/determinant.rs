@@ -0,0 +1,121 @@
+//! Determinants
+//!
+//! If Pr diag(R) A diag(C) Pc = L U, with L unit lower triangular,
+//! then det(A) = sign(Pr) sign(Pc) prod U(j,j) / (prod R prod C). The
+//! product of the diagonal of U overflows or underflows easily for
+//! large matrices, so it is never formed: the logarithm of |det(A)|
+//! is the sum of the logarithms of the factors, and the sign (a
+//! complex number of modulus one, for complex matrices) is kept
+//! separately.
+
+use num::traits::{Float, One, Zero};
+
+use crate::factorization::Factorization;
+use crate::lu_factorization::LuFactorization;
+use crate::options::Trans;
+use crate::super_matrix::SuperNodeMatrix;
+use crate::value_type::ValueType;
+
+/// True if the permutation is odd (perm[i] is the image of i)
+fn is_odd(perm: &[i32]) -> bool {
+    // A permutation of n elements with k cycles is the product of
+    // n - k transpositions
+    let mut seen = vec![false; perm.len()];
+    let mut cycles = 0;
+    for start in 0..perm.len() {
+	if !seen[start] {
+	    cycles += 1;
+	    let mut i = start;
+	    while !seen[i] {
+		seen[i] = true;
+		i = perm[i] as usize;
+	    }
+	}
+    }
+    (perm.len() - cycles) % 2 == 1
+}
+
+/// The sign and the logarithm of the absolute value of the
+/// determinant of A, where Pr diag(R) M diag(C) Pc = L U and A is M
+/// with trans applied
+fn sign_and_log<T: ValueType>(l: &SuperNodeMatrix<T>, perm_r: &[i32], perm_c: &[i32],
+			      row_scale: Option<&[T::Real]>, col_scale: Option<&[T::Real]>,
+			      trans: Trans) -> (T, T::Real) {
+    let mut sign = T::one();
+    let mut log_abs = T::Real::zero();
+    for u in l.diag_u() {
+	let modulus = u.abs();
+	if modulus == T::Real::zero() {
+	    return (T::zero(), T::Real::neg_infinity());
+	}
+	sign *= u.scale(T::Real::one() / modulus);
+	log_abs = log_abs + modulus.ln();
+    }
+    // The scale factors are positive
+    for scale in row_scale.into_iter().chain(col_scale) {
+	log_abs = log_abs - scale.iter().fold(T::Real::zero(), |sum, s| sum + s.ln());
+    }
+    if is_odd(perm_r) != is_odd(perm_c) {
+	sign = T::zero() - sign;
+    }
+    // The product of many values of modulus one drifts away from it
+    let sign = sign.scale(T::Real::one() / sign.abs());
+    // det(A^T) = det(A) and det(A^H) = conj(det(A))
+    match trans {
+	Trans::Conj => (sign.conj(), log_abs),
+	Trans::NoTrans | Trans::Trans => (sign, log_abs),
+    }
+}
+
+impl<T: ValueType> LuFactorization<T> {
+    /// The sign of det(A): plus or minus one for real matrices, and
+    /// det(A) / |det(A)| for complex matrices
+    pub fn sign(&self) -> T {
+	self.sign_and_log().0
+    }
+
+    /// The natural logarithm of |det(A)|
+    ///
+    /// This does not overflow, however large or small det(A) is.
+    pub fn log_abs_determinant(&self) -> T::Real {
+	self.sign_and_log().1
+    }
+
+    /// det(A) (which may overflow to infinity or underflow to zero;
+    /// see log_abs_determinant)
+    pub fn determinant(&self) -> T {
+	let (sign, log_abs) = self.sign_and_log();
+	sign.scale(log_abs.exp())
+    }
+
+    fn sign_and_log(&self) -> (T, T::Real) {
+	sign_and_log(self.l(), self.perm_r(), self.perm_c(), self.row_scale(), self.col_scale(),
+		     self.trans())
+    }
+}
+
+impl<T: ValueType> Factorization<T> {
+    /// The sign of det(A): plus or minus one for real matrices, and
+    /// det(A) / |det(A)| for complex matrices
+    pub fn sign(&self) -> T {
+	self.sign_and_log().0
+    }
+
+    /// The natural logarithm of |det(A)|
+    ///
+    /// This does not overflow, however large or small det(A) is.
+    pub fn log_abs_determinant(&self) -> T::Real {
+	self.sign_and_log().1
+    }
+
+    /// det(A) (which may overflow to infinity or underflow to zero;
+    /// see log_abs_determinant)
+    pub fn determinant(&self) -> T {
+	let (sign, log_abs) = self.sign_and_log();
+	sign.scale(log_abs.exp())
+    }
+
+    fn sign_and_log(&self) -> (T, T::Real) {
+	sign_and_log(self.l(), self.perm_r(), self.perm_c(), None, None, self.trans())
+    }
+}
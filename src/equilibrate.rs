@@ -0,0 +1,203 @@
+//! Equilibration
+//!
+//! The xgsequ routines compute row and column scale factors R and C
+//! that make the largest absolute value in each row and column of
+//! diag(R) A diag(C) equal to one, together with the ratios of the
+//! smallest to the largest factors (rowcnd and colcnd) and the largest
+//! absolute value of A (amax). The xlaqgs routines then scale A in
+//! place, rows and columns separately, but only where this is
+//! worthwhile according to those three numbers.
+//!
+//! The expert driver does both internally. ScaleFactors computes
+//! the factors once, so that they can be inspected and applied to
+//! several matrices, and takes care of the right-hand sides and
+//! solutions: if A X = B, then (diag(R) A diag(C)) Y = diag(R) B with
+//! X = diag(C) Y.
+
+use num::traits::{Float, NumCast, One, Zero};
+
+use crate::error::SolveError;
+use crate::expert_driver::Equilibration;
+use crate::super_matrix::{CompColMatrix, Ownership, SuperMatrix, NC};
+use crate::value_type::ValueType;
+use crate::Mtype_t_SLU_GE;
+
+/// Row and column scale factors computed from a matrix
+#[derive(Debug, Clone)]
+pub struct ScaleFactors<T: ValueType> {
+    row_scale: Vec<T::Real>,
+    col_scale: Vec<T::Real>,
+    rowcnd: T::Real,
+    colcnd: T::Real,
+    amax: T::Real,
+}
+
+impl<T: ValueType> ScaleFactors<T> {
+    /// Compute the scale factors of A
+    ///
+    /// A half-stored symmetric or Hermitian matrix is treated as the
+    /// full matrix. SolveError::ZeroRow or SolveError::ZeroColumn is
+    /// returned if a whole row or column of A is zero.
+    pub fn new<O: Ownership>(a: &SuperMatrix<NC, T, O>) -> Result<Self, SolveError> {
+	if a.is_half_stored() {
+	    return Self::new(&a.to_general());
+	}
+	let (nrow, ncol) = (a.nrow(), a.ncol());
+	let mut row_scale = vec![T::Real::zero(); nrow];
+	let mut col_scale = vec![T::Real::zero(); ncol];
+	let (mut rowcnd, mut colcnd, mut amax) = (T::Real::one(), T::Real::one(), T::Real::zero());
+	let mut info = 0;
+	unsafe {
+	    // xgsequ only reads A, but rejects matrices that are not
+	    // SLU_GE (such as triangular ones, which are stored in full)
+	    let mut raw = *a.as_raw();
+	    raw.Mtype = Mtype_t_SLU_GE;
+	    T::c_gsequ(&mut raw, row_scale.as_mut_ptr(), col_scale.as_mut_ptr(),
+		       &mut rowcnd, &mut colcnd, &mut amax, &mut info);
+	}
+	if info < 0 {
+	    return Err(SolveError::IllegalArgument(-info as usize));
+	} else if info as usize > nrow {
+	    return Err(SolveError::ZeroColumn(info as usize - nrow - 1));
+	} else if info > 0 {
+	    return Err(SolveError::ZeroRow(info as usize - 1));
+	}
+	Ok(Self { row_scale, col_scale, rowcnd, colcnd, amax })
+    }
+
+    /// Number of rows of the matrix
+    pub fn nrow(&self) -> usize {
+	self.row_scale.len()
+    }
+
+    /// Number of columns of the matrix
+    pub fn ncol(&self) -> usize {
+	self.col_scale.len()
+    }
+
+    /// The row scale factors R
+    pub fn row_scale(&self) -> &[T::Real] {
+	&self.row_scale
+    }
+
+    /// The column scale factors C
+    pub fn col_scale(&self) -> &[T::Real] {
+	&self.col_scale
+    }
+
+    /// The ratio of the smallest to the largest row scale factor
+    pub fn rowcnd(&self) -> T::Real {
+	self.rowcnd
+    }
+
+    /// The ratio of the smallest to the largest column scale factor
+    pub fn colcnd(&self) -> T::Real {
+	self.colcnd
+    }
+
+    /// The largest absolute value in the matrix
+    pub fn amax(&self) -> T::Real {
+	self.amax
+    }
+
+    /// The scaling that apply does
+    ///
+    /// The rows are scaled if rowcnd is below 0.1 or amax is close to
+    /// overflow or underflow, and the columns if colcnd is below 0.1
+    /// (the test in xlaqgs). Equilibration::None means scaling is not
+    /// worthwhile.
+    pub fn equilibration(&self) -> Equilibration {
+	if self.nrow() == 0 || self.ncol() == 0 {
+	    return Equilibration::None;
+	}
+	let thresh: T::Real = NumCast::from(0.1).unwrap();
+	let small = T::Real::min_positive_value() / T::Real::epsilon();
+	let large = T::Real::one() / small;
+	let rows = self.rowcnd < thresh || self.amax < small || self.amax > large;
+	let cols = self.colcnd < thresh;
+	match (rows, cols) {
+	    (false, false) => Equilibration::None,
+	    (true, false) => Equilibration::Row,
+	    (false, true) => Equilibration::Col,
+	    (true, true) => Equilibration::Both,
+	}
+    }
+
+    /// Scale A in place, as described by equilibration
+    ///
+    /// A may be a different matrix from the one the factors were
+    /// computed from, with the same dimensions. Panics if it has
+    /// different dimensions, or is half-stored (the rows and columns
+    /// of the stored triangle are scaled differently).
+    pub fn apply<O: Ownership>(&self, a: &mut SuperMatrix<NC, T, O>) -> Equilibration {
+	assert_eq!((a.nrow(), a.ncol()), (self.nrow(), self.ncol()), "matrix has the wrong dimensions");
+	assert!(!a.is_half_stored(), "cannot equilibrate a half-stored matrix");
+	let mut equed = b'N' as libc::c_char;
+	let (mut r, mut c) = (self.row_scale.clone(), self.col_scale.clone());
+	unsafe {
+	    // The copy of the struct points to the same values
+	    let mut raw = *a.as_raw();
+	    T::c_laqgs(&mut raw, r.as_mut_ptr(), c.as_mut_ptr(), self.rowcnd, self.colcnd,
+		       self.amax, &mut equed);
+	}
+	Equilibration::from_raw(equed)
+    }
+
+    /// Replace the right-hand sides B (stored column by column) by
+    /// diag(R) B, if the rows are scaled
+    pub fn scale_rhs(&self, b: &mut [T]) {
+	if self.equilibration().rows() {
+	    scale_columns(b, &self.row_scale, |s| s);
+	}
+    }
+
+    /// Replace diag(R) B by B (the reverse of scale_rhs)
+    pub fn unscale_rhs(&self, b: &mut [T]) {
+	if self.equilibration().rows() {
+	    scale_columns(b, &self.row_scale, |s| T::Real::one() / s);
+	}
+    }
+
+    /// Replace the solutions Y of the scaled system by the solutions
+    /// X = diag(C) Y of the original one, if the columns are scaled
+    pub fn unscale_solution(&self, x: &mut [T]) {
+	if self.equilibration().cols() {
+	    scale_columns(x, &self.col_scale, |s| s);
+	}
+    }
+
+    /// Replace X by diag(C)^-1 X (the reverse of unscale_solution,
+    /// for example to scale an initial guess)
+    pub fn scale_solution(&self, x: &mut [T]) {
+	if self.equilibration().cols() {
+	    scale_columns(x, &self.col_scale, |s| T::Real::one() / s);
+	}
+    }
+}
+
+/// Multiply each column of the column-major values by diag(f(scale))
+fn scale_columns<T: ValueType>(values: &mut [T], scale: &[T::Real], f: impl Fn(T::Real) -> T::Real) {
+    assert_eq!(values.len() % scale.len(), 0, "length is not a multiple of the matrix dimension");
+    for col in values.chunks_mut(scale.len()) {
+	for (v, &s) in col.iter_mut().zip(scale) {
+	    *v = v.scale(f(s));
+	}
+    }
+}
+
+/// A copy of A scaled to diag(R) A diag(C), where a missing R or C
+/// stands for the identity
+pub(crate) fn scaled_copy<T: ValueType, O: Ownership>(a: &SuperMatrix<NC, T, O>,
+						     rows: Option<&[T::Real]>,
+						     cols: Option<&[T::Real]>) -> CompColMatrix<T> {
+    let (rowind, colptr) = (a.rowind(), a.colptr());
+    let mut values = a.nzval().to_vec();
+    for j in 0..a.ncol() {
+	let c = cols.map_or(T::Real::one(), |c| c[j]);
+	for k in colptr[j] as usize..colptr[j + 1] as usize {
+	    let r = rows.map_or(T::Real::one(), |r| r[rowind[k] as usize]);
+	    values[k] = values[k].scale(r * c);
+	}
+    }
+    CompColMatrix::new(a.nrow(), a.ncol(), &values, rowind, colptr)
+}
@@ -0,0 +1,94 @@
+//! Errors reported by the SuperLU routines
+//!
+//! The SuperLU drivers report problems through an info argument,
+//! which is negative for an illegal argument, between 1 and n if the
+//! matrix is exactly singular, and greater than n if memory ran out.
+//! The expert driver also uses n + 1 to report a matrix that is
+//! singular to working precision, which is only a warning.
+
+use std::fmt;
+
+use crate::options::ColumnOrdering;
+
+/// Error returned when a factorization or solve fails
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    /// U(j,j) is exactly zero for the (zero-based) column j, so the
+    /// matrix is singular and cannot be used to solve a system.
+    Singular(usize),
+    /// SuperLU ran out of memory, after allocating this many bytes.
+    OutOfMemory(usize),
+    /// The (one-based) argument of the SuperLU routine had an
+    /// illegal value.
+    IllegalArgument(usize),
+    /// The matrix does not have the sparsity pattern that was
+    /// analyzed (see SymbolicLu).
+    PatternMismatch,
+    /// Row i (zero-based) of the matrix is exactly zero, so it
+    /// cannot be equilibrated.
+    ZeroRow(usize),
+    /// Column j (zero-based) of the matrix is exactly zero, so it
+    /// cannot be equilibrated.
+    ZeroColumn(usize),
+    /// The column ordering is not implemented by the serial SuperLU
+    /// library.
+    UnavailableOrdering(ColumnOrdering),
+    /// The element at this (zero-based) position of a permutation is
+    /// out of range, or repeats an earlier element.
+    InvalidPermutation(usize),
+    /// No row permutation leaves the diagonal free of structural
+    /// zeros.
+    StructurallySingular,
+}
+
+impl SolveError {
+    /// Interpret the info value returned by a SuperLU routine, for
+    /// a matrix with n columns. Returns None if info is zero.
+    pub(crate) fn from_info(info: i32, n: usize) -> Option<Self> {
+	if info < 0 {
+	    Some(Self::IllegalArgument(-info as usize))
+	} else if info == 0 {
+	    None
+	} else if info as usize <= n {
+	    Some(Self::Singular(info as usize - 1))
+	} else {
+	    Some(Self::OutOfMemory(info as usize - n))
+	}
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+	match self {
+	    Self::Singular(j) => write!(f, "matrix is singular (U({0},{0}) is exactly zero)", j),
+	    Self::OutOfMemory(bytes) => write!(f, "out of memory after allocating {} bytes", bytes),
+	    Self::IllegalArgument(i) => write!(f, "argument {} had an illegal value", i),
+	    Self::PatternMismatch => write!(f, "matrix does not have the analyzed sparsity pattern"),
+	    Self::ZeroRow(i) => write!(f, "row {} of the matrix is exactly zero", i),
+	    Self::ZeroColumn(j) => write!(f, "column {} of the matrix is exactly zero", j),
+	    Self::UnavailableOrdering(ordering) => {
+		write!(f, "the {:?} column ordering is not available in serial SuperLU", ordering)
+	    }
+	    Self::InvalidPermutation(i) => write!(f, "element {} of the permutation is invalid", i),
+	    Self::StructurallySingular => write!(f, "matrix is structurally singular"),
+	}
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+/// Warning returned alongside a successful solve
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SolveWarning {
+    /// The estimated reciprocal condition number is smaller than
+    /// the machine precision, so the solution may be inaccurate.
+    IllConditioned,
+}
+
+impl fmt::Display for SolveWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+	match self {
+	    Self::IllConditioned => write!(f, "matrix is singular to working precision"),
+	}
+    }
+}
@@ -0,0 +1,256 @@
+//! Predicting the size of a factorization
+//!
+//! George and Ng showed that, whatever rows are chosen as pivots, the
+//! structures of L and U in Pr A Pc = L U are contained in those of
+//! R^T and R, where R^T R is the Cholesky factorization of
+//! (A Pc)^T (A Pc). The analyze function orders and postorders the
+//! columns as the drivers do, computes the structure of R from the
+//! pattern of A (without forming A^T A), and groups the columns into
+//! supernodes, so that it can bound the size of the factors before
+//! any numeric work is done.
+//!
+//! The bounds are exact for some matrices (dense ones, for example),
+//! but with little pivoting the real factors are often much smaller.
+//! The supernode count and the operation count are estimates for the
+//! bound structure. SuperLU itself only makes a rough guess from the
+//! fill ratio tuning parameter, for its initial allocation, and
+//! expands the storage during the factorization when it runs out.
+
+use std::ops::Range;
+
+use crate::etree::EliminationTree;
+use crate::options::Options;
+use crate::preorder::{column_permutation, preorder};
+use crate::simple_driver::SystemMatrix;
+use crate::sp_ienv::{get_tuning_params, TuningParams};
+use crate::super_matrix::CompColMatrix;
+use crate::value_type::ValueType;
+
+/// Predicted sizes of the LU factorization of a matrix
+#[derive(Debug, Clone, PartialEq)]
+pub struct FactorizationEstimate {
+    perm_c: Vec<i32>,
+    nnz_l: usize,
+    nnz_u: usize,
+    supernodes: usize,
+    flops: f64,
+    bytes: usize,
+    initial_bytes: usize,
+}
+
+impl FactorizationEstimate {
+    /// The column permutation the estimate is for (after
+    /// postordering), which factorize_with_permutation can reuse
+    pub fn perm_c(&self) -> &[i32] {
+	&self.perm_c
+    }
+
+    /// An upper bound on the number of entries of L, counted as
+    /// SuperLU does (including the diagonal, and the explicit zeros of
+    /// relaxed supernodes)
+    pub fn nnz_l(&self) -> usize {
+	self.nnz_l
+    }
+
+    /// An upper bound on the number of entries of U, counted in the
+    /// same way (the bound on U is the transpose of the bound on L, so
+    /// this is the same number)
+    pub fn nnz_u(&self) -> usize {
+	self.nnz_u
+    }
+
+    /// The estimated number of supernodes
+    pub fn supernodes(&self) -> usize {
+	self.supernodes
+    }
+
+    /// The estimated number of floating-point operations in the
+    /// factorization (an upper bound, for the bound structure)
+    pub fn flops(&self) -> f64 {
+	self.flops
+    }
+
+    /// An estimate of the bytes needed to store L and U with the
+    /// bound structure (the values and indices, and the arrays of one
+    /// element per column), which is usually more than needed
+    pub fn bytes(&self) -> usize {
+	self.bytes
+    }
+
+    /// The bytes SuperLU allocates at the start of the factorization
+    /// (the total_needed of xgssvx with lwork = -1), a guess based on
+    /// the fill ratio tuning parameter rather than on the structure
+    pub fn initial_bytes(&self) -> usize {
+	self.initial_bytes
+    }
+}
+
+/// Predict the size of the LU factorization of A, without factorizing
+/// it
+///
+/// The options set the column ordering (and symmetric mode), as in
+/// factorize; the other options do not change the estimate, which
+/// uses the current tuning parameters. A must be square.
+pub fn analyze<T: ValueType, A: SystemMatrix<T>>(a: &A, options: &Options) -> FactorizationEstimate {
+    let (a, _) = a.comp_col_copy();
+    assert_eq!(a.nrow(), a.ncol(), "A must be square");
+    let n = a.ncol();
+    if n == 0 {
+	return FactorizationEstimate {
+	    perm_c: Vec::new(),
+	    nnz_l: 0,
+	    nnz_u: 0,
+	    supernodes: 0,
+	    flops: 0.0,
+	    bytes: 0,
+	    initial_bytes: 0,
+	};
+    }
+    let tuning = get_tuning_params();
+    let perm_c = column_permutation(options, &a);
+    let (perm_c, etree) = {
+	let ac = preorder(options, &a, &perm_c);
+	(ac.perm_c().to_vec(), ac.etree().to_vec())
+    };
+    let relaxed = EliminationTree::from_parents(etree).relaxed_supernodes(tuning.relaxation_param);
+    let (counts, parent) = cholesky_counts(&a, &perm_c);
+    let supernodes = supernode_partition(&counts, &parent, &relaxed, tuning.max_supernode_size);
+
+    // Each supernode is stored as a dense block of w columns in L, with
+    // the rows of the block and h rows below it, and the same h
+    // columns in U to the right of the block
+    let (mut nnz, mut l_values, mut u_values, mut lsub) = (0, 0, 0, 0);
+    let mut flops = 0.0;
+    for s in &supernodes {
+	let (w, h) = (s.len(), counts[s.end - 1] - 1);
+	nnz += w * (w + 1) / 2 + w * h;
+	l_values += w * (w + h);
+	u_values += w * h;
+	lsub += w + h;
+	for t in 0..w {
+	    // Column t of the block: divide the column of L below the
+	    // diagonal, then update with its outer product with the row
+	    // of U
+	    let below = (w - 1 - t + h) as f64;
+	    flops += below + 2.0 * below * below;
+	}
+    }
+    // xsup, supno, xlsub, xlusup, xusub, and the permutations and etree
+    let arrays = 5 * (n + 1) + 3 * n;
+    let bytes = (l_values + u_values) * std::mem::size_of::<T>()
+	+ (lsub + u_values + arrays) * std::mem::size_of::<i32>();
+    FactorizationEstimate {
+	perm_c,
+	nnz_l: nnz,
+	nnz_u: nnz,
+	supernodes: supernodes.len(),
+	flops,
+	bytes,
+	initial_bytes: initial_allocation::<T>(n, a.nnz(), &tuning),
+    }
+}
+
+/// The bytes xgstrf allocates at the start of the factorization of
+/// an n x n matrix with annz entries (the size xLUMemInit returns for
+/// lwork = -1, without the n it adds to tell it apart from an error)
+fn initial_allocation<T: ValueType>(n: usize, annz: usize, tuning: &TuningParams) -> usize {
+    let (iword, dword) = (std::mem::size_of::<i32>(), std::mem::size_of::<T>());
+    // The guesses for the sizes of lusup and ucol (and usub), and of
+    // lsub, from the fill ratio
+    let fill_ratio = tuning.estimated_fills;
+    let nzumax = fill_ratio * annz;
+    let nzlumax = nzumax;
+    let nzlmax = fill_ratio.max(4) * annz / 4;
+    // GluIntArray(n): xsup, supno, xlsub, xlusup and xusub
+    let glu = (5 * n + 5) * iword;
+    // TempSpace(n, panel_size): the integer work arrays (with the
+    // NO_MARKER = 3 markers) and the dense work vectors
+    let w = tuning.panel_size;
+    let temp = (2 * w + 4 + 3) * n * iword + (w + 1) * n * dword;
+    glu + temp + (nzlmax + nzumax) * iword + (nzlumax + nzumax) * dword
+}
+
+/// The number of entries in each column of R^T, where R^T R is the
+/// Cholesky factorization of (A Pc)^T (A Pc), and the parent of each
+/// column in its elimination tree (n for a root)
+///
+/// The structure of column j is j, the columns of the rows of A Pc
+/// whose first entry is in column j, and the structures of the
+/// children of j without the children themselves.
+fn cholesky_counts<T: ValueType>(a: &CompColMatrix<T>, perm_c: &[i32]) -> (Vec<usize>, Vec<usize>) {
+    let (nrow, n, nnz) = (a.nrow(), a.ncol(), a.nnz());
+    let (colptr, rowind) = (a.colptr(), a.rowind());
+
+    // The columns of A Pc with an entry in each row
+    let mut rowptr = vec![0; nrow + 1];
+    for &i in &rowind[..nnz] {
+	rowptr[i as usize + 1] += 1;
+    }
+    for i in 0..nrow {
+	rowptr[i + 1] += rowptr[i];
+    }
+    let mut next = rowptr.clone();
+    let mut colind = vec![0; nnz];
+    for j in 0..n {
+	for &i in &rowind[colptr[j] as usize..colptr[j + 1] as usize] {
+	    colind[next[i as usize]] = perm_c[j] as usize;
+	    next[i as usize] += 1;
+	}
+    }
+    let mut first_rows = vec![Vec::new(); n];
+    for i in 0..nrow {
+	if let Some(&first) = colind[rowptr[i]..rowptr[i + 1]].iter().min() {
+	    first_rows[first].push(i);
+	}
+    }
+
+    let mut counts = vec![0; n];
+    let mut parent = vec![n; n];
+    let mut marker = vec![n; n];
+    // The structures of the children of each column, concatenated
+    let mut pending = vec![Vec::new(); n];
+    for j in 0..n {
+	marker[j] = j;
+	let mut structure = vec![j];
+	let rows = first_rows[j].iter().flat_map(|&i| &colind[rowptr[i]..rowptr[i + 1]]);
+	for &k in rows.chain(&std::mem::take(&mut pending[j])) {
+	    if marker[k] != j {
+		marker[k] = j;
+		structure.push(k);
+	    }
+	}
+	counts[j] = structure.len();
+	if let Some(&p) = structure[1..].iter().min() {
+	    parent[j] = p;
+	    pending[p].extend_from_slice(&structure[1..]);
+	}
+    }
+    (counts, parent)
+}
+
+/// Split the columns into the relaxed supernodes, and supernodes of
+/// consecutive columns with nested structures (at most max_size
+/// columns each)
+fn supernode_partition(counts: &[usize], parent: &[usize], relaxed: &[Range<usize>],
+		       max_size: usize) -> Vec<Range<usize>> {
+    let n = counts.len();
+    let mut relaxed_end = vec![None; n];
+    for s in relaxed {
+	relaxed_end[s.start] = Some(s.end);
+    }
+    let mut supernodes = Vec::new();
+    let mut j = 0;
+    while j < n {
+	let end = relaxed_end[j].unwrap_or_else(|| {
+	    let mut end = j + 1;
+	    while end < n && relaxed_end[end].is_none() && end - j < max_size.max(1)
+		&& parent[end - 1] == end && counts[end - 1] == counts[end] + 1 {
+		end += 1;
+	    }
+	    end
+	});
+	supernodes.push(j..end);
+	j = end;
+    }
+    supernodes
+}
@@ -0,0 +1,224 @@
+//! Column elimination trees
+//!
+//! The column elimination tree of A is the elimination tree of A^T A.
+//! Column j can only update the columns on its path to the root, so
+//! disjoint subtrees can be factorized independently, and the height
+//! of the tree bounds the length of the critical path. The tree is
+//! computed from the pattern alone by sp_coletree, in the column
+//! order of A (or of A Pc, for a column permutation Pc); the parent of
+//! a column always comes after it.
+//!
+//! The SuperLU drivers postorder the tree (TreePostorder), so that
+//! each subtree is a contiguous range of columns, and then group the
+//! small subtrees at the bottom of the tree into relaxed supernodes
+//! (relax_snode, or heap_relax_snode if the tree is not postordered).
+//! EliminationTree exposes all of this without factorizing anything.
+
+use std::ops::Range;
+
+use crate::ordering::Permutation;
+use crate::simple_driver::SystemMatrix;
+use crate::super_matrix::CompColMatrix;
+use crate::value_type::ValueType;
+use crate::{heap_relax_snode, relax_snode, sp_coletree, superlu_free, TreePostorder};
+
+/// The column elimination tree of a sparsity pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EliminationTree {
+    /// parent[j] is the parent of column j, or n for a root
+    parent: Vec<i32>,
+    /// The children of column j are children[child_start[j]..
+    /// child_start[j + 1]], and the roots are those of "column" n
+    child_start: Vec<usize>,
+    children: Vec<usize>,
+}
+
+impl EliminationTree {
+    /// The column elimination tree of A (the values are not used)
+    ///
+    /// For a compressed-row matrix or a transposed view, this is the
+    /// tree of the stored transpose, which is the matrix the
+    /// factorizations work with.
+    pub fn new<T: ValueType, A: SystemMatrix<T>>(a: &A) -> Self {
+	let (a, _) = a.comp_col_copy();
+	Self::coletree(&a, &Permutation::identity(a.ncol()))
+    }
+
+    /// The column elimination tree of A Pc (perm_c[j] = k means
+    /// column j of A is column k of A Pc)
+    ///
+    /// Panics if perm_c does not have one element for each column of
+    /// A (as stored, for a compressed-row matrix or a transposed
+    /// view).
+    pub fn with_permutation<T: ValueType, A: SystemMatrix<T>>(a: &A, perm_c: &Permutation) -> Self {
+	let (a, _) = a.comp_col_copy();
+	assert_eq!(perm_c.len(), a.ncol(), "perm_c must have one element for each column");
+	Self::coletree(&a, perm_c)
+    }
+
+    /// Call sp_coletree on the columns of A Pc
+    fn coletree<T: ValueType>(a: &CompColMatrix<T>, perm_c: &Permutation) -> Self {
+	let (nrow, ncol) = (a.nrow(), a.ncol());
+	if ncol == 0 {
+	    return Self::from_parents(Vec::new());
+	}
+	// Column j of A is column perm_c[j] of A Pc, as in sp_preorder
+	let colptr = a.colptr();
+	let mut colbeg = vec![0; ncol];
+	let mut colend = vec![0; ncol];
+	for (j, &pj) in perm_c.as_slice().iter().enumerate() {
+	    colbeg[pj as usize] = colptr[j];
+	    colend[pj as usize] = colptr[j + 1];
+	}
+	// sp_coletree takes mutable pointers, but only reads the pattern
+	let mut rowind = a.rowind().to_vec();
+	let mut parent = vec![0; ncol];
+	unsafe {
+	    sp_coletree(colbeg.as_mut_ptr(), colend.as_mut_ptr(), rowind.as_mut_ptr(),
+			nrow as i32, ncol as i32, parent.as_mut_ptr());
+	}
+	Self::from_parents(parent)
+    }
+
+    /// The tree with the given parents (parent[j] is the parent of
+    /// column j, or parent.len() for a root), for example the etree of
+    /// a factorization
+    ///
+    /// Panics unless each column comes before its parent.
+    pub fn from_parents(parent: Vec<i32>) -> Self {
+	let n = parent.len();
+	let mut counts = vec![0; n + 2];
+	for (j, &p) in parent.iter().enumerate() {
+	    assert!(p as usize > j && p as usize <= n, "the parent of column {} must come after it", j);
+	    counts[p as usize + 1] += 1;
+	}
+	let mut child_start = counts;
+	for j in 0..=n {
+	    child_start[j + 1] += child_start[j];
+	}
+	let mut next = child_start.clone();
+	let mut children = vec![0; n];
+	for (j, &p) in parent.iter().enumerate() {
+	    children[next[p as usize]] = j;
+	    next[p as usize] += 1;
+	}
+	Self { parent, child_start, children }
+    }
+
+    /// The number of columns
+    pub fn len(&self) -> usize {
+	self.parent.len()
+    }
+
+    /// True if there are no columns
+    pub fn is_empty(&self) -> bool {
+	self.parent.is_empty()
+    }
+
+    /// The parent array, in the SuperLU convention (n for a root)
+    pub fn parents(&self) -> &[i32] {
+	&self.parent
+    }
+
+    /// The parent of column j, or None if it is a root
+    pub fn parent(&self, j: usize) -> Option<usize> {
+	let p = self.parent[j] as usize;
+	(p < self.len()).then_some(p)
+    }
+
+    /// The children of column j, in increasing order
+    pub fn children(&self, j: usize) -> &[usize] {
+	assert!(j < self.len(), "column index out of range");
+	&self.children[self.child_start[j]..self.child_start[j + 1]]
+    }
+
+    /// The roots of the forest, in increasing order
+    pub fn roots(&self) -> &[usize] {
+	let n = self.len();
+	&self.children[self.child_start[n]..self.child_start[n + 1]]
+    }
+
+    /// A postorder of the tree (using TreePostorder): column j is
+    /// visited post[j]-th, so that each subtree is a contiguous range
+    pub fn postorder(&self) -> Permutation {
+	let n = self.len();
+	if n == 0 {
+	    return Permutation::identity(0);
+	}
+	let mut parent = self.parent.clone();
+	let post = unsafe {
+	    let raw = TreePostorder(n as i32, parent.as_mut_ptr());
+	    // The result has an extra element for the dummy root
+	    let post = std::slice::from_raw_parts(raw, n).to_vec();
+	    superlu_free(raw.cast());
+	    post
+	};
+	Permutation::new(post).unwrap()
+    }
+
+    /// True if the columns are already in the order of postorder (as
+    /// in the trees of the factorizations)
+    pub fn is_postordered(&self) -> bool {
+	self.postorder().as_slice().iter().enumerate().all(|(j, &p)| p as usize == j)
+    }
+
+    /// The number of columns in the subtree rooted at each column
+    /// (including the column itself)
+    pub fn subtree_sizes(&self) -> Vec<usize> {
+	let mut sizes = vec![1; self.len()];
+	for j in 0..self.len() {
+	    if let Some(p) = self.parent(j) {
+		sizes[p] += sizes[j];
+	    }
+	}
+	sizes
+    }
+
+    /// The number of columns on the longest path from a root to a
+    /// leaf (zero for an empty tree)
+    pub fn height(&self) -> usize {
+	let mut depth = vec![1; self.len()];
+	for j in (0..self.len()).rev() {
+	    if let Some(p) = self.parent(j) {
+		depth[j] = depth[p] + 1;
+	    }
+	}
+	depth.into_iter().max().unwrap_or(0)
+    }
+
+    /// The relaxed supernodes that xgstrf would use with this
+    /// relaxation parameter (see TuningParams), as ranges of columns
+    ///
+    /// Each is a subtree of at most relaxation_param columns at the
+    /// bottom of the tree (for a tree that is not postordered, only
+    /// those that are contiguous ranges of columns). The other columns
+    /// are grouped into supernodes during the factorization, depending
+    /// on the structure of L.
+    pub fn relaxed_supernodes(&self, relaxation_param: usize) -> Vec<Range<usize>> {
+	let n = self.len();
+	if n == 0 {
+	    return Vec::new();
+	}
+	// Both routines read one element past the end of descendants,
+	// and heap_relax_snode changes the tree while it runs
+	let mut parent = self.parent.clone();
+	let mut descendants = vec![0; n + 1];
+	let mut relax_end = vec![0; n];
+	let relax = relaxation_param.min(i32::MAX as usize) as i32;
+	unsafe {
+	    if self.is_postordered() {
+		relax_snode(n as i32, parent.as_mut_ptr(), relax, descendants.as_mut_ptr(),
+			    relax_end.as_mut_ptr());
+	    } else {
+		heap_relax_snode(n as i32, parent.as_mut_ptr(), relax, descendants.as_mut_ptr(),
+				 relax_end.as_mut_ptr());
+	    }
+	}
+	// relax_end[j] is the last column of the supernode starting at
+	// column j, or EMPTY
+	relax_end.iter().enumerate()
+	    .filter(|&(_, &end)| end >= 0)
+	    .map(|(j, &end)| j..end as usize + 1)
+	    .collect()
+    }
+}
@@ -0,0 +1,249 @@
+//! The expert driver
+//!
+//! The xgssvx routines solve A X = B like the simple driver, but can
+//! also equilibrate A, estimate its condition number and pivot growth,
+//! refine the solution, and bound its error (all controlled by the
+//! options). The solve_expert function wraps them for all four
+//! precisions, and returns everything they compute in an
+//! ExpertSolution.
+
+use std::mem::MaybeUninit;
+
+use crate::error::{SolveError, SolveWarning};
+use crate::options::Options;
+use crate::simple_driver::SystemMatrix;
+use crate::stat::SuperLUStat;
+use crate::super_matrix::{CompColMatrix, DenseMatrix, Ownership, SuperMatrix, SuperNodeMatrix, DN};
+use crate::value_type::ValueType;
+use crate::{fact_t_DOFACT, mem_usage_t, yes_no_t_YES, GlobalLU_t, SuperMatrix as c_SuperMatrix};
+
+/// How A was scaled before it was factorized
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Equilibration {
+    /// No scaling
+    None,
+    /// The rows were scaled: A is replaced by diag(R) A
+    Row,
+    /// The columns were scaled: A is replaced by A diag(C)
+    Col,
+    /// Both: A is replaced by diag(R) A diag(C)
+    Both,
+}
+
+impl Equilibration {
+    /// Interpret the equed character set by xgssvx
+    pub(crate) fn from_raw(equed: libc::c_char) -> Self {
+	match equed as u8 {
+	    b'R' => Self::Row,
+	    b'C' => Self::Col,
+	    b'B' => Self::Both,
+	    _ => Self::None,
+	}
+    }
+
+    /// The equed character for the SuperLU routines
+    pub(crate) fn to_raw(self) -> libc::c_char {
+	(match self {
+	    Self::None => b'N',
+	    Self::Row => b'R',
+	    Self::Col => b'C',
+	    Self::Both => b'B',
+	}) as libc::c_char
+    }
+
+    /// True if the rows were scaled
+    pub fn rows(self) -> bool {
+	matches!(self, Self::Row | Self::Both)
+    }
+
+    /// True if the columns were scaled
+    pub fn cols(self) -> bool {
+	matches!(self, Self::Col | Self::Both)
+    }
+}
+
+/// Memory used by the factorization, in bytes
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MemoryUsage {
+    /// Memory used to store L and U
+    pub for_lu: f64,
+    /// Total memory needed by the factorization
+    pub total_needed: f64,
+}
+
+impl From<mem_usage_t> for MemoryUsage {
+    fn from(raw: mem_usage_t) -> Self {
+	Self {
+	    for_lu: raw.for_lu as f64,
+	    total_needed: raw.total_needed as f64,
+	}
+    }
+}
+
+/// The solution of A X = B and the quantities computed by the expert
+/// driver
+#[derive(Debug)]
+pub struct ExpertSolution<T: ValueType> {
+    x: DenseMatrix<T>,
+    equilibration: Equilibration,
+    row_scale: Vec<f64>,
+    col_scale: Vec<f64>,
+    recip_pivot_growth: Option<f64>,
+    rcond: Option<f64>,
+    ferr: Vec<f64>,
+    berr: Vec<f64>,
+    memory_usage: MemoryUsage,
+    warning: Option<SolveWarning>,
+}
+
+impl<T: ValueType> ExpertSolution<T> {
+    /// The solution X
+    pub fn x(&self) -> &DenseMatrix<T> {
+	&self.x
+    }
+
+    /// The scaling applied to A
+    pub fn equilibration(&self) -> Equilibration {
+	self.equilibration
+    }
+
+    /// The row scale factors R, if the rows were scaled
+    pub fn row_scale(&self) -> Option<&[f64]> {
+	self.equilibration.rows().then_some(&self.row_scale[..])
+    }
+
+    /// The column scale factors C, if the columns were scaled
+    pub fn col_scale(&self) -> Option<&[f64]> {
+	self.equilibration.cols().then_some(&self.col_scale[..])
+    }
+
+    /// The reciprocal pivot growth factor max |A(i,j)| / max |U(i,j)|
+    /// (if requested with Options::pivot_growth). A small value means
+    /// the factorization may be unstable.
+    pub fn recip_pivot_growth(&self) -> Option<f64> {
+	self.recip_pivot_growth
+    }
+
+    /// The estimated reciprocal condition number of A (if requested
+    /// with Options::condition_number)
+    pub fn rcond(&self) -> Option<f64> {
+	self.rcond
+    }
+
+    /// The estimated forward error bound for each column of X. This
+    /// is only computed with Options::iterative_refinement;
+    /// otherwise, SuperLU sets every bound to 1.
+    pub fn ferr(&self) -> &[f64] {
+	&self.ferr
+    }
+
+    /// The componentwise relative backward error of each column of
+    /// X (set to 1 unless Options::iterative_refinement is used)
+    pub fn berr(&self) -> &[f64] {
+	&self.berr
+    }
+
+    /// Memory used by the factorization
+    pub fn memory_usage(&self) -> MemoryUsage {
+	self.memory_usage
+    }
+
+    /// A warning about the accuracy of the solution, if any
+    pub fn warning(&self) -> Option<SolveWarning> {
+	self.warning
+    }
+}
+
+/// Copy the columns of B into a new dense matrix (with lda = nrow)
+fn copy_dense<T: ValueType, O: Ownership>(b: &SuperMatrix<DN, T, O>) -> DenseMatrix<T> {
+    let (nrow, lda) = (b.nrow(), b.lda());
+    let values: Vec<T> = b.values().chunks(lda.max(1)).take(b.ncol())
+	.flat_map(|col| col[..nrow].iter().copied()).collect();
+    DenseMatrix::new(nrow, b.ncol(), &values)
+}
+
+fn to_f64<R: Into<f64> + Copy>(values: &[R]) -> Vec<f64> {
+    values.iter().map(|&x| x.into()).collect()
+}
+
+/// Solve A X = B using the expert driver (xgssvx)
+///
+/// A must be square, and B must have one row for each row of A.
+/// Neither is changed: A is copied (the expert driver scales it in
+/// place when equilibrating), and so is B. The solution, and the
+/// quantities requested in the options, are returned in an
+/// ExpertSolution. The Fact option is ignored (A is always factorized
+/// from scratch).
+///
+/// If the condition number is estimated and is worse than the machine
+/// precision, the solution is still returned, with
+/// SolveWarning::IllConditioned.
+pub fn solve_expert<T, A, O>(
+    a: &A,
+    b: &SuperMatrix<DN, T, O>,
+    options: &Options,
+) -> Result<ExpertSolution<T>, SolveError>
+where
+    T: ValueType,
+    A: SystemMatrix<T>,
+    O: Ownership,
+{
+    let (mut a, trans) = a.comp_col_copy();
+    let (nrow, ncol, nrhs) = (a.nrow(), a.ncol(), b.ncol());
+    assert_eq!(nrow, ncol, "A must be square");
+    assert_eq!(b.nrow(), nrow, "B must have one row for each row of A");
+
+    let mut b = copy_dense(b);
+    let mut x = DenseMatrix::new(nrow, nrhs, &vec![T::default(); nrow * nrhs]);
+    let mut raw_options = *options.as_raw();
+    raw_options.Fact = fact_t_DOFACT;
+    raw_options.Trans = trans.to_raw();
+    let mut perm_c = vec![0; ncol];
+    let mut perm_r = vec![0; nrow];
+    let mut etree = vec![0; ncol];
+    let mut equed = b'N' as libc::c_char;
+    let mut r = vec![T::Real::default(); nrow];
+    let mut c = vec![T::Real::default(); ncol];
+    let mut recip_pivot_growth = T::Real::default();
+    let mut rcond = T::Real::default();
+    let mut ferr = vec![T::Real::default(); nrhs];
+    let mut berr = vec![T::Real::default(); nrhs];
+    let mut stat = SuperLUStat::new();
+    let mut info = 0;
+    let memory_usage = unsafe {
+	let mut glu = MaybeUninit::<GlobalLU_t>::zeroed().assume_init();
+	let mut mem_usage = MaybeUninit::<mem_usage_t>::zeroed().assume_init();
+	let mut l = MaybeUninit::<c_SuperMatrix>::uninit();
+	let mut u = MaybeUninit::<c_SuperMatrix>::uninit();
+	T::c_gssvx(&mut raw_options, a.as_mut_ptr(), perm_c.as_mut_ptr(), perm_r.as_mut_ptr(),
+		   etree.as_mut_ptr(), &mut equed, r.as_mut_ptr(), c.as_mut_ptr(),
+		   l.as_mut_ptr(), u.as_mut_ptr(), std::ptr::null_mut(), 0, b.as_mut_ptr(),
+		   x.as_mut_ptr(), &mut recip_pivot_growth, &mut rcond, ferr.as_mut_ptr(),
+		   berr.as_mut_ptr(), &mut glu, &mut mem_usage, stat.as_mut_ptr(), &mut info);
+	// L and U are only created if the factorization ran to the
+	// end (which it does for singular matrices)
+	if info < 0 || info as usize > ncol + 1 {
+	    return Err(SolveError::from_info(info, ncol).unwrap());
+	}
+	drop(SuperNodeMatrix::<T>::from_raw(l.assume_init()));
+	drop(CompColMatrix::<T>::from_raw(u.assume_init()));
+	if info > 0 && info as usize <= ncol {
+	    return Err(SolveError::Singular(info as usize - 1));
+	}
+	MemoryUsage::from(mem_usage)
+    };
+
+    Ok(ExpertSolution {
+	x,
+	equilibration: Equilibration::from_raw(equed),
+	row_scale: to_f64(&r),
+	col_scale: to_f64(&c),
+	recip_pivot_growth: (raw_options.PivotGrowth == yes_no_t_YES)
+	    .then(|| recip_pivot_growth.into()),
+	rcond: (raw_options.ConditionNumber == yes_no_t_YES).then(|| rcond.into()),
+	ferr: to_f64(&ferr),
+	berr: to_f64(&berr),
+	memory_usage,
+	warning: (info as usize == ncol + 1).then_some(SolveWarning::IllConditioned),
+    })
+}
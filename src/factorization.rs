@@ -0,0 +1,122 @@
+//! LU factors returned by SuperLU
+//!
+//! A SuperLU factorization has the form Pr A Pc = L U, where Pr and
+//! Pc are the row and column permutations. L is stored in supernodal
+//! format and U in compressed-column format; both are allocated by
+//! SuperLU, and freed by Destroy_SuperNode_Matrix and
+//! Destroy_CompCol_Matrix when the Factorization is dropped.
+//!
+//! The factors may belong to the transpose of the matrix that was
+//! passed in (for example, when it was a transposed view), so the
+//! Factorization records which system (Trans) solves the original
+//! problem.
+
+use crate::error::SolveError;
+use crate::options::Trans;
+use crate::simple_driver::conj_in_place;
+use crate::stat::SuperLUStat;
+use crate::super_matrix::{CompColMatrix, Ownership, SuperMatrix, SuperNodeMatrix, DN};
+use crate::value_type::ValueType;
+
+/// The L and U factors, and the permutations, of a matrix
+///
+/// The permutation vectors follow the SuperLU convention:
+/// perm_r[i] = j means row i of A is row j of Pr A, and perm_c[i] = j
+/// means column i of A is column j of A Pc.
+#[derive(Debug)]
+pub struct Factorization<T: ValueType> {
+    l: SuperNodeMatrix<T>,
+    u: CompColMatrix<T>,
+    perm_r: Vec<i32>,
+    perm_c: Vec<i32>,
+    trans: Trans,
+}
+
+impl<T: ValueType> Factorization<T> {
+    pub(crate) fn new(l: SuperNodeMatrix<T>, u: CompColMatrix<T>,
+		      perm_r: Vec<i32>, perm_c: Vec<i32>) -> Self {
+	Self { l, u, perm_r, perm_c, trans: Trans::NoTrans }
+    }
+
+    /// Record that these are the factors of A^T, so that solve uses
+    /// them to solve the transposed (or conjugate-transposed) system
+    pub(crate) fn with_trans(mut self, trans: Trans) -> Self {
+	self.trans = trans;
+	self
+    }
+
+    /// The L factor (unit lower triangular, supernodal storage)
+    pub fn l(&self) -> &SuperNodeMatrix<T> {
+	&self.l
+    }
+
+    /// The U factor (upper triangular). Note that SuperLU stores the
+    /// dense blocks of U, including its diagonal, inside the
+    /// supernodes of L, so this matrix only holds part of U.
+    pub fn u(&self) -> &CompColMatrix<T> {
+	&self.u
+    }
+
+    /// The row permutation
+    pub fn perm_r(&self) -> &[i32] {
+	&self.perm_r
+    }
+
+    /// The column permutation
+    pub fn perm_c(&self) -> &[i32] {
+	&self.perm_c
+    }
+
+    /// The system solved by solve, relative to the stored factors
+    /// (for example, Trans if these are the factors of A^T)
+    pub fn trans(&self) -> Trans {
+	self.trans
+    }
+
+    /// Overwrite each column of B with the solution of the system
+    /// (a wrapper for xgstrs)
+    pub fn solve<O: Ownership>(&self, b: &mut SuperMatrix<DN, T, O>) -> Result<(), SolveError> {
+	self.solve_trans(Trans::NoTrans, b)
+    }
+
+    /// Overwrite each column of B with the solution of the transposed
+    /// system
+    pub fn solve_transpose<O: Ownership>(&self, b: &mut SuperMatrix<DN, T, O>) -> Result<(), SolveError> {
+	self.solve_trans(Trans::Trans, b)
+    }
+
+    /// Overwrite each column of B with the solution of the
+    /// conjugate-transposed system (the same as solve_transpose for
+    /// real matrices)
+    pub fn solve_adjoint<O: Ownership>(&self, b: &mut SuperMatrix<DN, T, O>) -> Result<(), SolveError> {
+	self.solve_trans(Trans::Conj, b)
+    }
+
+    /// Solve with trans applied to the original matrix
+    fn solve_trans<O: Ownership>(&self, trans: Trans,
+				 b: &mut SuperMatrix<DN, T, O>) -> Result<(), SolveError> {
+	assert_eq!(b.nrow(), self.l.nrow(), "B must have one row for each row of A");
+	let (trans, conj) = self.trans.then(trans);
+	if conj {
+	    conj_in_place(b);
+	}
+	// xgstrs takes mutable pointers, but does not change the
+	// factors or the permutations
+	let mut l = *self.l.as_raw();
+	let mut u = *self.u.as_raw();
+	let mut stat = SuperLUStat::new();
+	let mut info = 0;
+	unsafe {
+	    T::c_gstrs(trans.to_raw(), &mut l, &mut u, self.perm_c.as_ptr() as *mut i32,
+		       self.perm_r.as_ptr() as *mut i32, b.as_mut_ptr(), stat.as_mut_ptr(),
+		       &mut info);
+	}
+	if conj {
+	    conj_in_place(b);
+	}
+	match SolveError::from_info(info, self.l.ncol()) {
+	    Some(err) => Err(err),
+	    None => Ok(()),
+	}
+    }
+}
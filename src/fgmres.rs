@@ -0,0 +1,313 @@
+//! Flexible GMRES
+//!
+//! A Rust version of the restarted, right-preconditioned flexible
+//! GMRES in SuperLU's EXAMPLE/dfgmr.c (from Saad's ITSOL), which the
+//! ditersol.c example pairs with the incomplete LU factorization. The
+//! preconditioned vectors z_j = M^-1 v_j are stored, so the
+//! preconditioner may change from one iteration to the next (for
+//! example, an inner iterative solve). The residual estimates, the
+//! stopping tests and the restarts follow dfgmr.c, so the convergence
+//! history is the same as the one printed by the examples. For complex
+//! values, the Givens rotations are the unitary ones (dfgmr.c and its
+//! complex versions only agree for real values).
+
+use num::traits::{Float, NumCast, One, Zero};
+
+use crate::error::SolveError;
+use crate::operator::{LinearOperator, Preconditioner};
+use crate::value_type::ValueType;
+
+/// Why FGMRES stopped
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Termination {
+    /// The residual norm is at most the tolerance times the norm of
+    /// the initial residual (or of b, if the initial guess was
+    /// already good enough)
+    Converged,
+    /// The maximum number of iterations was reached
+    MaxIterations,
+    /// The residual at the end of a restart cycle was not smaller
+    /// than the initial residual
+    Stagnated,
+}
+
+/// The result of an FGMRES solve
+#[derive(Debug, Clone, PartialEq)]
+pub struct FgmresReport {
+    termination: Termination,
+    iterations: usize,
+    history: Vec<f64>,
+    residual_norm: f64,
+}
+
+impl FgmresReport {
+    /// Why the iteration stopped
+    pub fn termination(&self) -> Termination {
+	self.termination
+    }
+
+    /// True if the iteration converged
+    pub fn converged(&self) -> bool {
+	self.termination == Termination::Converged
+    }
+
+    /// Number of iterations (applications of the preconditioner)
+    pub fn iterations(&self) -> usize {
+	self.iterations
+    }
+
+    /// The norm of the initial residual, followed by the residual
+    /// norm estimated at each iteration (what dfgmr.c prints)
+    pub fn history(&self) -> &[f64] {
+	&self.history
+    }
+
+    /// The norm of b - A x for the returned x
+    pub fn residual_norm(&self) -> f64 {
+	self.residual_norm
+    }
+}
+
+/// Restarted flexible GMRES
+///
+/// Starts from the settings used by ditersol.c: a restart length of
+/// 50, a tolerance of 1e-8 and at most 1000 iterations.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Fgmres {
+    restart: usize,
+    tolerance: f64,
+    max_iterations: usize,
+}
+
+impl Default for Fgmres {
+    fn default() -> Self {
+	Self::new()
+    }
+}
+
+/// The inner product (x, y), conjugating x
+fn dot<T: ValueType>(x: &[T], y: &[T]) -> T {
+    x.iter().zip(y).fold(T::zero(), |sum, (&xi, &yi)| sum + xi.conj() * yi)
+}
+
+/// The Euclidean norm
+fn norm<T: ValueType>(x: &[T]) -> T::Real {
+    x.iter().fold(T::Real::zero(), |sum, &xi| sum + xi.abs() * xi.abs()).sqrt()
+}
+
+/// Subtract the projections of w on the (orthonormal) basis vectors,
+/// adding the coefficients to h, and return the norm of what is left
+fn project_out<T: ValueType>(basis: &[Vec<T>], w: &mut [T], h: &mut [T]) -> T::Real {
+    for (v, hj) in basis.iter().zip(h.iter_mut()) {
+	let tt = dot(v, w);
+	*hj += tt;
+	for (wk, &vk) in w.iter_mut().zip(v) {
+	    *wk -= tt * vk;
+	}
+    }
+    norm(w)
+}
+
+/// Overwrite r with b - A x
+fn residual<T: ValueType, A: LinearOperator<T> + ?Sized>(a: &A, x: &[T], b: &[T], r: &mut [T]) {
+    a.apply(x, r);
+    for (ri, &bi) in r.iter_mut().zip(b) {
+	*ri = bi - *ri;
+    }
+}
+
+impl Fgmres {
+    /// The default settings
+    pub fn new() -> Self {
+	Self {
+	    restart: 50,
+	    tolerance: 1e-8,
+	    max_iterations: 1000,
+	}
+    }
+
+    /// Set the dimension of the Krylov subspace built before each
+    /// restart
+    pub fn restart(mut self, restart: usize) -> Self {
+	assert!(restart > 0, "the restart length must be positive");
+	self.restart = restart;
+	self
+    }
+
+    /// Set the tolerance on the residual norm, relative to the
+    /// initial residual
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+	assert!(tolerance >= 0.0, "the tolerance must not be negative");
+	self.tolerance = tolerance;
+	self
+    }
+
+    /// Set the maximum number of iterations
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+	self.max_iterations = max_iterations;
+	self
+    }
+
+    /// Solve A x = b without a preconditioner (plain GMRES)
+    ///
+    /// x holds the initial guess, and is overwritten with the
+    /// solution.
+    pub fn solve<T, A>(&self, a: &A, b: &[T], x: &mut [T]) -> FgmresReport
+    where
+	T: ValueType,
+	A: LinearOperator<T> + ?Sized,
+    {
+	let identity = |v: &[T], z: &mut [T]| z.copy_from_slice(v);
+	// The identity never fails
+	self.solve_preconditioned(a, &identity, b, x).unwrap()
+    }
+
+    /// Solve A x = b, preconditioned on the right by M
+    ///
+    /// x holds the initial guess, and is overwritten with the
+    /// solution. Errors from the preconditioner are returned as they
+    /// are, leaving x at the last restart.
+    pub fn solve_preconditioned<T, A, M>(&self, a: &A, m: &M, b: &[T],
+					 x: &mut [T]) -> Result<FgmresReport, SolveError>
+    where
+	T: ValueType,
+	A: LinearOperator<T> + ?Sized,
+	M: Preconditioner<T> + ?Sized,
+    {
+	let n = b.len();
+	assert_eq!(a.nrows(), a.ncols(), "A must be square");
+	assert_eq!(a.nrows(), n, "b must have one element for each row of A");
+	assert_eq!(x.len(), n, "x must have one element for each column of A");
+	let im = self.restart;
+	let tol: T::Real = NumCast::from(self.tolerance).unwrap();
+	let half: T::Real = NumCast::from(0.5).unwrap();
+
+	// The Arnoldi basis, the preconditioned basis, the Hessenberg
+	// matrix (by columns), the Givens rotations and the rotated
+	// right-hand side of the least squares problem
+	let mut vv = vec![vec![T::zero(); n]; im + 1];
+	let mut z = vec![vec![T::zero(); n]; im];
+	let mut hh = vec![vec![T::zero(); im + 1]; im];
+	let mut c = vec![T::zero(); im];
+	let mut s = vec![T::zero(); im];
+	let mut rs = vec![T::zero(); im + 1];
+
+	let bnorm = norm(b);
+	let mut its = 0;
+	let mut eps1 = T::Real::zero();
+	let mut history = Vec::new();
+	let (termination, residual_norm) = loop {
+	    residual(a, x, b, &mut vv[0]);
+	    let beta = norm(&vv[0]);
+	    if its == 0 {
+		history.push(beta.into());
+	    }
+	    if beta <= tol * bnorm {
+		break (Termination::Converged, beta);
+	    }
+	    let t = T::Real::one() / beta;
+	    for v in vv[0].iter_mut() {
+		*v = v.scale(t);
+	    }
+	    if its == 0 {
+		eps1 = tol * beta;
+	    }
+	    rs[0] = T::from_real(beta);
+
+	    let mut i = 0;
+	    while i < im {
+		its += 1;
+		let i1 = i + 1;
+		// z_i = M^-1 v_i and w = A z_i
+		m.apply(&vv[i], &mut z[i])?;
+		let (basis, rest) = vv.split_at_mut(i1);
+		let w = &mut rest[0];
+		a.apply(&z[i], w);
+
+		// Modified Gram-Schmidt, repeated while the norm of w
+		// drops by more than half
+		let mut t0 = norm(w);
+		hh[i][..i1].fill(T::zero());
+		let mut t = project_out(basis, w, &mut hh[i]);
+		while t < half * t0 {
+		    t0 = t;
+		    t = project_out(basis, w, &mut hh[i]);
+		}
+		hh[i][i1] = T::from_real(t);
+		if t != T::Real::zero() {
+		    let t = T::Real::one() / t;
+		    for wk in w.iter_mut() {
+			*wk = wk.scale(t);
+		    }
+		}
+
+		// Apply the previous rotations to the new column, then
+		// compute the rotation that eliminates h_{i+1,i}
+		for k in 1..=i {
+		    let k1 = k - 1;
+		    let tt = hh[i][k1];
+		    hh[i][k1] = c[k1].conj() * tt + s[k1].conj() * hh[i][k];
+		    hh[i][k] = c[k1] * hh[i][k] - s[k1] * tt;
+		}
+		let gam = (hh[i][i].abs().powi(2) + hh[i][i1].abs().powi(2)).sqrt();
+		if gam == T::Real::zero() {
+		    c[i] = T::one();
+		    s[i] = T::zero();
+		} else {
+		    let scale = T::Real::one() / gam;
+		    c[i] = hh[i][i].scale(scale);
+		    s[i] = hh[i][i1].scale(scale);
+		}
+		rs[i1] = T::zero() - s[i] * rs[i];
+		rs[i] = c[i].conj() * rs[i];
+		hh[i][i] = c[i].conj() * hh[i][i] + s[i].conj() * hh[i][i1];
+
+		let beta = rs[i1].abs();
+		history.push(beta.into());
+		if beta <= eps1 || its >= self.max_iterations {
+		    break;
+		}
+		i += 1;
+	    }
+	    if i == im {
+		i -= 1;
+	    }
+
+	    // Solve the upper triangular system, and update x with the
+	    // preconditioned basis
+	    let diagonal = hh[i][i];
+	    rs[i] /= diagonal;
+	    for k in (0..i).rev() {
+		let mut tt = rs[k];
+		for j in k + 1..=i {
+		    tt -= hh[j][k] * rs[j];
+		}
+		rs[k] = tt / hh[k][k];
+	    }
+	    for (&rj, zj) in rs[..=i].iter().zip(&z) {
+		for (xk, &zk) in x.iter_mut().zip(zj) {
+		    *xk += rj * zk;
+		}
+	    }
+
+	    residual(a, x, b, &mut vv[0]);
+	    let beta = norm(&vv[0]);
+	    // eps1 / tol is the initial residual norm
+	    if beta.is_nan() || beta >= eps1 / tol {
+		break (Termination::Stagnated, beta);
+	    }
+	    if beta <= eps1 {
+		break (Termination::Converged, beta);
+	    }
+	    if its >= self.max_iterations {
+		break (Termination::MaxIterations, beta);
+	    }
+	};
+	Ok(FgmresReport {
+	    termination,
+	    iterations: its,
+	    history,
+	    residual_norm: residual_norm.into(),
+	})
+    }
+}
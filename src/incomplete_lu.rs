@@ -0,0 +1,438 @@
+//! Incomplete LU factorization
+//!
+//! SuperLU includes a supernodal threshold incomplete LU
+//! factorization (ILUTP), computed by the xgsitrf routines and driven
+//! by xgsisx. Entries of L and U are dropped according to the ILU
+//! options, so the factors are much sparser than the exact ones, and
+//! solving with them gives an approximate solution of A x = b. This
+//! is mostly useful as a preconditioner for an iterative method. The
+//! options are described in section 2.4 of the SuperLU manual, and the
+//! method in Li and Shao, "A supernodal approach to incomplete LU
+//! factorization with partial pivoting" (ACM TOMS 37, 2011).
+
+use std::mem::MaybeUninit;
+use std::sync::{Mutex, PoisonError};
+
+use bitflags::bitflags;
+
+use crate::error::SolveError;
+use crate::expert_driver::{Equilibration, MemoryUsage};
+use crate::lu_factorization::scale_rows;
+use crate::options::{yes_no, ColumnOrdering, Trans};
+use crate::simple_driver::{conj_in_place, SystemMatrix};
+use crate::stat::SuperLUStat;
+use crate::super_matrix::{CompColMatrix, DenseMatrix, SuperNodeMatrix};
+use crate::value_type::ValueType;
+use crate::{
+    fact_t_DOFACT, ilu_set_default_options, mem_usage_t, milu_t, milu_t_SILU, milu_t_SMILU_1,
+    milu_t_SMILU_2, milu_t_SMILU_3, norm_t, norm_t_INF_NORM, norm_t_ONE_NORM, norm_t_TWO_NORM,
+    superlu_options_t, GlobalLU_t, SuperMatrix as c_SuperMatrix, DROP_AREA, DROP_BASIC,
+    DROP_COLUMN, DROP_DYNAMIC, DROP_INTERP, DROP_PROWS,
+};
+
+bitflags! {
+    /// The rules used to drop entries of L and U (ILU_DropRule)
+    ///
+    /// PROWS, COLUMN and AREA are mutually exclusive.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub struct DropRule: i32 {
+	/// Drop entries smaller than the drop tolerance, relative to
+	/// their column (ILU(tau))
+	const BASIC = DROP_BASIC as i32;
+	/// Keep at most p = gamma nnz(A) / n rows in each supernode
+	/// (ILUTP(p, tau)), where gamma is the fill factor
+	const PROWS = DROP_PROWS as i32;
+	/// Keep at most p = gamma nnz(A(:, j)) entries in column j
+	const COLUMN = DROP_COLUMN as i32;
+	/// Drop more entries when nnz(F(:, 1:j)) / nnz(A(:, 1:j))
+	/// exceeds the fill factor
+	const AREA = DROP_AREA as i32;
+	/// Adapt the drop tolerance during the factorization
+	const DYNAMIC = DROP_DYNAMIC as i32;
+	/// Compute the secondary dropping threshold by interpolation
+	/// instead of sorting (faster, but the fill factor is not
+	/// guaranteed)
+	const INTERP = DROP_INTERP as i32;
+    }
+}
+
+/// The norm used to measure the rows of a supernode (ILU_Norm)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IluNorm {
+    /// The sum of the absolute values
+    One,
+    /// The Euclidean norm
+    Two,
+    /// The maximum norm (the default)
+    Infinity,
+}
+
+impl IluNorm {
+    fn to_raw(self) -> norm_t {
+	match self {
+	    Self::One => norm_t_ONE_NORM,
+	    Self::Two => norm_t_TWO_NORM,
+	    Self::Infinity => norm_t_INF_NORM,
+	}
+    }
+}
+
+/// Modified ILU variants (ILU_MILU), which add the dropped entries
+/// to the diagonal in different ways
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Milu {
+    /// Ordinary ILU, with no modification (the default)
+    Silu,
+    /// Add the sum of the dropped entries to the diagonal
+    Smilu1,
+    /// Add the sum of the absolute values of the dropped entries to
+    /// the diagonal
+    Smilu2,
+    /// Add the sum of the absolute values of the dropped entries to
+    /// the diagonal, with the sign of the diagonal
+    Smilu3,
+}
+
+impl Milu {
+    fn to_raw(self) -> milu_t {
+	match self {
+	    Self::Silu => milu_t_SILU,
+	    Self::Smilu1 => milu_t_SMILU_1,
+	    Self::Smilu2 => milu_t_SMILU_2,
+	    Self::Smilu3 => milu_t_SMILU_3,
+	}
+    }
+}
+
+/// Options for the incomplete LU factorization
+///
+/// These start from the SuperLU ILU defaults (set by
+/// ilu_set_default_options): BASIC | AREA dropping with a drop
+/// tolerance of 1e-4 and a fill factor of 10, a diagonal pivot
+/// threshold of 0.1, equilibration, and the MC64 row permutation to
+/// put large entries on the diagonal.
+#[derive(Debug, Copy, Clone)]
+pub struct IluOptions {
+    raw: superlu_options_t,
+}
+
+impl Default for IluOptions {
+    fn default() -> Self {
+	Self::new()
+    }
+}
+
+impl IluOptions {
+    /// The SuperLU default ILU options
+    pub fn new() -> Self {
+	let raw = unsafe {
+	    let mut raw = MaybeUninit::<superlu_options_t>::uninit();
+	    ilu_set_default_options(raw.as_mut_ptr());
+	    raw.assume_init()
+	};
+	Self { raw }
+    }
+
+    /// Set the dropping rules (ILU_DropRule)
+    pub fn drop_rule(mut self, rule: DropRule) -> Self {
+	let exclusive = DropRule::PROWS | DropRule::COLUMN | DropRule::AREA;
+	assert!(rule.intersection(exclusive).bits().count_ones() <= 1,
+		"PROWS, COLUMN and AREA are mutually exclusive");
+	self.raw.ILU_DropRule = rule.bits();
+	self
+    }
+
+    /// Set the threshold below which entries are dropped
+    /// (ILU_DropTol). Zero gives the complete factorization.
+    pub fn drop_tol(mut self, tol: f64) -> Self {
+	assert!(tol >= 0.0, "ILU_DropTol must not be negative");
+	self.raw.ILU_DropTol = tol;
+	self
+    }
+
+    /// Set the bound (gamma) on the fill used by the secondary
+    /// dropping rules (ILU_FillFactor)
+    pub fn fill_factor(mut self, gamma: f64) -> Self {
+	assert!(gamma >= 1.0, "ILU_FillFactor must be at least one");
+	self.raw.ILU_FillFactor = gamma;
+	self
+    }
+
+    /// Set the norm used to measure rows (ILU_Norm)
+    pub fn norm(mut self, norm: IluNorm) -> Self {
+	self.raw.ILU_Norm = norm.to_raw();
+	self
+    }
+
+    /// Set the threshold for replacing zero pivots (ILU_FillTol)
+    pub fn fill_tol(mut self, tol: f64) -> Self {
+	self.raw.ILU_FillTol = tol;
+	self
+    }
+
+    /// Choose the modified ILU variant (ILU_MILU)
+    pub fn milu(mut self, milu: Milu) -> Self {
+	self.raw.ILU_MILU = milu.to_raw();
+	self
+    }
+
+    /// Set the column ordering (ColPerm)
+    ///
+    /// Panics if the ordering is not available (see
+    /// ColumnOrdering::is_available).
+    pub fn column_ordering(mut self, ordering: ColumnOrdering) -> Self {
+	assert!(ordering.is_available(), "the {:?} ordering is not available in serial SuperLU", ordering);
+	self.raw.ColPerm = ordering.to_raw();
+	self
+    }
+
+    /// Set the threshold used for diagonal pivoting (DiagPivotThresh)
+    pub fn diag_pivot_thresh(mut self, thresh: f64) -> Self {
+	assert!((0.0..=1.0).contains(&thresh), "DiagPivotThresh must be between 0 and 1");
+	self.raw.DiagPivotThresh = thresh;
+	self
+    }
+
+    /// Scale the rows and columns of A before factorizing (Equil)
+    pub fn equilibrate(mut self, enable: bool) -> Self {
+	self.raw.Equil = yes_no(enable);
+	self
+    }
+
+    /// The underlying superlu_options_t struct
+    pub fn as_raw(&self) -> &superlu_options_t {
+	&self.raw
+    }
+}
+
+/// The amount of fill in an incomplete factorization
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FillStatistics {
+    /// Number of stored entries of A
+    pub nnz_a: usize,
+    /// Number of stored entries of L (including the diagonal
+    /// blocks of the supernodes)
+    pub nnz_l: usize,
+    /// Number of stored entries of U (excluding the diagonal)
+    pub nnz_u: usize,
+    /// Memory used by the factors, and by the factorization
+    pub memory_usage: MemoryUsage,
+}
+
+impl FillStatistics {
+    /// The number of entries of L and U relative to the number of
+    /// entries of A
+    pub fn fill_ratio(&self) -> f64 {
+	(self.nnz_l + self.nnz_u) as f64 / self.nnz_a as f64
+    }
+}
+
+/// Held while xgsisx runs. The row and column dropping routines
+/// (ilu_xdrop_row.c and ilu_xcopy_to_ucol.c) pass the array being
+/// sorted to their qsort comparison function through a static
+/// variable, so two incomplete factorizations must not run at the
+/// same time.
+static ILU_LOCK: Mutex<()> = Mutex::new(());
+
+/// The incomplete LU factorization of a square matrix
+///
+/// The factorization has the form Pr diag(R) A diag(C) Pc ~ L U,
+/// where Pr includes the MC64 row permutation if one was used.
+#[derive(Debug)]
+pub struct IncompleteLu<T: ValueType> {
+    l: SuperNodeMatrix<T>,
+    u: CompColMatrix<T>,
+    perm_c: Vec<i32>,
+    perm_r: Vec<i32>,
+    equilibration: Equilibration,
+    r: Vec<T::Real>,
+    c: Vec<T::Real>,
+    /// The system solved by apply, relative to the factorized copy
+    trans: Trans,
+    zero_pivots: usize,
+    fill: FillStatistics,
+}
+
+impl<T: ValueType> IncompleteLu<T> {
+    /// Compute the incomplete factorization of A (using xgsisx)
+    ///
+    /// A is copied, and is not changed. Zero pivots do not cause an
+    /// error; they are replaced by small values (see
+    /// IluOptions::fill_tol) and counted by zero_pivots.
+    ///
+    /// SuperLU's ILU routines use a static variable, so calls from
+    /// different threads wait for each other (the solves with
+    /// existing factors run in parallel).
+    pub fn new<A: SystemMatrix<T>>(a: &A, options: &IluOptions) -> Result<Self, SolveError> {
+	let (mut a, trans) = a.comp_col_copy();
+	assert_eq!(a.nrow(), a.ncol(), "A must be square");
+	let n = a.ncol();
+	let nnz_a = a.nnz();
+	let mut raw_options = options.raw;
+	raw_options.Fact = fact_t_DOFACT;
+	raw_options.Trans = trans.to_raw();
+	let mut perm_c = vec![0; n];
+	let mut perm_r = vec![0; n];
+	let mut etree = vec![0; n];
+	let mut equed = b'N' as libc::c_char;
+	let mut r = vec![T::Real::default(); n];
+	let mut c = vec![T::Real::default(); n];
+	// No right-hand sides, so xgsisx only factorizes A
+	let mut b = DenseMatrix::<T>::new(n, 0, &[]);
+	let mut x = DenseMatrix::<T>::new(n, 0, &[]);
+	let mut stat = SuperLUStat::new();
+	let (l, u, info, memory_usage) = unsafe {
+	    let mut glu = MaybeUninit::<GlobalLU_t>::zeroed().assume_init();
+	    let mut mem_usage = MaybeUninit::<mem_usage_t>::zeroed().assume_init();
+	    let mut recip_pivot_growth = T::Real::default();
+	    let mut rcond = T::Real::default();
+	    let mut l = MaybeUninit::<c_SuperMatrix>::uninit();
+	    let mut u = MaybeUninit::<c_SuperMatrix>::uninit();
+	    let mut info = 0;
+	    // Nothing is left half-done by a panic while holding the lock
+	    let guard = ILU_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+	    T::c_gsisx(&mut raw_options, a.as_mut_ptr(), perm_c.as_mut_ptr(), perm_r.as_mut_ptr(),
+		       etree.as_mut_ptr(), &mut equed, r.as_mut_ptr(), c.as_mut_ptr(),
+		       l.as_mut_ptr(), u.as_mut_ptr(), std::ptr::null_mut(), 0, b.as_mut_ptr(),
+		       x.as_mut_ptr(), &mut recip_pivot_growth, &mut rcond, &mut glu,
+		       &mut mem_usage, stat.as_mut_ptr(), &mut info);
+	    drop(guard);
+	    // For xgsitrf, 0 < info <= n is the number of zero pivots,
+	    // and L and U are only missing after an error
+	    if info < 0 || info as usize > n {
+		return Err(SolveError::from_info(info, n).unwrap());
+	    }
+	    T::c_ilu_query_space(l.as_mut_ptr(), u.as_mut_ptr(), &mut mem_usage);
+	    let l = SuperNodeMatrix::<T>::from_raw(l.assume_init());
+	    let u = CompColMatrix::<T>::from_raw(u.assume_init());
+	    (l, u, info as usize, MemoryUsage::from(mem_usage))
+	};
+	let fill = FillStatistics {
+	    nnz_a,
+	    nnz_l: l.nnz(),
+	    nnz_u: u.nnz(),
+	    memory_usage,
+	};
+	Ok(Self {
+	    l,
+	    u,
+	    perm_c,
+	    perm_r,
+	    equilibration: Equilibration::from_raw(equed),
+	    r,
+	    c,
+	    trans,
+	    zero_pivots: info,
+	    fill,
+	})
+    }
+
+    /// Number of rows (and columns) of A
+    pub fn n(&self) -> usize {
+	self.l.ncol()
+    }
+
+    /// The L factor
+    pub fn l(&self) -> &SuperNodeMatrix<T> {
+	&self.l
+    }
+
+    /// The U factor
+    pub fn u(&self) -> &CompColMatrix<T> {
+	&self.u
+    }
+
+    /// The row permutation (including the MC64 permutation)
+    pub fn perm_r(&self) -> &[i32] {
+	&self.perm_r
+    }
+
+    /// The column permutation
+    pub fn perm_c(&self) -> &[i32] {
+	&self.perm_c
+    }
+
+    /// The scaling applied to A before it was factorized
+    pub fn equilibration(&self) -> Equilibration {
+	self.equilibration
+    }
+
+    /// The row scale factors R, if the rows were scaled
+    pub fn row_scale(&self) -> Option<&[T::Real]> {
+	self.equilibration.rows().then_some(&self.r[..])
+    }
+
+    /// The column scale factors C, if the columns were scaled
+    pub fn col_scale(&self) -> Option<&[T::Real]> {
+	self.equilibration.cols().then_some(&self.c[..])
+    }
+
+    /// The system solved by apply, relative to the factorized copy
+    pub(crate) fn trans(&self) -> Trans {
+	self.trans
+    }
+
+    /// Number of zero pivots that were replaced during the
+    /// factorization
+    pub fn zero_pivots(&self) -> usize {
+	self.zero_pivots
+    }
+
+    /// The number of entries in the factors, and the memory they use
+    /// (from ilu_xQuerySpace)
+    pub fn fill_statistics(&self) -> FillStatistics {
+	self.fill
+    }
+
+    /// Overwrite x with the approximate solution of A y = x, using
+    /// the incomplete factors
+    pub fn apply(&self, x: &mut [T]) -> Result<(), SolveError> {
+	self.solve(Trans::NoTrans, x)
+    }
+
+    /// Overwrite x with the approximate solution of A^T y = x
+    pub fn apply_transpose(&self, x: &mut [T]) -> Result<(), SolveError> {
+	self.solve(Trans::Trans, x)
+    }
+
+    /// Solve with trans applied to A (as in LuFactorization)
+    fn solve(&self, trans: Trans, x: &mut [T]) -> Result<(), SolveError> {
+	let n = self.n();
+	assert_eq!(x.len(), n, "x must have one element for each row of A");
+	let mut b = DenseMatrix::from_slice(n, 1, x);
+	let (trans, conj) = self.trans.then(trans);
+	if conj {
+	    conj_in_place(&mut b);
+	}
+	// The same scaling as LuFactorization::solve_many
+	let rows = self.equilibration.rows().then_some(&self.r[..]);
+	let cols = self.equilibration.cols().then_some(&self.c[..]);
+	let (before, after) = match trans {
+	    Trans::NoTrans => (rows, cols),
+	    Trans::Trans | Trans::Conj => (cols, rows),
+	};
+	if let Some(scale) = before {
+	    scale_rows(&mut b, scale);
+	}
+	let mut stat = SuperLUStat::new();
+	let mut info = 0;
+	unsafe {
+	    // xgstrs does not change the factors or the permutations
+	    let mut l = *self.l.as_raw();
+	    let mut u = *self.u.as_raw();
+	    T::c_gstrs(trans.to_raw(), &mut l, &mut u, self.perm_c.as_ptr() as *mut i32,
+		       self.perm_r.as_ptr() as *mut i32, b.as_mut_ptr(), stat.as_mut_ptr(),
+		       &mut info);
+	}
+	if let Some(err) = SolveError::from_info(info, n) {
+	    return Err(err);
+	}
+	if let Some(scale) = after {
+	    scale_rows(&mut b, scale);
+	}
+	if conj {
+	    conj_in_place(&mut b);
+	}
+	Ok(())
+    }
+}
@@ -2,8 +2,63 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 
+mod batch;
+mod determinant;
+mod equilibrate;
+mod error;
+mod estimate;
+mod etree;
+mod expert_driver;
+mod factorization;
+mod fgmres;
+mod incomplete_lu;
+mod lu_factorization;
+mod matching;
+mod mixed_precision;
+mod norm;
+mod operator;
+mod options;
+mod ordering;
+mod pivot_growth;
+mod preorder;
+mod refine;
+mod simple_driver;
 mod sp_ienv;
+mod stat;
+pub mod super_matrix;
+mod symbolic;
+mod symmetric;
+mod transpose;
+mod value_type;
 
+pub use batch::{factorize_batch, solve_batch};
+pub use equilibrate::ScaleFactors;
+pub use error::{SolveError, SolveWarning};
+pub use estimate::{analyze, FactorizationEstimate};
+pub use etree::EliminationTree;
+pub use expert_driver::{solve_expert, Equilibration, ExpertSolution, MemoryUsage};
+pub use factorization::Factorization;
+pub use fgmres::{Fgmres, FgmresReport, Termination};
+pub use incomplete_lu::{DropRule, FillStatistics, IluNorm, IluOptions, IncompleteLu, Milu};
+pub use lu_factorization::{factorize, factorize_with_permutation, LuFactorization};
+pub use matching::{large_diagonal_matching, DiagonalMatching, MatchingJob};
+pub use mixed_precision::{DoublePrecision, MixedPrecisionLu, MixedPrecisionReport};
+pub use norm::MatrixNorm;
+pub use operator::{LinearOperator, MatrixFree, Preconditioner};
+pub use options::{ColumnOrdering, Norm, Options, Trans};
+pub use ordering::{column_ordering, Permutation};
+pub use pivot_growth::PivotGrowthPolicy;
+pub use preorder::{preorder, PermutedMatrix};
+pub use refine::RefinementReport;
+pub use simple_driver::{solve, SystemMatrix};
 pub use sp_ienv::{TuningParams, get_tuning_params, set_tuning_params};
+pub use stat::SuperLUStat;
+pub use super_matrix::{
+    Borrowed, CompColMatrix, CompRowMatrix, DenseMatrix, Owned, Ownership, Storage,
+    SuperNodeMatrix, DN, NC, NCP, NR, SC,
+};
+pub use symbolic::SymbolicLu;
+pub use transpose::Transposed;
+pub use value_type::ValueType;
 
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
@@ -0,0 +1,442 @@
+//! Reusable LU factorization
+//!
+//! The factorize function factorizes A once with the expert driver
+//! (xgssvx, with no right-hand sides), and keeps everything needed to
+//! solve with the factors later: L and U, the permutations, the
+//! elimination tree, and the equilibration scale factors. Each solve
+//! calls xgstrs, scaling the right-hand sides and solutions in the
+//! same way as the expert driver. L and U are freed (by
+//! Destroy_SuperNode_Matrix and Destroy_CompCol_Matrix) when the
+//! LuFactorization is dropped.
+
+use std::mem::MaybeUninit;
+use std::sync::OnceLock;
+
+use crate::error::{SolveError, SolveWarning};
+use crate::expert_driver::Equilibration;
+use crate::options::{Norm, Options, Trans};
+use crate::ordering::Permutation;
+use crate::simple_driver::{conj_in_place, SystemMatrix};
+use crate::stat::SuperLUStat;
+use crate::super_matrix::{CompColMatrix, DenseMatrix, Ownership, SuperMatrix, SuperNodeMatrix, DN};
+use crate::value_type::ValueType;
+use crate::{
+    fact_t, fact_t_DOFACT, fact_t_SamePattern_SameRowPerm, mem_usage_t, superlu_options_t,
+    GlobalLU_t, NCformat, SCformat, SuperMatrix as c_SuperMatrix,
+};
+
+/// The LU factorization of a square matrix, for repeated solves
+///
+/// The factorization has the form Pr diag(R) A diag(C) Pc = L U,
+/// where R and C are the equilibration scale factors (both one if A
+/// was not scaled).
+#[derive(Debug)]
+pub struct LuFactorization<T: ValueType> {
+    /// The (scaled) copy of A that was factorized
+    a: CompColMatrix<T>,
+    l: SuperNodeMatrix<T>,
+    u: CompColMatrix<T>,
+    perm_c: Vec<i32>,
+    perm_r: Vec<i32>,
+    etree: Vec<i32>,
+    equilibration: Equilibration,
+    r: Vec<T::Real>,
+    c: Vec<T::Real>,
+    /// The system solved by solve, relative to the factorized copy
+    trans: Trans,
+    /// The SuperLU memory bookkeeping for L and U, which is reused
+    /// by SamePattern_SameRowPerm refactorizations
+    glu: GlobalLU_t,
+    stat: SuperLUStat,
+    warning: Option<SolveWarning>,
+    /// The reciprocal condition numbers of the factorized copy,
+    /// estimated when first asked for (indexed by Norm::One and
+    /// Norm::Inf)
+    rcond: [OnceLock<T::Real>; 2],
+}
+
+// The Glu only points into the arrays of L and U, which move with
+// it, and it is only used by refactorizations (through &mut self).
+// Solves use their own statistics, so they can run in parallel.
+unsafe impl<T: ValueType> Send for LuFactorization<T> {}
+unsafe impl<T: ValueType> Sync for LuFactorization<T> {}
+
+/// Factorize A for repeated solves (using xgssvx)
+///
+/// The options control the column ordering, the pivoting and the
+/// equilibration; the Fact option is ignored. A is copied, and is not
+/// changed. If the condition number is estimated (see
+/// Options::condition_number) and is worse than the machine
+/// precision, the factorization still succeeds, with
+/// SolveWarning::IllConditioned.
+pub fn factorize<T, A>(a: &A, options: &Options) -> Result<LuFactorization<T>, SolveError>
+where
+    T: ValueType,
+    A: SystemMatrix<T>,
+{
+    let (a, trans) = a.comp_col_copy();
+    assert_eq!(a.nrow(), a.ncol(), "A must be square");
+    let n = a.ncol();
+    LuFactorization::compute(a, trans, options, fact_t_DOFACT, vec![0; n], vec![0; n])
+}
+
+/// Factorize A for repeated solves, with the column permutation
+/// perm_c instead of the ordering in the options (ColPerm = MY_PERMC)
+///
+/// As with column_ordering, perm_c permutes the columns of the stored
+/// transpose if A is a compressed-row matrix or a transposed view.
+/// Unless symmetric mode is enabled, SuperLU still postorders the
+/// elimination tree, so the permutation of the factorization may
+/// differ from perm_c. Panics if perm_c does not have one element for
+/// each column of A.
+pub fn factorize_with_permutation<T, A>(a: &A, perm_c: &Permutation,
+					options: &Options) -> Result<LuFactorization<T>, SolveError>
+where
+    T: ValueType,
+    A: SystemMatrix<T>,
+{
+    let (a, trans) = a.comp_col_copy();
+    assert_eq!(a.nrow(), a.ncol(), "A must be square");
+    let n = a.ncol();
+    assert_eq!(perm_c.len(), n, "perm_c must have one element for each column");
+    let options = options.user_column_ordering();
+    LuFactorization::compute(a, trans, &options, fact_t_DOFACT, perm_c.as_slice().to_vec(), vec![0; n])
+}
+
+/// The outputs of xgssvx that describe the factorization
+struct Factored<T: ValueType> {
+    equilibration: Equilibration,
+    r: Vec<T::Real>,
+    c: Vec<T::Real>,
+    warning: Option<SolveWarning>,
+}
+
+/// Call xgssvx with no right-hand sides, so that it only factorizes
+/// A, writing the factors to l and u
+///
+/// # Safety
+///
+/// The options must be valid for a general compressed-column matrix,
+/// and perm_c, etree, l, u and glu must hold whatever the Fact option
+/// requires as input (see the xgssvx documentation).
+#[allow(clippy::too_many_arguments)]
+unsafe fn factor_in_place<T: ValueType>(
+    raw_options: &mut superlu_options_t,
+    a: &mut CompColMatrix<T>,
+    perm_c: &mut [i32],
+    perm_r: &mut [i32],
+    etree: &mut [i32],
+    l: *mut c_SuperMatrix,
+    u: *mut c_SuperMatrix,
+    glu: &mut GlobalLU_t,
+    stat: &mut SuperLUStat,
+) -> (i32, Factored<T>) {
+    let (nrow, ncol) = (a.nrow(), a.ncol());
+    let mut b = DenseMatrix::<T>::new(nrow, 0, &[]);
+    let mut x = DenseMatrix::<T>::new(nrow, 0, &[]);
+    let mut equed = b'N' as libc::c_char;
+    let mut r = vec![T::Real::default(); nrow];
+    let mut c = vec![T::Real::default(); ncol];
+    let mut recip_pivot_growth = T::Real::default();
+    let mut rcond = T::Real::default();
+    let mut mem_usage = MaybeUninit::<mem_usage_t>::zeroed().assume_init();
+    let mut info = 0;
+    T::c_gssvx(raw_options, a.as_mut_ptr(), perm_c.as_mut_ptr(), perm_r.as_mut_ptr(),
+	       etree.as_mut_ptr(), &mut equed, r.as_mut_ptr(), c.as_mut_ptr(), l, u,
+	       std::ptr::null_mut(), 0, b.as_mut_ptr(), x.as_mut_ptr(), &mut recip_pivot_growth,
+	       &mut rcond, std::ptr::null_mut(), std::ptr::null_mut(), glu, &mut mem_usage,
+	       stat.as_mut_ptr(), &mut info);
+    let factored = Factored {
+	equilibration: Equilibration::from_raw(equed),
+	r,
+	c,
+	warning: (info as usize == ncol + 1).then_some(SolveWarning::IllConditioned),
+    };
+    (info, factored)
+}
+
+impl<T: ValueType> LuFactorization<T> {
+    /// Factorize a general compressed-column matrix, with Fact set
+    /// to DOFACT or SamePattern (in which case perm_c and etree must
+    /// come from an earlier analysis of a matrix with the same
+    /// pattern)
+    pub(crate) fn compute(mut a: CompColMatrix<T>, trans: Trans, options: &Options, fact: fact_t,
+			  mut perm_c: Vec<i32>, mut etree: Vec<i32>) -> Result<Self, SolveError> {
+	let n = a.ncol();
+	let mut raw_options = *options.as_raw();
+	raw_options.Fact = fact;
+	raw_options.Trans = trans.to_raw();
+	let mut perm_r = vec![0; n];
+	let mut stat = SuperLUStat::new();
+	let (l, u, glu, factored) = unsafe {
+	    let mut glu = MaybeUninit::<GlobalLU_t>::zeroed().assume_init();
+	    let mut l = MaybeUninit::<c_SuperMatrix>::uninit();
+	    let mut u = MaybeUninit::<c_SuperMatrix>::uninit();
+	    let (info, factored) = factor_in_place(&mut raw_options, &mut a, &mut perm_c,
+						   &mut perm_r, &mut etree, l.as_mut_ptr(),
+						   u.as_mut_ptr(), &mut glu, &mut stat);
+	    // L and U are only created if the factorization ran to the
+	    // end (which it does for singular matrices)
+	    if info < 0 || info as usize > n + 1 {
+		return Err(SolveError::from_info(info, n).unwrap());
+	    }
+	    let l = SuperNodeMatrix::<T>::from_raw(l.assume_init());
+	    let u = CompColMatrix::<T>::from_raw(u.assume_init());
+	    if info > 0 && info as usize <= n {
+		return Err(SolveError::Singular(info as usize - 1));
+	    }
+	    (l, u, glu, factored)
+	};
+	Ok(Self {
+	    a,
+	    l,
+	    u,
+	    perm_c,
+	    perm_r,
+	    etree,
+	    equilibration: factored.equilibration,
+	    r: factored.r,
+	    c: factored.c,
+	    trans,
+	    glu,
+	    stat,
+	    warning: factored.warning,
+	    rcond: Default::default(),
+	})
+    }
+
+    /// Factorize a matrix with the same pattern as the one factorized
+    /// before, reusing the row permutation and the storage of L and U
+    /// (Fact = SamePattern_SameRowPerm)
+    ///
+    /// If this fails, the factors are not usable until the matrix is
+    /// factorized again.
+    pub(crate) fn refactor_same_row_perm(&mut self, mut a: CompColMatrix<T>,
+					 options: &Options) -> Result<(), SolveError> {
+	let n = self.n();
+	let mut raw_options = *options.as_raw();
+	raw_options.Fact = fact_t_SamePattern_SameRowPerm;
+	raw_options.Trans = self.trans.to_raw();
+	let (info, factored) = unsafe {
+	    factor_in_place(&mut raw_options, &mut a, &mut self.perm_c, &mut self.perm_r,
+			    &mut self.etree, self.l.as_mut_ptr(), self.u.as_mut_ptr(),
+			    &mut self.glu, &mut self.stat)
+	};
+	if info < 0 {
+	    return Err(SolveError::from_info(info, n).unwrap());
+	}
+	if info as usize > n + 1 {
+	    // The arrays in the Glu may have been moved by memory
+	    // expansions before the failure, and the stores of L and U
+	    // must point to them, so that they are freed correctly
+	    unsafe {
+		let l_store = &mut *((*self.l.as_mut_ptr()).Store as *mut SCformat);
+		l_store.nzval = self.glu.lusup;
+		l_store.rowind = self.glu.lsub;
+		let u_store = &mut *((*self.u.as_mut_ptr()).Store as *mut NCformat);
+		u_store.nzval = self.glu.ucol;
+		u_store.rowind = self.glu.usub;
+	    }
+	    return Err(SolveError::from_info(info, n).unwrap());
+	}
+	self.a = a;
+	self.equilibration = factored.equilibration;
+	self.r = factored.r;
+	self.c = factored.c;
+	self.warning = factored.warning;
+	self.rcond = Default::default();
+	if info > 0 && info as usize <= n {
+	    return Err(SolveError::Singular(info as usize - 1));
+	}
+	Ok(())
+    }
+}
+
+/// Multiply each row of a dense matrix by a scale factor
+pub(crate) fn scale_rows<T: ValueType, O: Ownership>(b: &mut SuperMatrix<DN, T, O>, scale: &[T::Real]) {
+    let (nrow, lda) = (b.nrow(), b.lda().max(1));
+    for col in b.values_mut().chunks_mut(lda) {
+	for (x, &s) in col[..nrow].iter_mut().zip(scale) {
+	    *x = x.scale(s);
+	}
+    }
+}
+
+impl<T: ValueType> LuFactorization<T> {
+    /// Number of rows (and columns) of A
+    pub fn n(&self) -> usize {
+	self.a.ncol()
+    }
+
+    /// The L factor
+    pub fn l(&self) -> &SuperNodeMatrix<T> {
+	&self.l
+    }
+
+    /// The U factor (see Factorization::u)
+    pub fn u(&self) -> &CompColMatrix<T> {
+	&self.u
+    }
+
+    /// The row permutation
+    pub fn perm_r(&self) -> &[i32] {
+	&self.perm_r
+    }
+
+    /// The column permutation
+    pub fn perm_c(&self) -> &[i32] {
+	&self.perm_c
+    }
+
+    /// The column elimination tree
+    pub fn etree(&self) -> &[i32] {
+	&self.etree
+    }
+
+    /// The scaling applied to A before it was factorized
+    pub fn equilibration(&self) -> Equilibration {
+	self.equilibration
+    }
+
+    /// The row scale factors R, if the rows were scaled
+    pub fn row_scale(&self) -> Option<&[T::Real]> {
+	self.equilibration.rows().then_some(&self.r[..])
+    }
+
+    /// The column scale factors C, if the columns were scaled
+    pub fn col_scale(&self) -> Option<&[T::Real]> {
+	self.equilibration.cols().then_some(&self.c[..])
+    }
+
+    /// The (scaled) copy of A that was factorized
+    pub(crate) fn factorized_matrix(&self) -> &CompColMatrix<T> {
+	&self.a
+    }
+
+    /// The system solved by solve, relative to the factorized copy
+    pub(crate) fn trans(&self) -> Trans {
+	self.trans
+    }
+
+    /// A warning about the accuracy of the factorization, if any
+    pub fn warning(&self) -> Option<SolveWarning> {
+	self.warning
+    }
+
+    /// An estimate of the reciprocal condition number of A in the
+    /// given norm (using xgscon, with the norm of A from xlangs)
+    ///
+    /// If A was equilibrated, this is the condition number of the
+    /// scaled matrix that was factorized, as for the rcond computed by
+    /// xgssvx. The estimate is cached, so only the first call for
+    /// each norm does any work. Panics for Norm::Max and
+    /// Norm::Frobenius, for which xgscon has no estimate.
+    pub fn rcond(&self, norm: Norm) -> T::Real {
+	assert!(matches!(norm, Norm::One | Norm::Inf),
+		"the condition number can only be estimated in the one- or infinity-norm");
+	// The factorized copy holds A^T if the system is transposed
+	let norm = match self.trans {
+	    Trans::NoTrans => norm,
+	    Trans::Trans | Trans::Conj => norm.transposed(),
+	};
+	*self.rcond[norm as usize].get_or_init(|| self.estimate_rcond(norm))
+    }
+
+    /// Estimate the reciprocal condition number of the factorized copy
+    fn estimate_rcond(&self, norm: Norm) -> T::Real {
+	let mut code = norm.to_raw();
+	let mut rcond = T::Real::default();
+	let mut stat = SuperLUStat::new();
+	let mut info = 0;
+	unsafe {
+	    // xlangs and xgscon take mutable pointers, but do not
+	    // change the matrices
+	    let mut a = *self.a.as_raw();
+	    let anorm = T::c_langs(&mut code, &mut a);
+	    let mut l = *self.l.as_raw();
+	    let mut u = *self.u.as_raw();
+	    T::c_gscon(&mut code, &mut l, &mut u, anorm, &mut rcond, stat.as_mut_ptr(), &mut info);
+	}
+	// The arguments are always valid
+	debug_assert_eq!(info, 0, "xgscon failed");
+	rcond
+    }
+
+    /// Overwrite b with the solution of A x = b
+    pub fn solve(&self, b: &mut [T]) -> Result<(), SolveError> {
+	let n = self.n();
+	assert_eq!(b.len(), n, "b must have one element for each row of A");
+	self.solve_many(&mut DenseMatrix::from_slice(n, 1, b))
+    }
+
+    /// Overwrite b with the solution of A^T x = b
+    pub fn solve_transpose(&self, b: &mut [T]) -> Result<(), SolveError> {
+	let n = self.n();
+	assert_eq!(b.len(), n, "b must have one element for each row of A");
+	self.solve_many_trans(Trans::Trans, &mut DenseMatrix::from_slice(n, 1, b))
+    }
+
+    /// Overwrite b with the solution of A^H x = b (the same as
+    /// solve_transpose for real matrices)
+    pub fn solve_adjoint(&self, b: &mut [T]) -> Result<(), SolveError> {
+	let n = self.n();
+	assert_eq!(b.len(), n, "b must have one element for each row of A");
+	self.solve_many_trans(Trans::Conj, &mut DenseMatrix::from_slice(n, 1, b))
+    }
+
+    /// Overwrite each column of B with the solution of A X = B
+    pub fn solve_many<O: Ownership>(&self, b: &mut SuperMatrix<DN, T, O>) -> Result<(), SolveError> {
+	self.solve_many_trans(Trans::NoTrans, b)
+    }
+
+    /// Overwrite each column of B with the solution of A X = B,
+    /// A^T X = B or A^H X = B, depending on trans
+    pub fn solve_many_trans<O: Ownership>(&self, trans: Trans,
+					  b: &mut SuperMatrix<DN, T, O>) -> Result<(), SolveError> {
+	let (trans, conj) = self.trans.then(trans);
+	if conj {
+	    conj_in_place(b);
+	}
+	let result = self.solve_factors(trans, b);
+	if conj {
+	    conj_in_place(b);
+	}
+	result
+    }
+
+    /// Solve using the stored factors, with trans relative to the
+    /// factorized matrix (not to the matrix passed to factorize)
+    fn solve_factors<O: Ownership>(&self, trans: Trans,
+				   b: &mut SuperMatrix<DN, T, O>) -> Result<(), SolveError> {
+	assert_eq!(b.nrow(), self.n(), "B must have one row for each row of A");
+	// The factorized matrix is diag(R) A diag(C), so A x = b is
+	// solved as (diag(R) A diag(C)) y = diag(R) b, x = diag(C) y,
+	// and A^T x = b (or A^H x = b) the other way round
+	let rows = self.equilibration.rows().then_some(&self.r[..]);
+	let cols = self.equilibration.cols().then_some(&self.c[..]);
+	let (before, after) = match trans {
+	    Trans::NoTrans => (rows, cols),
+	    Trans::Trans | Trans::Conj => (cols, rows),
+	};
+	if let Some(scale) = before {
+	    scale_rows(b, scale);
+	}
+	let mut stat = SuperLUStat::new();
+	let mut info = 0;
+	unsafe {
+	    // xgstrs takes mutable pointers, but does not change the
+	    // factors or the permutations
+	    let mut l = *self.l.as_raw();
+	    let mut u = *self.u.as_raw();
+	    T::c_gstrs(trans.to_raw(), &mut l, &mut u, self.perm_c.as_ptr() as *mut i32,
+		       self.perm_r.as_ptr() as *mut i32, b.as_mut_ptr(), stat.as_mut_ptr(), &mut info);
+	}
+	if let Some(err) = SolveError::from_info(info, self.n()) {
+	    return Err(err);
+	}
+	if let Some(scale) = after {
+	    scale_rows(b, scale);
+	}
+	Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+//! Large-diagonal row permutations
+//!
+//! The xldperm routines run the MC64 code from HSL, which finds a row
+//! permutation P such that P A has large entries on its diagonal: a
+//! perfect matching of the rows and columns of A that is optimal for
+//! the chosen MatchingJob. For MatchingJob::MaxProduct they also
+//! compute scale factors R and C such that the diagonal entries of
+//! P diag(R) A diag(C) are one in absolute value, and the other
+//! entries are at most one.
+//!
+//! The incomplete LU driver does this internally (with RowPerm =
+//! LargeDiag_MC64). Doing it directly is useful to preprocess a
+//! matrix before factorizing it, for example to move the zero block
+//! of a saddle-point matrix off the diagonal. MC64 measures a complex
+//! entry by |Re| + |Im|.
+
+use num::traits::{Float, Zero};
+
+use crate::equilibrate::scaled_copy;
+use crate::error::SolveError;
+use crate::ordering::Permutation;
+use crate::super_matrix::{CompColMatrix, Ownership, SuperMatrix, NC};
+use crate::value_type::ValueType;
+
+/// What the matching maximizes (the JOB argument of MC64)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MatchingJob {
+    /// The number of nonzero entries on the diagonal, whatever their
+    /// size
+    MaxCardinality,
+    /// The smallest absolute value on the diagonal
+    MaxSmallest,
+    /// The smallest absolute value on the diagonal, with a different
+    /// algorithm (which may be faster or slower)
+    MaxSmallestAlt,
+    /// The sum of the absolute values on the diagonal
+    MaxSum,
+    /// The product of the absolute values on the diagonal, with row
+    /// and column scale factors
+    MaxProduct,
+}
+
+impl MatchingJob {
+    fn to_raw(self) -> i32 {
+	match self {
+	    Self::MaxCardinality => 1,
+	    Self::MaxSmallest => 2,
+	    Self::MaxSmallestAlt => 3,
+	    Self::MaxSum => 4,
+	    Self::MaxProduct => 5,
+	}
+    }
+}
+
+/// A row permutation putting large entries on the diagonal, and the
+/// scale factors for MatchingJob::MaxProduct
+#[derive(Debug, Clone)]
+pub struct DiagonalMatching<T: ValueType> {
+    perm_r: Permutation,
+    scaling: Option<(Vec<T::Real>, Vec<T::Real>)>,
+}
+
+/// Find a row permutation that puts large entries on the diagonal of
+/// A (using xldperm)
+///
+/// A must be square; a half-stored symmetric or Hermitian matrix is
+/// treated as the full matrix. Returns SolveError::StructurallySingular
+/// if no permutation leaves the diagonal free of structural zeros
+/// (SuperLU prints the unmatched rows in this case). With
+/// MatchingJob::MaxProduct, the scale factors of a badly scaled
+/// matrix may be very large.
+pub fn large_diagonal_matching<T: ValueType, O: Ownership>(
+    a: &SuperMatrix<NC, T, O>,
+    job: MatchingJob,
+) -> Result<DiagonalMatching<T>, SolveError> {
+    if a.is_half_stored() {
+	return large_diagonal_matching(&a.to_general(), job);
+    }
+    assert_eq!(a.nrow(), a.ncol(), "A must be square");
+    let (n, nnz) = (a.ncol(), a.nnz());
+    let max_product = job == MatchingJob::MaxProduct;
+    // MC64 rejects empty matrices, and matrices with no entries
+    if n == 0 {
+	let scaling = max_product.then(|| (Vec::new(), Vec::new()));
+	return Ok(DiagonalMatching { perm_r: Permutation::identity(0), scaling });
+    }
+    if nnz == 0 {
+	return Err(SolveError::StructurallySingular);
+    }
+
+    // xldperm shifts the indices to one-based numbering during the
+    // call, so it gets copies of the arrays
+    let mut colptr = a.colptr().to_vec();
+    let mut rowind = a.rowind()[..nnz].to_vec();
+    let mut values = a.nzval()[..nnz].to_vec();
+    let mut perm = vec![0; n];
+    let mut u = vec![T::Real::zero(); n];
+    let mut v = vec![T::Real::zero(); n];
+    let info = unsafe {
+	T::c_ldperm(job.to_raw(), n as i32, nnz as i32, colptr.as_mut_ptr(), rowind.as_mut_ptr(),
+		    values.as_mut_ptr(), perm.as_mut_ptr(), u.as_mut_ptr(), v.as_mut_ptr())
+    };
+    if info < 0 {
+	// MC64 also rejects repeated row indices in a column
+	return Err(SolveError::IllegalArgument(-info as usize));
+    } else if info == 1 {
+	return Err(SolveError::StructurallySingular);
+    }
+    // MC64 completes the permutation of a singular matrix, and only
+    // reports the singularity if the scale factors were not too large,
+    // so check the diagonal as well
+    let perm_r = Permutation::new(perm).map_err(|_| SolveError::StructurallySingular)?;
+    if !has_full_diagonal(a, perm_r.as_slice()) {
+	return Err(SolveError::StructurallySingular);
+    }
+
+    // xldperm returns the logarithms of the scale factors
+    let scaling = max_product.then(|| {
+	(u.into_iter().map(Float::exp).collect(), v.into_iter().map(Float::exp).collect())
+    });
+    Ok(DiagonalMatching { perm_r, scaling })
+}
+
+/// True if P A has an entry in each diagonal position
+fn has_full_diagonal<T: ValueType, O: Ownership>(a: &SuperMatrix<NC, T, O>, perm_r: &[i32]) -> bool {
+    let (colptr, rowind) = (a.colptr(), a.rowind());
+    (0..a.ncol()).all(|j| {
+	rowind[colptr[j] as usize..colptr[j + 1] as usize].iter().any(|&i| perm_r[i as usize] as usize == j)
+    })
+}
+
+impl<T: ValueType> DiagonalMatching<T> {
+    /// The row permutation (perm_r[i] = j means row i of A is row j
+    /// of P A)
+    pub fn perm_r(&self) -> &Permutation {
+	&self.perm_r
+    }
+
+    /// The row scale factors R, for MatchingJob::MaxProduct
+    pub fn row_scale(&self) -> Option<&[T::Real]> {
+	self.scaling.as_ref().map(|(r, _)| &r[..])
+    }
+
+    /// The column scale factors C, for MatchingJob::MaxProduct
+    pub fn col_scale(&self) -> Option<&[T::Real]> {
+	self.scaling.as_ref().map(|(_, c)| &c[..])
+    }
+
+    /// The permutation, and the row and column scale factors (if any)
+    pub fn into_parts(self) -> (Permutation, Option<(Vec<T::Real>, Vec<T::Real>)>) {
+	(self.perm_r, self.scaling)
+    }
+
+    /// A copy of A with its rows permuted and scaled: P diag(R) A
+    /// diag(C), or P A if there are no scale factors
+    ///
+    /// A may be a different matrix from the one the matching was
+    /// computed from, with the same dimensions. If A X = B, then
+    /// (P diag(R) A diag(C)) Y = P diag(R) B with X = diag(C) Y. The
+    /// row indices of each column are left in the permuted order,
+    /// which SuperLU accepts.
+    pub fn apply<O: Ownership>(&self, a: &SuperMatrix<NC, T, O>) -> CompColMatrix<T> {
+	let n = self.perm_r.len();
+	assert_eq!((a.nrow(), a.ncol()), (n, n), "matrix has the wrong dimensions");
+	let a = a.to_general();
+	let scaled = scaled_copy(&a, self.row_scale(), self.col_scale());
+	let perm = self.perm_r.as_slice();
+	let rowind: Vec<i32> = scaled.rowind().iter().map(|&i| perm[i as usize]).collect();
+	CompColMatrix::new(n, n, scaled.nzval(), &rowind, scaled.colptr())
+    }
+}
@@ -0,0 +1,305 @@
+//! Mixed-precision solves
+//!
+//! MixedPrecisionLu factorizes A in single precision (the expert
+//! driver calls sgstrf or cgstrf), so the factors take about half the
+//! memory of double-precision ones, and recovers double precision by
+//! iterative refinement, as in LAPACK's dsgesv and zcgesv. The
+//! residual r = b - A x is computed in double precision (with
+//! sp_dgemv or sp_zgemv), and the correction is found by solving
+//! A d = r with the single-precision factors. This converges when A
+//! is not too badly conditioned for single precision (roughly, while
+//! cond(A) is well below 1e7). If the refinement stalls, or if A
+//! cannot be factorized in single precision at all, A is factorized
+//! again in double precision, and the double factors are used from
+//! then on.
+
+use num::Complex;
+
+use crate::error::SolveError;
+use crate::fact_t_DOFACT;
+use crate::lu_factorization::LuFactorization;
+use crate::operator::sp_gemv;
+use crate::options::{Options, Trans};
+use crate::simple_driver::SystemMatrix;
+use crate::super_matrix::CompColMatrix;
+use crate::value_type::ValueType;
+
+/// Double-precision value types, which can be factorized in single
+/// precision
+///
+/// This is implemented for f64 (factorized as f32) and Complex<f64>
+/// (factorized as Complex<f32>).
+pub trait DoublePrecision: ValueType<Real = f64> {
+    /// The single-precision type used for the factors
+    type Single: ValueType<Real = f32>;
+
+    /// Round to single precision
+    fn to_single(self) -> Self::Single;
+
+    /// Convert from single precision (this is exact)
+    fn from_single(x: Self::Single) -> Self;
+}
+
+impl DoublePrecision for f64 {
+    type Single = f32;
+
+    fn to_single(self) -> f32 {
+	self as f32
+    }
+
+    fn from_single(x: f32) -> Self {
+	x as f64
+    }
+}
+
+impl DoublePrecision for Complex<f64> {
+    type Single = Complex<f32>;
+
+    fn to_single(self) -> Complex<f32> {
+	Complex::new(self.re as f32, self.im as f32)
+    }
+
+    fn from_single(x: Complex<f32>) -> Self {
+	Complex::new(x.re as f64, x.im as f64)
+    }
+}
+
+/// The factors currently used for solves
+#[derive(Debug)]
+enum Factors<T: DoublePrecision> {
+    Single(LuFactorization<T::Single>),
+    Double(LuFactorization<T>),
+}
+
+/// The result of a mixed-precision solve
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MixedPrecisionReport {
+    iterations: usize,
+    backward_error: f64,
+    double_precision: bool,
+}
+
+impl MixedPrecisionReport {
+    /// Number of refinement steps after the first solve (zero if the
+    /// double-precision factors were used)
+    pub fn iterations(&self) -> usize {
+	self.iterations
+    }
+
+    /// The componentwise backward error of the solution,
+    /// max_i |b - A x|_i / (|A| |x| + |b|)_i
+    pub fn backward_error(&self) -> f64 {
+	self.backward_error
+    }
+
+    /// True if the solution came from double-precision factors
+    /// (because the refinement stalled, in this solve or an earlier
+    /// one, or A could not be factorized in single precision)
+    pub fn used_double_precision(&self) -> bool {
+	self.double_precision
+    }
+}
+
+/// The single-precision LU factorization of a double-precision
+/// matrix, for solves refined to double precision
+///
+/// Starts from the settings of dsgesv: at most 30 refinement steps,
+/// stopping when the backward error is at most sqrt(n) times the
+/// double-precision machine epsilon. Each step must at least halve
+/// the backward error, otherwise the refinement has stalled.
+#[derive(Debug)]
+pub struct MixedPrecisionLu<T: DoublePrecision> {
+    /// A general compressed-column copy of A (in double precision),
+    /// for computing residuals
+    a: CompColMatrix<T>,
+    /// The system solved, relative to the copy
+    trans: Trans,
+    options: Options,
+    factors: Factors<T>,
+    max_iterations: usize,
+    tolerance: f64,
+}
+
+impl<T: DoublePrecision> MixedPrecisionLu<T> {
+    /// Factorize A in single precision (or in double precision, if
+    /// that fails)
+    ///
+    /// A is copied, and is not changed. An error is only returned if
+    /// the double-precision factorization fails too.
+    pub fn new<A: SystemMatrix<T>>(a: &A, options: &Options) -> Result<Self, SolveError> {
+	let (a, trans) = a.comp_col_copy();
+	assert_eq!(a.nrow(), a.ncol(), "A must be square");
+	let factors = match factorize_single(&a, trans, options) {
+	    Some(lu) => Factors::Single(lu),
+	    None => Factors::Double(factorize_double(&a, trans, options)?),
+	};
+	Ok(Self {
+	    tolerance: f64::EPSILON * (a.ncol() as f64).sqrt().max(1.0),
+	    a,
+	    trans,
+	    options: *options,
+	    factors,
+	    max_iterations: 30,
+	})
+    }
+
+    /// Set the maximum number of refinement steps
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+	self.max_iterations = max_iterations;
+	self
+    }
+
+    /// Set the backward error at which the refinement stops
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+	assert!(tolerance >= 0.0, "the tolerance must not be negative");
+	self.tolerance = tolerance;
+	self
+    }
+
+    /// Number of rows (and columns) of A
+    pub fn n(&self) -> usize {
+	self.a.ncol()
+    }
+
+    /// True while the single-precision factors are used
+    pub fn is_single_precision(&self) -> bool {
+	matches!(self.factors, Factors::Single(_))
+    }
+
+    /// The reciprocal pivot growth factor of the factors currently
+    /// used (see LuFactorization::pivot_growth)
+    pub fn pivot_growth(&self) -> f64 {
+	match &self.factors {
+	    Factors::Single(lu) => lu.pivot_growth().into(),
+	    Factors::Double(lu) => lu.pivot_growth(),
+	}
+    }
+
+    /// Overwrite b with the solution of A x = b
+    ///
+    /// If the refinement stalls, A is factorized in double precision
+    /// (once), and b is solved again with the new factors. b is only
+    /// changed if the solve succeeds.
+    pub fn solve(&mut self, b: &mut [T]) -> Result<MixedPrecisionReport, SolveError> {
+	assert_eq!(b.len(), self.n(), "b must have one element for each row of A");
+	if let Factors::Single(lu) = &self.factors {
+	    if let Some((x, report)) = self.refine(lu, b)? {
+		b.copy_from_slice(&x);
+		return Ok(report);
+	    }
+	    self.factors = Factors::Double(factorize_double(&self.a, self.trans, &self.options)?);
+	}
+	let Factors::Double(lu) = &self.factors else {
+	    unreachable!("the single-precision factors were replaced above");
+	};
+	let mut x = b.to_vec();
+	lu.solve(&mut x)?;
+	let mut r = vec![T::zero(); x.len()];
+	self.residual(&x, b, &mut r);
+	let report = MixedPrecisionReport {
+	    iterations: 0,
+	    backward_error: self.backward_error(&x, b, &r),
+	    double_precision: true,
+	};
+	b.copy_from_slice(&x);
+	Ok(report)
+    }
+
+    /// Solve with the single-precision factors, and refine the
+    /// solution. Returns None if the refinement stalls.
+    fn refine(&self, lu: &LuFactorization<T::Single>,
+	      b: &[T]) -> Result<Option<(Vec<T>, MixedPrecisionReport)>, SolveError> {
+	let n = b.len();
+	let mut x = vec![T::zero(); n];
+	let mut r = b.to_vec();
+	let mut d = vec![T::Single::zero(); n];
+	let mut last_error = f64::INFINITY;
+	// The first pass is the plain single-precision solve
+	for iterations in 0..=self.max_iterations {
+	    for (di, &ri) in d.iter_mut().zip(&r) {
+		*di = ri.to_single();
+	    }
+	    lu.solve(&mut d)?;
+	    for (xi, &di) in x.iter_mut().zip(&d) {
+		*xi += T::from_single(di);
+	    }
+	    self.residual(&x, b, &mut r);
+	    let error = self.backward_error(&x, b, &r);
+	    if error <= self.tolerance {
+		let report = MixedPrecisionReport {
+		    iterations,
+		    backward_error: error,
+		    double_precision: false,
+		};
+		return Ok(Some((x, report)));
+	    }
+	    if error.is_nan() || error > 0.5 * last_error {
+		return Ok(None);
+	    }
+	    last_error = error;
+	}
+	Ok(None)
+    }
+
+    /// Overwrite r with b - A x, in double precision
+    fn residual(&self, x: &[T], b: &[T], r: &mut [T]) {
+	let trans = match self.trans {
+	    Trans::NoTrans => b'N',
+	    Trans::Trans => b'T',
+	    Trans::Conj => b'C',
+	};
+	sp_gemv(&self.a, trans, x, r);
+	for (ri, &bi) in r.iter_mut().zip(b) {
+	    *ri = bi - *ri;
+	}
+    }
+
+    /// The componentwise backward error max_i |r_i| / (|A| |x| + |b|)_i
+    fn backward_error(&self, x: &[T], b: &[T], r: &[T]) -> f64 {
+	let (nzval, rowind, colptr) = (self.a.nzval(), self.a.rowind(), self.a.colptr());
+	let mut w: Vec<f64> = b.iter().map(|bi| bi.abs()).collect();
+	for j in 0..self.n() {
+	    for k in colptr[j] as usize..colptr[j + 1] as usize {
+		let i = rowind[k] as usize;
+		// The copy holds A^T if the system is transposed
+		match self.trans {
+		    Trans::NoTrans => w[i] += nzval[k].abs() * x[j].abs(),
+		    Trans::Trans | Trans::Conj => w[j] += nzval[k].abs() * x[i].abs(),
+		}
+	    }
+	}
+	r.iter().zip(&w).map(|(ri, &wi)| {
+	    let ri = ri.abs();
+	    if ri == 0.0 {
+		0.0
+	    } else if wi > 0.0 {
+		ri / wi
+	    } else {
+		f64::INFINITY
+	    }
+	}).fold(0.0, f64::max)
+    }
+}
+
+/// Factorize a single-precision copy of A, returning None if any
+/// value is too large for single precision or the factorization fails
+fn factorize_single<T: DoublePrecision>(a: &CompColMatrix<T>, trans: Trans,
+					options: &Options) -> Option<LuFactorization<T::Single>> {
+    let values: Vec<T::Single> = a.nzval().iter().map(|&v| v.to_single()).collect();
+    if values.iter().any(|v| !v.abs().is_finite()) {
+	return None;
+    }
+    let n = a.ncol();
+    let single = CompColMatrix::new(n, n, &values, a.rowind(), a.colptr());
+    LuFactorization::compute(single, trans, options, fact_t_DOFACT, vec![0; n], vec![0; n]).ok()
+}
+
+/// Factorize A in double precision
+fn factorize_double<T: DoublePrecision>(a: &CompColMatrix<T>, trans: Trans,
+					options: &Options) -> Result<LuFactorization<T>, SolveError> {
+    // A is already general, but it is kept for the residuals, so the
+    // factorization (which scales its matrix in place) needs a copy
+    let n = a.ncol();
+    let copy = CompColMatrix::new(n, n, a.nzval(), a.rowind(), a.colptr());
+    LuFactorization::compute(copy, trans, options, fact_t_DOFACT, vec![0; n], vec![0; n])
+}
@@ -0,0 +1,98 @@
+//! Matrix norms
+//!
+//! The xlangs routines compute the one, infinity and max-abs norms of
+//! a compressed-column matrix (they stop the program if asked for the
+//! Frobenius norm, which they do not implement). MatrixNorm uses them
+//! for compressed-column matrices, and for compressed-row matrices and
+//! transposed views through the compressed-column matrix holding the
+//! transpose (whose one-norm is the infinity-norm of the original, and
+//! the other way round). The Frobenius norm and the norms of dense
+//! matrices are computed in Rust.
+
+use num::traits::{Float, One, Zero};
+
+use crate::options::Norm;
+use crate::super_matrix::{Ownership, SuperMatrix, DN, NC, NR};
+use crate::transpose::Transposed;
+use crate::value_type::ValueType;
+
+/// Matrices whose norms can be computed
+pub trait MatrixNorm<T: ValueType> {
+    /// The norm of the matrix (zero for an empty matrix)
+    fn norm(&self, norm: Norm) -> T::Real;
+}
+
+/// The Frobenius norm of the values, accumulated as in LAPACK's
+/// xlassq so that the squares do not overflow
+fn frobenius<T: ValueType>(values: impl Iterator<Item = T>) -> T::Real {
+    let mut scale = T::Real::zero();
+    let mut ssq = T::Real::one();
+    for v in values {
+	let a = v.abs();
+	if a == T::Real::zero() {
+	    continue;
+	}
+	if scale < a {
+	    ssq = T::Real::one() + ssq * (scale / a).powi(2);
+	    scale = a;
+	} else {
+	    ssq = ssq + (a / scale).powi(2);
+	}
+    }
+    scale * ssq.sqrt()
+}
+
+impl<T: ValueType, O: Ownership> MatrixNorm<T> for SuperMatrix<NC, T, O> {
+    /// Computed with xlangs, except for the Frobenius norm. A
+    /// half-stored symmetric or Hermitian matrix is expanded first.
+    fn norm(&self, norm: Norm) -> T::Real {
+	if self.is_half_stored() {
+	    return self.to_general().norm(norm);
+	}
+	if norm == Norm::Frobenius {
+	    return frobenius(self.nzval().iter().copied());
+	}
+	let mut code = norm.to_raw();
+	// xlangs takes a mutable pointer, but does not change A
+	let mut raw = *self.as_raw();
+	unsafe { T::c_langs(&mut code, &mut raw) }
+    }
+}
+
+impl<T: ValueType, O: Ownership> MatrixNorm<T> for SuperMatrix<NR, T, O> {
+    fn norm(&self, norm: Norm) -> T::Real {
+	self.reinterpret_cols().norm(norm.transposed())
+    }
+}
+
+impl<T: ValueType, O: Ownership> MatrixNorm<T> for SuperMatrix<DN, T, O> {
+    fn norm(&self, norm: Norm) -> T::Real {
+	let nrow = self.nrow();
+	let columns = self.values().chunks(self.lda().max(1)).map(|col| &col[..nrow]);
+	let max = |x: T::Real, y: T::Real| x.max(y);
+	match norm {
+	    Norm::One => columns
+		.map(|col| col.iter().fold(T::Real::zero(), |sum, v| sum + v.abs()))
+		.fold(T::Real::zero(), max),
+	    Norm::Inf => {
+		let mut sums = vec![T::Real::zero(); nrow];
+		for col in columns {
+		    for (sum, v) in sums.iter_mut().zip(col) {
+			*sum = *sum + v.abs();
+		    }
+		}
+		sums.into_iter().fold(T::Real::zero(), max)
+	    }
+	    Norm::Max => columns.flatten().fold(T::Real::zero(), |m, v| m.max(v.abs())),
+	    Norm::Frobenius => frobenius(columns.flatten().copied()),
+	}
+    }
+}
+
+impl<'a, T: ValueType> MatrixNorm<T> for Transposed<'a, T> {
+    /// The norms do not depend on conjugation, so this is the same
+    /// for transpose and adjoint views
+    fn norm(&self, norm: Norm) -> T::Real {
+	self.matrix().norm(norm)
+    }
+}
@@ -0,0 +1,225 @@
+//! Operators for iterative solvers
+//!
+//! The iterative solvers in this crate only multiply by A and apply a
+//! preconditioner, so they are written in terms of the LinearOperator
+//! and Preconditioner traits instead of a particular matrix type, and
+//! code outside the crate can use the same traits. A compressed-column
+//! matrix is a LinearOperator (multiplying with sp_xgemv), and
+//! MatrixFree wraps closures for operators that are never stored.
+//!
+//! The factorizations (LuFactorization and Factorization, solving with
+//! xgstrs, and IncompleteLu) are both: as a LinearOperator they apply
+//! the (approximate) inverse of the factorized matrix, and as a
+//! Preconditioner they are M = L U. Any closure computing z = M^-1 x
+//! is also a Preconditioner.
+
+use crate::error::SolveError;
+use crate::factorization::Factorization;
+use crate::incomplete_lu::IncompleteLu;
+use crate::lu_factorization::LuFactorization;
+use crate::super_matrix::{DenseMatrix, Ownership, SuperMatrix, NC};
+use crate::value_type::ValueType;
+
+/// A linear map y = A x
+pub trait LinearOperator<T: ValueType> {
+    /// Number of rows of A (the length of y)
+    fn nrows(&self) -> usize;
+
+    /// Number of columns of A (the length of x)
+    fn ncols(&self) -> usize;
+
+    /// Overwrite y with A x
+    fn apply(&self, x: &[T], y: &mut [T]);
+
+    /// Overwrite y with A^T x (the transpose, without conjugation)
+    fn apply_transpose(&self, x: &[T], y: &mut [T]);
+}
+
+/// An approximation M of a square matrix A, applied as z = M^-1 x
+pub trait Preconditioner<T: ValueType> {
+    /// Overwrite z with M^-1 x
+    fn apply(&self, x: &[T], z: &mut [T]) -> Result<(), SolveError>;
+}
+
+impl<T: ValueType, O: Ownership> LinearOperator<T> for SuperMatrix<NC, T, O> {
+    fn nrows(&self) -> usize {
+	self.nrow()
+    }
+
+    fn ncols(&self) -> usize {
+	self.ncol()
+    }
+
+    /// Multiply using sp_xgemv
+    ///
+    /// A must be stored in full (see to_general), otherwise this
+    /// panics.
+    fn apply(&self, x: &[T], y: &mut [T]) {
+	sp_gemv(self, b'N', x, y);
+    }
+
+    /// Multiply by the transpose using sp_xgemv
+    fn apply_transpose(&self, x: &[T], y: &mut [T]) {
+	sp_gemv(self, b'T', x, y);
+    }
+}
+
+/// Overwrite y with op(A) x, where op is given by trans ('N', 'T' or
+/// 'C')
+pub(crate) fn sp_gemv<T: ValueType, O: Ownership>(a: &SuperMatrix<NC, T, O>, trans: u8, x: &[T], y: &mut [T]) {
+    assert!(!a.is_half_stored(), "apply needs a matrix stored in full (see to_general)");
+    let (nrow, ncol) = match trans {
+	b'N' => (a.nrow(), a.ncol()),
+	_ => (a.ncol(), a.nrow()),
+    };
+    assert_eq!(x.len(), ncol, "x must have one element for each column of op(A)");
+    assert_eq!(y.len(), nrow, "y must have one element for each row of op(A)");
+    // sp_xgemv takes a mutable pointer, but does not change A
+    let mut raw = *a.as_raw();
+    let mut trans = trans as libc::c_char;
+    unsafe {
+	T::c_sp_gemv(&mut trans, T::one(), &mut raw, x.as_ptr(), 1, T::zero(), y.as_mut_ptr(), 1);
+    }
+}
+
+/// A linear operator given by closures, which overwrite y with A x
+/// and with A^T x
+#[derive(Debug, Copy, Clone)]
+pub struct MatrixFree<F, G> {
+    nrows: usize,
+    ncols: usize,
+    apply: F,
+    apply_transpose: G,
+}
+
+impl<F, G> MatrixFree<F, G> {
+    /// The operator with the given dimensions that computes y = A x
+    /// by calling apply(x, y), and y = A^T x by calling
+    /// apply_transpose(x, y)
+    pub fn new(nrows: usize, ncols: usize, apply: F, apply_transpose: G) -> Self {
+	Self { nrows, ncols, apply, apply_transpose }
+    }
+}
+
+impl<T, F, G> LinearOperator<T> for MatrixFree<F, G>
+where
+    T: ValueType,
+    F: Fn(&[T], &mut [T]),
+    G: Fn(&[T], &mut [T]),
+{
+    fn nrows(&self) -> usize {
+	self.nrows
+    }
+
+    fn ncols(&self) -> usize {
+	self.ncols
+    }
+
+    fn apply(&self, x: &[T], y: &mut [T]) {
+	(self.apply)(x, y)
+    }
+
+    fn apply_transpose(&self, x: &[T], y: &mut [T]) {
+	(self.apply_transpose)(x, y)
+    }
+}
+
+impl<T: ValueType> LinearOperator<T> for LuFactorization<T> {
+    fn nrows(&self) -> usize {
+	self.n()
+    }
+
+    fn ncols(&self) -> usize {
+	self.n()
+    }
+
+    /// Overwrite y with A^-1 x (solving with xgstrs)
+    fn apply(&self, x: &[T], y: &mut [T]) {
+	y.copy_from_slice(x);
+	// Solves only fail for invalid arguments, which are checked
+	// before calling xgstrs
+	LuFactorization::solve(self, y).expect("xgstrs failed");
+    }
+
+    /// Overwrite y with A^-T x
+    fn apply_transpose(&self, x: &[T], y: &mut [T]) {
+	y.copy_from_slice(x);
+	LuFactorization::solve_transpose(self, y).expect("xgstrs failed");
+    }
+}
+
+impl<T: ValueType> Preconditioner<T> for LuFactorization<T> {
+    fn apply(&self, x: &[T], z: &mut [T]) -> Result<(), SolveError> {
+	z.copy_from_slice(x);
+	LuFactorization::solve(self, z)
+    }
+}
+
+impl<T: ValueType> LinearOperator<T> for Factorization<T> {
+    fn nrows(&self) -> usize {
+	self.l().nrow()
+    }
+
+    fn ncols(&self) -> usize {
+	self.l().nrow()
+    }
+
+    /// Overwrite y with A^-1 x (solving with xgstrs)
+    fn apply(&self, x: &[T], y: &mut [T]) {
+	y.copy_from_slice(x);
+	// Solves only fail for invalid arguments, which are checked
+	// before calling xgstrs
+	Factorization::solve(self, &mut DenseMatrix::from_slice(y.len(), 1, y)).expect("xgstrs failed");
+    }
+
+    /// Overwrite y with A^-T x
+    fn apply_transpose(&self, x: &[T], y: &mut [T]) {
+	y.copy_from_slice(x);
+	Factorization::solve_transpose(self, &mut DenseMatrix::from_slice(y.len(), 1, y)).expect("xgstrs failed");
+    }
+}
+
+impl<T: ValueType> Preconditioner<T> for Factorization<T> {
+    fn apply(&self, x: &[T], z: &mut [T]) -> Result<(), SolveError> {
+	z.copy_from_slice(x);
+	Factorization::solve(self, &mut DenseMatrix::from_slice(z.len(), 1, z))
+    }
+}
+
+impl<T: ValueType> LinearOperator<T> for IncompleteLu<T> {
+    fn nrows(&self) -> usize {
+	self.n()
+    }
+
+    fn ncols(&self) -> usize {
+	self.n()
+    }
+
+    /// Overwrite y with A^-1 x (solving with the incomplete factors)
+    fn apply(&self, x: &[T], y: &mut [T]) {
+	y.copy_from_slice(x);
+	// Solves only fail for invalid arguments, which are checked
+	// before calling xgstrs
+	IncompleteLu::apply(self, y).expect("xgstrs failed");
+    }
+
+    /// Overwrite y with A^-T x
+    fn apply_transpose(&self, x: &[T], y: &mut [T]) {
+	y.copy_from_slice(x);
+	IncompleteLu::apply_transpose(self, y).expect("xgstrs failed");
+    }
+}
+
+impl<T: ValueType> Preconditioner<T> for IncompleteLu<T> {
+    fn apply(&self, x: &[T], z: &mut [T]) -> Result<(), SolveError> {
+	z.copy_from_slice(x);
+	IncompleteLu::apply(self, z)
+    }
+}
+
+impl<T: ValueType, F: Fn(&[T], &mut [T])> Preconditioner<T> for F {
+    fn apply(&self, x: &[T], z: &mut [T]) -> Result<(), SolveError> {
+	self(x, z);
+	Ok(())
+    }
+}
@@ -0,0 +1,248 @@
+//! Solver options
+//!
+//! The superlu_options_t struct controls how the drivers factorize
+//! and solve a system. It is documented in section 2.4 of the SuperLU
+//! manual. Options starts from the SuperLU defaults (set by
+//! set_default_options), and the builder methods change one setting
+//! at a time.
+
+use std::mem::MaybeUninit;
+
+use crate::super_matrix::{Ownership, SuperMatrix, NC};
+use crate::value_type::ValueType;
+use crate::{
+    colperm_t, colperm_t_COLAMD, colperm_t_METIS_AT_PLUS_A, colperm_t_MMD_ATA,
+    colperm_t_MMD_AT_PLUS_A, colperm_t_MY_PERMC, colperm_t_NATURAL, colperm_t_PARMETIS,
+    colperm_t_ZOLTAN, set_default_options, superlu_options_t, trans_t, trans_t_CONJ,
+    trans_t_NOTRANS, trans_t_TRANS, yes_no_t, yes_no_t_NO, yes_no_t_YES,
+    IterRefine_t_NOREFINE, IterRefine_t_SLU_DOUBLE,
+    Mtype_t_SLU_HEL, Mtype_t_SLU_HEU, Mtype_t_SLU_SYL, Mtype_t_SLU_SYU,
+};
+
+/// Fill-reducing ordering applied to the columns of the matrix
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColumnOrdering {
+    /// Use the natural ordering (no permutation)
+    Natural,
+    /// Minimum degree ordering on the structure of A^T A
+    MmdAtA,
+    /// Minimum degree ordering on the structure of A^T + A
+    MmdAtPlusA,
+    /// Approximate minimum degree column ordering (the default)
+    Colamd,
+    /// METIS ordering on the structure of A^T + A (not available in
+    /// the serial library)
+    MetisAtPlusA,
+    /// ParMETIS ordering (not available in the serial library)
+    ParMetis,
+    /// Zoltan ordering (not available in the serial library)
+    Zoltan,
+}
+
+impl ColumnOrdering {
+    pub(crate) fn to_raw(self) -> colperm_t {
+	match self {
+	    Self::Natural => colperm_t_NATURAL,
+	    Self::MmdAtA => colperm_t_MMD_ATA,
+	    Self::MmdAtPlusA => colperm_t_MMD_AT_PLUS_A,
+	    Self::Colamd => colperm_t_COLAMD,
+	    Self::MetisAtPlusA => colperm_t_METIS_AT_PLUS_A,
+	    Self::ParMetis => colperm_t_PARMETIS,
+	    Self::Zoltan => colperm_t_ZOLTAN,
+	}
+    }
+
+    /// True if get_perm_c implements this ordering (the others are
+    /// only implemented by the parallel versions of SuperLU, and the
+    /// serial library stops the program if asked for them)
+    pub fn is_available(self) -> bool {
+	matches!(self, Self::Natural | Self::MmdAtA | Self::MmdAtPlusA | Self::Colamd)
+    }
+}
+
+/// Which system to solve with the factors of A
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Trans {
+    /// Solve A x = b
+    NoTrans,
+    /// Solve A^T x = b
+    Trans,
+    /// Solve A^H x = b (the same as Trans for real matrices)
+    Conj,
+}
+
+impl Trans {
+    pub(crate) fn to_raw(self) -> trans_t {
+	match self {
+	    Self::NoTrans => trans_t_NOTRANS,
+	    Self::Trans => trans_t_TRANS,
+	    Self::Conj => trans_t_CONJ,
+	}
+    }
+
+    /// Combine a stored trans (the system that gives the original
+    /// matrix, relative to the factorized matrix M) with an operation
+    /// on the original matrix. Returns the trans to use with the
+    /// factors, and whether the result is conj(M), which must be
+    /// solved by conjugating the right-hand side and the solution.
+    pub(crate) fn then(self, op: Trans) -> (Trans, bool) {
+	match (self, op) {
+	    (Self::NoTrans, op) => (op, false),
+	    (stored, Self::NoTrans) => (stored, false),
+	    // (M^T)^T = M and (M^H)^H = M
+	    (Self::Trans, Self::Trans) | (Self::Conj, Self::Conj) => (Self::NoTrans, false),
+	    // (M^T)^H = (M^H)^T = conj(M)
+	    (Self::Trans, Self::Conj) | (Self::Conj, Self::Trans) => (Self::NoTrans, true),
+	}
+    }
+}
+
+/// A matrix norm
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Norm {
+    /// The one-norm (the largest column sum of absolute values)
+    One,
+    /// The infinity-norm (the largest row sum of absolute values)
+    Inf,
+    /// The largest absolute value (not a consistent matrix norm)
+    Max,
+    /// The square root of the sum of the squared absolute values
+    Frobenius,
+}
+
+impl Norm {
+    /// The character that selects this norm in xgscon and xlangs
+    ///
+    /// Panics for the Frobenius norm, which neither implements.
+    pub(crate) fn to_raw(self) -> libc::c_char {
+	(match self {
+	    Self::One => b'1',
+	    Self::Inf => b'I',
+	    Self::Max => b'M',
+	    Self::Frobenius => panic!("SuperLU does not implement the Frobenius norm"),
+	}) as libc::c_char
+    }
+
+    /// The same norm of the transpose (or conjugate transpose)
+    pub(crate) fn transposed(self) -> Self {
+	match self {
+	    Self::One => Self::Inf,
+	    Self::Inf => Self::One,
+	    norm => norm,
+	}
+    }
+}
+
+pub(crate) fn yes_no(enable: bool) -> yes_no_t {
+    if enable { yes_no_t_YES } else { yes_no_t_NO }
+}
+
+/// Options for the SuperLU drivers
+#[derive(Debug, Copy, Clone)]
+pub struct Options {
+    raw: superlu_options_t,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+	Self::new()
+    }
+}
+
+impl Options {
+    /// The SuperLU default options
+    pub fn new() -> Self {
+	let raw = unsafe {
+	    let mut raw = MaybeUninit::<superlu_options_t>::uninit();
+	    set_default_options(raw.as_mut_ptr());
+	    raw.assume_init()
+	};
+	Self { raw }
+    }
+
+    /// Default options suited to the matrix A
+    ///
+    /// This is the same as Options::new(), except for matrices stored
+    /// as one half of a symmetric or Hermitian matrix. For those,
+    /// symmetric mode is enabled and the columns are ordered using the
+    /// structure of A^T + A, which is what the SuperLU manual
+    /// recommends for (nearly) symmetric problems.
+    pub fn for_matrix<T: ValueType, O: Ownership>(a: &SuperMatrix<NC, T, O>) -> Self {
+	let options = Self::new();
+	match a.mtype() {
+	    Mtype_t_SLU_SYL | Mtype_t_SLU_SYU | Mtype_t_SLU_HEL | Mtype_t_SLU_HEU => options
+		.symmetric_mode(true)
+		.column_ordering(ColumnOrdering::MmdAtPlusA),
+	    _ => options,
+	}
+    }
+
+    /// Set the column ordering (ColPerm)
+    ///
+    /// Panics if the ordering is not available (see
+    /// ColumnOrdering::is_available). To use a permutation chosen by
+    /// the caller, see factorize_with_permutation.
+    pub fn column_ordering(mut self, ordering: ColumnOrdering) -> Self {
+	assert!(ordering.is_available(), "the {:?} ordering is not available in serial SuperLU", ordering);
+	self.raw.ColPerm = ordering.to_raw();
+	self
+    }
+
+    /// Use the column permutation passed to the driver (ColPerm =
+    /// MY_PERMC)
+    pub(crate) fn user_column_ordering(mut self) -> Self {
+	self.raw.ColPerm = colperm_t_MY_PERMC;
+	self
+    }
+
+    /// Use symmetric mode (SymmetricMode), which favours pivots on
+    /// the diagonal. Useful for matrices that are (nearly) symmetric
+    /// in structure and diagonally dominant.
+    pub fn symmetric_mode(mut self, enable: bool) -> Self {
+	self.raw.SymmetricMode = yes_no(enable);
+	self
+    }
+
+    /// Set the threshold used for diagonal pivoting (DiagPivotThresh),
+    /// between 0.0 (always pick the diagonal) and 1.0 (partial
+    /// pivoting, the default).
+    pub fn diag_pivot_thresh(mut self, thresh: f64) -> Self {
+	assert!((0.0..=1.0).contains(&thresh), "DiagPivotThresh must be between 0 and 1");
+	self.raw.DiagPivotThresh = thresh;
+	self
+    }
+
+    /// Scale the rows and columns of A before factorizing (Equil),
+    /// if that improves its condition (expert driver only; enabled
+    /// by default)
+    pub fn equilibrate(mut self, enable: bool) -> Self {
+	self.raw.Equil = yes_no(enable);
+	self
+    }
+
+    /// Estimate the reciprocal condition number of A
+    /// (ConditionNumber, expert driver only)
+    pub fn condition_number(mut self, enable: bool) -> Self {
+	self.raw.ConditionNumber = yes_no(enable);
+	self
+    }
+
+    /// Compute the reciprocal pivot growth factor (PivotGrowth,
+    /// expert driver only)
+    pub fn pivot_growth(mut self, enable: bool) -> Self {
+	self.raw.PivotGrowth = yes_no(enable);
+	self
+    }
+
+    /// Refine the solution iteratively, and compute forward and
+    /// backward error bounds (IterRefine, expert driver only)
+    pub fn iterative_refinement(mut self, enable: bool) -> Self {
+	self.raw.IterRefine = if enable { IterRefine_t_SLU_DOUBLE } else { IterRefine_t_NOREFINE };
+	self
+    }
+
+    /// The underlying superlu_options_t struct
+    pub fn as_raw(&self) -> &superlu_options_t {
+	&self.raw
+    }
+}
@@ -0,0 +1,108 @@
+//! Column orderings
+//!
+//! The get_perm_c routine computes the fill-reducing column orderings
+//! of the serial library: the natural order, minimum degree on the
+//! structure of A^T A or A^T + A, and COLAMD. The colperm_t enum also
+//! names the METIS, ParMETIS and Zoltan orderings of the parallel
+//! versions of SuperLU, for which get_perm_c stops the program, so
+//! column_ordering returns an error for them instead.
+//!
+//! A permutation computed here (or by any other means) can be given to
+//! factorize_with_permutation or SymbolicLu::with_permutation, which
+//! pass it to SuperLU with ColPerm = MY_PERMC. A Permutation is always
+//! valid, so SuperLU never sees a repeated or out-of-range column.
+
+use crate::error::SolveError;
+use crate::options::ColumnOrdering;
+use crate::simple_driver::SystemMatrix;
+use crate::value_type::ValueType;
+use crate::get_perm_c;
+
+/// A permutation of 0..n, in the SuperLU convention: perm[i] = j
+/// means column i of A is column j of A Pc (for perm_c), or row i of
+/// A is row j of Pr A (for perm_r)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Permutation {
+    perm: Vec<i32>,
+}
+
+impl Permutation {
+    /// Check that perm holds each of 0..perm.len() exactly once
+    ///
+    /// Returns SolveError::InvalidPermutation with the position of the
+    /// first element that is out of range or repeated.
+    pub fn new(perm: Vec<i32>) -> Result<Self, SolveError> {
+	let n = perm.len();
+	let mut seen = vec![false; n];
+	for (i, &j) in perm.iter().enumerate() {
+	    if j < 0 || j as usize >= n || seen[j as usize] {
+		return Err(SolveError::InvalidPermutation(i));
+	    }
+	    seen[j as usize] = true;
+	}
+	Ok(Self { perm })
+    }
+
+    /// The identity permutation of 0..n
+    pub fn identity(n: usize) -> Self {
+	Self { perm: (0..n as i32).collect() }
+    }
+
+    /// The number of elements permuted
+    pub fn len(&self) -> usize {
+	self.perm.len()
+    }
+
+    /// True if the permutation is empty
+    pub fn is_empty(&self) -> bool {
+	self.perm.is_empty()
+    }
+
+    /// The permutation as a slice
+    pub fn as_slice(&self) -> &[i32] {
+	&self.perm
+    }
+
+    /// The inverse permutation
+    pub fn inverse(&self) -> Self {
+	let mut inverse = vec![0; self.len()];
+	for (i, &j) in self.perm.iter().enumerate() {
+	    inverse[j as usize] = i as i32;
+	}
+	Self { perm: inverse }
+    }
+
+    /// The permutation as a vector
+    pub fn into_vec(self) -> Vec<i32> {
+	self.perm
+    }
+}
+
+/// Compute a fill-reducing column ordering of A (using get_perm_c)
+///
+/// For a compressed-row matrix or a transposed view, which the
+/// factorizations handle by factorizing the stored transpose, this is
+/// an ordering of the columns of that transpose. Returns
+/// SolveError::UnavailableOrdering for the orderings the serial
+/// library does not implement. Panics for ColumnOrdering::MmdAtPlusA
+/// if A is not square.
+pub fn column_ordering<T, A>(a: &A, ordering: ColumnOrdering) -> Result<Permutation, SolveError>
+where
+    T: ValueType,
+    A: SystemMatrix<T>,
+{
+    if !ordering.is_available() {
+	return Err(SolveError::UnavailableOrdering(ordering));
+    }
+    let (a, _) = a.comp_col_copy();
+    if ordering == ColumnOrdering::MmdAtPlusA {
+	assert_eq!(a.nrow(), a.ncol(), "the MmdAtPlusA ordering needs a square matrix");
+    }
+    // get_perm_c takes a mutable pointer, but only reads A
+    let mut raw = *a.as_raw();
+    let mut perm = vec![0; a.ncol()];
+    unsafe {
+	get_perm_c(ordering.to_raw() as i32, &mut raw, perm.as_mut_ptr());
+    }
+    Ok(Permutation { perm })
+}
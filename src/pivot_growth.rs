@@ -0,0 +1,112 @@
+//! Pivot growth
+//!
+//! The reciprocal pivot growth factor of a factorization is
+//! min_j (max_i |A(i,j)| / max_i |U(i,j)|), computed by the
+//! xPivotGrowth routines. Gaussian elimination is backward stable
+//! when the elements of U stay about as large as those of A, so a
+//! value much smaller than one warns that the factors (and the
+//! solutions computed with them) may be inaccurate. Pivoting closer
+//! to partial pivoting (a larger DiagPivotThresh) usually helps.
+
+use num::traits::One;
+
+use crate::equilibrate::scaled_copy;
+use crate::factorization::Factorization;
+use crate::incomplete_lu::IncompleteLu;
+use crate::lu_factorization::LuFactorization;
+use crate::options::Options;
+use crate::simple_driver::SystemMatrix;
+use crate::super_matrix::{CompColMatrix, SuperNodeMatrix};
+use crate::transpose::oriented_copy;
+use crate::value_type::ValueType;
+
+/// The reciprocal pivot growth factor of the factors of A (in the
+/// orientation and scaling that was factorized)
+fn recip_pivot_growth<T: ValueType>(a: &CompColMatrix<T>, perm_c: &[i32], l: &SuperNodeMatrix<T>,
+				    u: &CompColMatrix<T>) -> T::Real {
+    let n = a.ncol();
+    if n == 0 {
+	return T::Real::one();
+    }
+    unsafe {
+	// xPivotGrowth takes mutable pointers, but only reads its
+	// arguments
+	let mut a = *a.as_raw();
+	let mut l = *l.as_raw();
+	let mut u = *u.as_raw();
+	T::c_pivot_growth(n as i32, &mut a, perm_c.as_ptr() as *mut i32, &mut l, &mut u)
+    }
+}
+
+impl<T: ValueType> LuFactorization<T> {
+    /// The reciprocal pivot growth factor (see the module
+    /// documentation), as computed by xgssvx with
+    /// Options::pivot_growth
+    ///
+    /// If A was equilibrated, this is the growth for the scaled
+    /// matrix that was factorized.
+    pub fn pivot_growth(&self) -> T::Real {
+	recip_pivot_growth(self.factorized_matrix(), self.perm_c(), self.l(), self.u())
+    }
+}
+
+impl<T: ValueType> Factorization<T> {
+    /// The reciprocal pivot growth factor, where A is the matrix that
+    /// was factorized (the factors do not keep a copy of it)
+    pub fn pivot_growth<A: SystemMatrix<T>>(&self, a: &A) -> T::Real {
+	let m = oriented_copy(a, self.trans());
+	assert_eq!(m.ncol(), self.u().ncol(), "A must have the dimensions of the factorized matrix");
+	recip_pivot_growth(&m, self.perm_c(), self.l(), self.u())
+    }
+}
+
+impl<T: ValueType> IncompleteLu<T> {
+    /// The reciprocal pivot growth factor of the incomplete factors,
+    /// where A is the matrix that was factorized (the factors do not
+    /// keep a copy of it)
+    pub fn pivot_growth<A: SystemMatrix<T>>(&self, a: &A) -> T::Real {
+	let m = oriented_copy(a, self.trans());
+	assert_eq!(m.ncol(), self.n(), "A must have the dimensions of the factorized matrix");
+	let m = scaled_copy(&m, self.row_scale(), self.col_scale());
+	recip_pivot_growth(&m, self.perm_c(), self.l(), self.u())
+    }
+}
+
+/// A rule for flagging factorizations whose reciprocal pivot growth
+/// is too small
+///
+/// After factorizing, check is_unstable(lu.pivot_growth()), and if it
+/// is true, factorize A again with the options from stricter.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PivotGrowthPolicy {
+    threshold: f64,
+}
+
+impl PivotGrowthPolicy {
+    /// Flag reciprocal pivot growth factors below threshold
+    pub fn new(threshold: f64) -> Self {
+	assert!(threshold >= 0.0, "the threshold must not be negative");
+	Self { threshold }
+    }
+
+    /// The smallest acceptable reciprocal pivot growth factor
+    pub fn threshold(&self) -> f64 {
+	self.threshold
+    }
+
+    /// True if the reciprocal pivot growth factor is below the
+    /// threshold (or NaN)
+    pub fn is_unstable<R: Into<f64>>(&self, recip_pivot_growth: R) -> bool {
+	let recip_pivot_growth = recip_pivot_growth.into();
+	recip_pivot_growth.is_nan() || recip_pivot_growth < self.threshold
+    }
+
+    /// The options with a stricter DiagPivotThresh, for factorizing a
+    /// flagged matrix again: ten times the old threshold, but at
+    /// least 0.1 and at most 1 (partial pivoting, which cannot be made
+    /// stricter)
+    pub fn stricter(&self, options: &Options) -> Options {
+	let thresh = options.as_raw().DiagPivotThresh;
+	options.diag_pivot_thresh((10.0 * thresh).clamp(0.1, 1.0))
+    }
+}
@@ -0,0 +1,174 @@
+//! Column preordering and the low-level factorization routine
+//!
+//! Before factorizing, the SuperLU drivers call sp_preorder, which
+//! applies the column permutation to A (producing the SLU_NCP matrix
+//! AC), computes the column elimination tree, and postorders the
+//! tree (changing the column permutation to match). The factorization
+//! itself is done by the xgstrf routines, which take AC and the
+//! elimination tree.
+//!
+//! The preorder function exposes the first step, so that the
+//! permuted matrix can be inspected, or factorized directly with
+//! PermutedMatrix::factorize as part of a custom pipeline.
+
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+
+use crate::error::SolveError;
+use crate::factorization::Factorization;
+use crate::options::Options;
+use crate::stat::SuperLUStat;
+use crate::super_matrix::{Ownership, SuperMatrix, NC, NCP};
+use crate::value_type::ValueType;
+use crate::{
+    fact_t_DOFACT, get_perm_c, get_tuning_params, sp_preorder, GlobalLU_t,
+    SuperMatrix as c_SuperMatrix,
+};
+
+/// The column-permuted matrix AC = A Pc, and its elimination tree
+///
+/// AC uses the values and row indices of A in place, and only owns
+/// the colbeg and colend arrays (freed with Destroy_CompCol_Permuted
+/// when it is dropped), so it borrows A for its lifetime.
+#[derive(Debug)]
+pub struct PermutedMatrix<'a, T: ValueType> {
+    ac: SuperMatrix<NCP, T>,
+    etree: Vec<i32>,
+    perm_c: Vec<i32>,
+    marker: PhantomData<&'a ()>,
+}
+
+/// Check that perm_c is a permutation of 0..n
+fn check_permutation(perm: &[i32], n: usize) {
+    assert_eq!(perm.len(), n, "perm_c must have one element for each column");
+    let mut seen = vec![false; n];
+    for &j in perm {
+	assert!(j >= 0 && (j as usize) < n, "perm_c index out of range");
+	assert!(!seen[j as usize], "perm_c must not repeat a column");
+	seen[j as usize] = true;
+    }
+}
+
+/// Compute the column permutation selected by the ColPerm option
+/// (a wrapper for get_perm_c)
+pub(crate) fn column_permutation<T: ValueType, O: Ownership>(
+    options: &Options,
+    a: &SuperMatrix<NC, T, O>,
+) -> Vec<i32> {
+    // The ColumnOrdering values match the ispec argument of get_perm_c
+    let ispec = options.as_raw().ColPerm as i32;
+    let mut raw_a = *a.as_raw();
+    let mut perm_c = vec![0; a.ncol()];
+    unsafe {
+	get_perm_c(ispec, &mut raw_a, perm_c.as_mut_ptr());
+    }
+    perm_c
+}
+
+/// Permute the columns of A and compute the column elimination tree
+///
+/// This is a wrapper for sp_preorder. The column permutation perm_c
+/// (where perm_c[i] = j means column i of A is column j of AC) is
+/// usually obtained from a fill-reducing ordering; unless symmetric
+/// mode is enabled in the options, it is changed to a postorder of
+/// the elimination tree, and the new permutation is stored in the
+/// PermutedMatrix. The elimination tree is always computed (the Fact
+/// option is ignored).
+///
+/// A must be square and stored in full (not half-stored), and perm_c
+/// must be a permutation of the columns, otherwise this function
+/// panics.
+pub fn preorder<'a, T: ValueType, O: Ownership>(
+    options: &Options,
+    a: &'a SuperMatrix<NC, T, O>,
+    perm_c: &[i32],
+) -> PermutedMatrix<'a, T> {
+    assert_eq!(a.nrow(), a.ncol(), "A must be square");
+    assert!(!a.is_half_stored(), "preorder needs a matrix stored in full (see to_general)");
+    let ncol = a.ncol();
+    check_permutation(perm_c, ncol);
+
+    // sp_preorder takes mutable pointers, but only reads A, so a
+    // copy of the SuperMatrix header is enough
+    let mut raw_options = *options.as_raw();
+    raw_options.Fact = fact_t_DOFACT;
+    let mut raw_a = *a.as_raw();
+    let mut perm_c = perm_c.to_vec();
+    let mut etree = vec![0; ncol];
+    let ac = unsafe {
+	let mut ac = MaybeUninit::<c_SuperMatrix>::uninit();
+	sp_preorder(&mut raw_options, &mut raw_a, perm_c.as_mut_ptr(),
+		    etree.as_mut_ptr(), ac.as_mut_ptr());
+	SuperMatrix::from_raw(ac.assume_init())
+    };
+    PermutedMatrix {
+	ac,
+	etree,
+	perm_c,
+	marker: PhantomData,
+    }
+}
+
+impl<'a, T: ValueType> PermutedMatrix<'a, T> {
+    /// The permuted matrix AC
+    pub fn matrix(&self) -> &SuperMatrix<NCP, T> {
+	&self.ac
+    }
+
+    /// The column elimination tree (etree[j] is the parent of column
+    /// j, or ncol if j is a root)
+    ///
+    /// The tree is in postorder, except in symmetric mode, where the
+    /// columns are kept in the order of the given perm_c and the tree
+    /// is not postordered.
+    pub fn etree(&self) -> &[i32] {
+	&self.etree
+    }
+
+    /// The column permutation after postordering (the given perm_c in
+    /// symmetric mode)
+    pub fn perm_c(&self) -> &[i32] {
+	&self.perm_c
+    }
+
+    /// Compute the LU factorization of AC (a wrapper for xgstrf)
+    ///
+    /// The relaxation and panel size parameters are taken from the
+    /// current tuning parameters. Row pivoting is controlled by the
+    /// options (for example, DiagPivotThresh); the Fact option is
+    /// ignored, and a new factorization is always computed. Panics if
+    /// AC is not square.
+    pub fn factorize(&self, options: &Options) -> Result<Factorization<T>, SolveError> {
+	let n = self.ac.ncol();
+	assert_eq!(self.ac.nrow(), n, "A must be square");
+	let mut raw_options = *options.as_raw();
+	raw_options.Fact = fact_t_DOFACT;
+	let mut raw_ac = *self.ac.as_raw();
+	let mut perm_c = self.perm_c.clone();
+	let mut etree = self.etree.clone();
+	let mut perm_r = vec![0; self.ac.nrow()];
+	let mut stat = SuperLUStat::new();
+	let tuning = get_tuning_params();
+	let mut info = 0;
+	let (l, u) = unsafe {
+	    let mut glu = MaybeUninit::<GlobalLU_t>::zeroed().assume_init();
+	    let mut l = MaybeUninit::<c_SuperMatrix>::uninit();
+	    let mut u = MaybeUninit::<c_SuperMatrix>::uninit();
+	    T::c_gstrf(&mut raw_options, &mut raw_ac, tuning.relaxation_param as i32,
+		       tuning.panel_size as i32, etree.as_mut_ptr(), std::ptr::null_mut(), 0,
+		       perm_c.as_mut_ptr(), perm_r.as_mut_ptr(), l.as_mut_ptr(), u.as_mut_ptr(),
+		       &mut glu, stat.as_mut_ptr(), &mut info);
+	    // L and U are only created if the factorization ran to the
+	    // end (which it does for singular matrices)
+	    if info < 0 || info as usize > n {
+		return Err(SolveError::from_info(info, n).unwrap());
+	    }
+	    (SuperMatrix::from_raw(l.assume_init()), SuperMatrix::from_raw(u.assume_init()))
+	};
+	let factors = Factorization::new(l, u, perm_r, perm_c);
+	match SolveError::from_info(info, n) {
+	    Some(err) => Err(err),
+	    None => Ok(factors),
+	}
+    }
+}
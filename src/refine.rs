@@ -0,0 +1,208 @@
+//! Iterative refinement
+//!
+//! The xgsrfs routines improve approximate solutions X of A X = B
+//! with existing LU factors of A. Each step computes the residual
+//! B - A X and solves for a correction with the factors, until the
+//! componentwise backward error falls below the machine precision,
+//! fails to halve, or five steps have been taken. They also estimate
+//! a bound on the forward error of each solution.
+//!
+//! The expert driver does this after solving, but X can come from
+//! anywhere (for example, the solution of a nearby system), and A
+//! need not be exactly the matrix that was factorized, as long as it
+//! is close enough for the refinement to converge.
+
+use num::traits::{One, Zero};
+
+use crate::equilibrate::scaled_copy;
+use crate::error::SolveError;
+use crate::expert_driver::Equilibration;
+use crate::factorization::Factorization;
+use crate::lu_factorization::{scale_rows, LuFactorization};
+use crate::options::Trans;
+use crate::simple_driver::SystemMatrix;
+use crate::stat::SuperLUStat;
+use crate::transpose::oriented_copy;
+use crate::super_matrix::{CompColMatrix, DenseMatrix, Ownership, SuperMatrix, SuperNodeMatrix, DN};
+use crate::value_type::ValueType;
+
+/// The error bounds computed by refine, for each column of X
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefinementReport<T: ValueType> {
+    ferr: Vec<T::Real>,
+    berr: Vec<T::Real>,
+    steps: Vec<usize>,
+}
+
+impl<T: ValueType> RefinementReport<T> {
+    /// The estimated bound on the forward error of each column of X,
+    /// relative to its largest element
+    pub fn ferr(&self) -> &[T::Real] {
+	&self.ferr
+    }
+
+    /// The componentwise relative backward error of each column of X
+    pub fn berr(&self) -> &[T::Real] {
+	&self.berr
+    }
+
+    /// The number of refinement steps taken for each column of X
+    pub fn steps(&self) -> &[usize] {
+	&self.steps
+    }
+}
+
+/// The parts of a factorization used by xgsrfs: Pr diag(R) M diag(C)
+/// Pc = L U, where A is M with trans applied
+struct Factors<'a, T: ValueType> {
+    l: &'a SuperNodeMatrix<T>,
+    u: &'a CompColMatrix<T>,
+    perm_c: &'a [i32],
+    perm_r: &'a [i32],
+    row_scale: Option<&'a [T::Real]>,
+    col_scale: Option<&'a [T::Real]>,
+    trans: Trans,
+}
+
+fn refine<T, A, OB, OX>(factors: Factors<'_, T>, a: &A, b: &SuperMatrix<DN, T, OB>,
+			x: &mut SuperMatrix<DN, T, OX>) -> Result<RefinementReport<T>, SolveError>
+where
+    T: ValueType,
+    A: SystemMatrix<T>,
+    OB: Ownership,
+    OX: Ownership,
+{
+    let n = factors.u.ncol();
+    // The copy must be oriented and scaled like the factorized matrix
+    let (rows, cols) = (factors.row_scale, factors.col_scale);
+    let m = oriented_copy(a, factors.trans);
+    assert_eq!((m.nrow(), m.ncol()), (n, n), "A must have the dimensions of the factorized matrix");
+    assert_eq!(b.nrow(), n, "B must have one row for each row of A");
+    assert_eq!(x.nrow(), n, "X must have one row for each row of A");
+    assert_eq!(b.ncol(), x.ncol(), "X must have one column for each column of B");
+    let mut m = if rows.is_some() || cols.is_some() { scaled_copy(&m, rows, cols) } else { m };
+
+    // As in the solves, the scaled system has right-hand sides
+    // diag(R) B and solutions diag(C)^-1 X (the other way round for a
+    // transposed system)
+    let (before, after) = match factors.trans {
+	Trans::NoTrans => (rows, cols),
+	Trans::Trans | Trans::Conj => (cols, rows),
+    };
+    let mut b_scaled = DenseMatrix::new(n, b.ncol(), &columns(b));
+    if let Some(scale) = before {
+	scale_rows(&mut b_scaled, scale);
+    }
+    let mut x_scaled = DenseMatrix::new(n, x.ncol(), &columns(x));
+    if let Some(scale) = after {
+	let inverse: Vec<T::Real> = scale.iter().map(|&s| T::Real::one() / s).collect();
+	scale_rows(&mut x_scaled, &inverse);
+    }
+
+    // xgsrfs only records the number of steps for the last column, so
+    // the columns are refined one at a time
+    let equed = match (rows.is_some(), cols.is_some()) {
+	(false, false) => Equilibration::None,
+	(true, false) => Equilibration::Row,
+	(false, true) => Equilibration::Col,
+	(true, true) => Equilibration::Both,
+    };
+    let nrhs = b.ncol();
+    let mut report = RefinementReport {
+	ferr: vec![T::Real::zero(); nrhs],
+	berr: vec![T::Real::zero(); nrhs],
+	steps: vec![0; nrhs],
+    };
+    for j in 0..nrhs {
+	let column = j * n..(j + 1) * n;
+	let mut bj = DenseMatrix::from_slice(n, 1, &mut b_scaled.values_mut()[column.clone()]);
+	let mut xj = DenseMatrix::from_slice(n, 1, &mut x_scaled.values_mut()[column]);
+	let mut equed = equed.to_raw();
+	let mut stat = SuperLUStat::new();
+	let mut info = 0;
+	unsafe {
+	    // xgsrfs takes mutable pointers, but only changes X
+	    let mut l = *factors.l.as_raw();
+	    let mut u = *factors.u.as_raw();
+	    let r = rows.map_or(std::ptr::null_mut(), |r| r.as_ptr() as *mut T::Real);
+	    let c = cols.map_or(std::ptr::null_mut(), |c| c.as_ptr() as *mut T::Real);
+	    T::c_gsrfs(factors.trans.to_raw(), m.as_mut_ptr(), &mut l, &mut u,
+		       factors.perm_c.as_ptr() as *mut i32, factors.perm_r.as_ptr() as *mut i32,
+		       &mut equed, r, c, bj.as_mut_ptr(), xj.as_mut_ptr(), &mut report.ferr[j],
+		       &mut report.berr[j], stat.as_mut_ptr(), &mut info);
+	}
+	if let Some(err) = SolveError::from_info(info, n) {
+	    return Err(err);
+	}
+	report.steps[j] = stat.refine_steps();
+    }
+
+    if let Some(scale) = after {
+	scale_rows(&mut x_scaled, scale);
+    }
+    let lda = x.lda().max(1);
+    for (dest, src) in x.values_mut().chunks_mut(lda).zip(x_scaled.values().chunks(n.max(1))) {
+	dest[..n].copy_from_slice(src);
+    }
+    Ok(report)
+}
+
+/// The first nrow elements of each column of a dense matrix
+fn columns<T: ValueType, O: Ownership>(b: &SuperMatrix<DN, T, O>) -> Vec<T> {
+    let (nrow, lda) = (b.nrow(), b.lda().max(1));
+    b.values().chunks(lda).flat_map(|col| &col[..nrow]).copied().collect()
+}
+
+impl<T: ValueType> LuFactorization<T> {
+    /// Refine the solutions X of A X = B, and bound their errors
+    /// (using xgsrfs)
+    ///
+    /// A is the matrix that was factorized, or one close to it, in
+    /// any form accepted by factorize. X holds approximate solutions
+    /// on entry, and the refined solutions on exit.
+    pub fn refine<A, OB, OX>(&self, a: &A, b: &SuperMatrix<DN, T, OB>,
+			     x: &mut SuperMatrix<DN, T, OX>) -> Result<RefinementReport<T>, SolveError>
+    where
+	A: SystemMatrix<T>,
+	OB: Ownership,
+	OX: Ownership,
+    {
+	let factors = Factors {
+	    l: self.l(),
+	    u: self.u(),
+	    perm_c: self.perm_c(),
+	    perm_r: self.perm_r(),
+	    row_scale: self.row_scale(),
+	    col_scale: self.col_scale(),
+	    trans: self.trans(),
+	};
+	refine(factors, a, b, x)
+    }
+}
+
+impl<T: ValueType> Factorization<T> {
+    /// Refine the solutions X of A X = B, and bound their errors
+    /// (using xgsrfs)
+    ///
+    /// A is the matrix that was factorized, or one close to it, in
+    /// any form accepted by solve. X holds approximate solutions on
+    /// entry, and the refined solutions on exit.
+    pub fn refine<A, OB, OX>(&self, a: &A, b: &SuperMatrix<DN, T, OB>,
+			     x: &mut SuperMatrix<DN, T, OX>) -> Result<RefinementReport<T>, SolveError>
+    where
+	A: SystemMatrix<T>,
+	OB: Ownership,
+	OX: Ownership,
+    {
+	let factors = Factors {
+	    l: self.l(),
+	    u: self.u(),
+	    perm_c: self.perm_c(),
+	    perm_r: self.perm_r(),
+	    row_scale: None,
+	    col_scale: None,
+	    trans: self.trans(),
+	};
+	refine(factors, a, b, x)
+    }
+}
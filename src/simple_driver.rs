@@ -0,0 +1,175 @@
+//! The simple driver
+//!
+//! The xgssv routines factorize A and solve A X = B in one call,
+//! using the options to choose the column ordering and the pivoting.
+//! The solve function wraps them for all four precisions. It allocates
+//! the permutation vectors and the statistics, overwrites B with the
+//! solution, and returns the factors (which are freed when the
+//! Factorization is dropped).
+
+use std::mem::MaybeUninit;
+
+use crate::error::SolveError;
+use crate::factorization::Factorization;
+use crate::options::{Options, Trans};
+use crate::stat::SuperLUStat;
+use crate::super_matrix::{CompColMatrix, Ownership, Storage, SuperMatrix, DN, NC, NR};
+use crate::transpose::Transposed;
+use crate::value_type::ValueType;
+use crate::{fact_t_DOFACT, SuperMatrix as c_SuperMatrix};
+
+pub(crate) mod private {
+    use super::*;
+
+    /// The matrix as passed to SuperLU, and whether the adjoint of
+    /// that matrix is meant. If the input had to be expanded to a
+    /// general matrix, the expanded copy is kept alive here.
+    pub struct System<T: ValueType> {
+	pub raw: c_SuperMatrix,
+	pub conj: bool,
+	pub expanded: Option<CompColMatrix<T>>,
+    }
+
+    pub trait Sealed<T: ValueType> {
+	fn system(&self) -> System<T>;
+
+	/// A general compressed-column copy of the matrix, and the
+	/// system that gives the original problem (for example, Trans
+	/// for a compressed-row matrix, whose arrays describe A^T as a
+	/// compressed-column matrix)
+	fn comp_col_copy(&self) -> (CompColMatrix<T>, Trans);
+    }
+}
+
+use private::System;
+
+/// Matrices that can be passed to the solvers
+///
+/// This is implemented for compressed-column matrices (which may be
+/// half-stored symmetric or Hermitian matrices, expanded before the
+/// solve), compressed-row matrices, and transposed views. Solving
+/// with a compressed-row matrix or a transposed view factorizes the
+/// transpose of the matrix, so the returned Factorization solves
+/// with TRANS (or CONJ, for an adjoint view).
+pub trait SystemMatrix<T: ValueType>: private::Sealed<T> {}
+
+impl<T: ValueType, O: Ownership> private::Sealed<T> for SuperMatrix<NC, T, O> {
+    fn system(&self) -> System<T> {
+	if self.is_half_stored() {
+	    let expanded = self.to_general();
+	    System { raw: *expanded.as_raw(), conj: false, expanded: Some(expanded) }
+	} else {
+	    System { raw: *self.as_raw(), conj: false, expanded: None }
+	}
+    }
+
+    fn comp_col_copy(&self) -> (CompColMatrix<T>, Trans) {
+	(self.to_general(), Trans::NoTrans)
+    }
+}
+
+impl<T: ValueType, O: Ownership> private::Sealed<T> for SuperMatrix<NR, T, O> {
+    fn system(&self) -> System<T> {
+	System { raw: *self.as_raw(), conj: false, expanded: None }
+    }
+
+    fn comp_col_copy(&self) -> (CompColMatrix<T>, Trans) {
+	(self.reinterpret_cols().to_general(), Trans::Trans)
+    }
+}
+
+impl<'a, T: ValueType> private::Sealed<T> for Transposed<'a, T> {
+    fn system(&self) -> System<T> {
+	System { raw: *self.matrix().as_raw(), conj: self.trans() == Trans::Conj, expanded: None }
+    }
+
+    fn comp_col_copy(&self) -> (CompColMatrix<T>, Trans) {
+	(self.matrix().reinterpret_cols().to_general(), self.trans())
+    }
+}
+
+impl<T: ValueType, O: Ownership> SystemMatrix<T> for SuperMatrix<NC, T, O> {}
+impl<T: ValueType, O: Ownership> SystemMatrix<T> for SuperMatrix<NR, T, O> {}
+impl<'a, T: ValueType> SystemMatrix<T> for Transposed<'a, T> {}
+
+/// Conjugate every value of a dense matrix in place
+pub(crate) fn conj_in_place<T: ValueType, O: Ownership>(b: &mut SuperMatrix<DN, T, O>) {
+    for x in b.values_mut() {
+	*x = x.conj();
+    }
+}
+
+/// Solve A X = B using the simple driver (xgssv)
+///
+/// A must be square, and B must have one row for each row of A. On
+/// success, B is overwritten with the solution X, and the factors of
+/// A are returned. The Fact option is ignored (A is always factorized
+/// from scratch).
+///
+/// If A is singular, U(j,j) is exactly zero for some column j, and
+/// SolveError::Singular(j) is returned; B is left unchanged.
+///
+/// Only sparse matrices can be used for A:
+///
+/// ```compile_fail
+/// use csuperlu_sys::{solve, DenseMatrix, Options};
+/// let a = DenseMatrix::new(1, 1, &[2.0]);
+/// let mut b = DenseMatrix::new(1, 1, &[1.0]);
+/// solve(&a, &mut b, &Options::new());
+/// ```
+pub fn solve<T, A, O>(
+    a: &A,
+    b: &mut SuperMatrix<DN, T, O>,
+    options: &Options,
+) -> Result<Factorization<T>, SolveError>
+where
+    T: ValueType,
+    A: SystemMatrix<T>,
+    O: Ownership,
+{
+    let mut system = a.system();
+    let (nrow, ncol) = (system.raw.nrow as usize, system.raw.ncol as usize);
+    assert_eq!(nrow, ncol, "A must be square");
+    assert_eq!(b.nrow(), nrow, "B must have one row for each row of A");
+
+    // A^H X = B is solved as A^T conj(X) = conj(B)
+    if system.conj {
+	conj_in_place(b);
+    }
+    let mut raw_options = *options.as_raw();
+    raw_options.Fact = fact_t_DOFACT;
+    let mut perm_c = vec![0; ncol];
+    let mut perm_r = vec![0; nrow];
+    let mut stat = SuperLUStat::new();
+    let mut info = 0;
+    let (l, u) = unsafe {
+	let mut l = MaybeUninit::<c_SuperMatrix>::uninit();
+	let mut u = MaybeUninit::<c_SuperMatrix>::uninit();
+	T::c_gssv(&mut raw_options, &mut system.raw, perm_c.as_mut_ptr(), perm_r.as_mut_ptr(),
+		  l.as_mut_ptr(), u.as_mut_ptr(), b.as_mut_ptr(), stat.as_mut_ptr(), &mut info);
+	// L and U are only created if the factorization ran to the
+	// end (which it does for singular matrices)
+	if info < 0 || info as usize > ncol {
+	    if system.conj {
+		conj_in_place(b);
+	    }
+	    return Err(SolveError::from_info(info, ncol).unwrap());
+	}
+	(SuperMatrix::from_raw(l.assume_init()), SuperMatrix::from_raw(u.assume_init()))
+    };
+    if system.conj {
+	conj_in_place(b);
+    }
+
+    // For compressed-row input, xgssv factorizes the transpose
+    let trans = match (system.raw.Stype == NR::STYPE, system.conj) {
+	(false, _) => Trans::NoTrans,
+	(true, false) => Trans::Trans,
+	(true, true) => Trans::Conj,
+    };
+    let factors = Factorization::new(l, u, perm_r, perm_c).with_trans(trans);
+    match SolveError::from_info(info, ncol) {
+	Some(err) => Err(err),
+	None => Ok(factors),
+    }
+}
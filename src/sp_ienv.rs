@@ -5,7 +5,12 @@
 //! the computer architecture or the types of systems being solved.
 //!
 //! This function has been moved out of the superlu C code in order to
-//! allow access by rust functions.
+//! allow access by rust functions. The parameters are global, and are
+//! read by every factorization, so they are kept behind a lock (they
+//! may be changed while other threads are factorizing, which then use
+//! either the old or the new parameters).
+
+use std::sync::{PoisonError, RwLock};
 
 #[derive(Debug, Copy, Clone)]
 pub struct TuningParams{
@@ -53,16 +58,18 @@ impl TuningParams {
     }
 }
 
-static mut tuning_params: TuningParams = TuningParams::new();
+static TUNING_PARAMS: RwLock<TuningParams> = RwLock::new(TuningParams::new());
 
 /// Read the current SuperLU performance-tuning parameters
-pub unsafe fn get_tuning_params() -> TuningParams {
-    tuning_params
+pub fn get_tuning_params() -> TuningParams {
+    // The parameters are Copy, so a panic while holding the lock
+    // cannot leave them half-written
+    *TUNING_PARAMS.read().unwrap_or_else(PoisonError::into_inner)
 }
 
 /// Set new SuperLU performance-tuning parameters
-pub unsafe fn set_tuning_params(new_params: TuningParams) {
-    tuning_params = new_params;
+pub fn set_tuning_params(new_params: TuningParams) {
+    *TUNING_PARAMS.write().unwrap_or_else(PoisonError::into_inner) = new_params;
 }
 
 
@@ -110,8 +117,5 @@ pub unsafe fn set_tuning_params(new_params: TuningParams) {
 ///         < 0:  if SP_IENV = -k, the k-th argument had an illegal value. 
 #[no_mangle]
 pub extern "C" fn sp_ienv(ispec: libc::c_int) -> libc::c_int {
-    println!("Hello from conterfeit sp_ienv!");
-    unsafe {
-	tuning_params.sp_ienv(ispec)
-    }
+    get_tuning_params().sp_ienv(ispec)
 }
@@ -0,0 +1,67 @@
+//! Performance statistics
+//!
+//! SuperLU records statistics such as the number of floating-point
+//! operations and the time spent in each phase of the solver in a
+//! SuperLUStat_t struct, which every driver needs. The SuperLUStat
+//! wrapper allocates it with StatInit and frees it with StatFree.
+
+use std::mem::MaybeUninit;
+
+use crate::{StatFree, StatInit, SuperLUStat_t};
+
+/// Performance statistics (a wrapper for SuperLUStat_t)
+#[derive(Debug)]
+pub struct SuperLUStat {
+    raw: SuperLUStat_t,
+}
+
+impl Default for SuperLUStat {
+    fn default() -> Self {
+	Self::new()
+    }
+}
+
+impl SuperLUStat {
+    /// Allocate and initialise the statistics
+    pub fn new() -> Self {
+	let raw = unsafe {
+	    let mut raw = MaybeUninit::<SuperLUStat_t>::uninit();
+	    StatInit(raw.as_mut_ptr());
+	    raw.assume_init()
+	};
+	Self { raw }
+    }
+
+    /// Number of tiny pivots (replaced to avoid division by zero)
+    pub fn tiny_pivots(&self) -> usize {
+	self.raw.TinyPivots as usize
+    }
+
+    /// Number of memory expansions during the factorization
+    pub fn expansions(&self) -> usize {
+	self.raw.expansions as usize
+    }
+
+    /// Number of steps of iterative refinement done for the last
+    /// right-hand side
+    pub fn refine_steps(&self) -> usize {
+	self.raw.RefineSteps as usize
+    }
+
+    /// Pointer to the underlying struct, for passing to SuperLU
+    pub fn as_mut_ptr(&mut self) -> *mut SuperLUStat_t {
+	&mut self.raw
+    }
+}
+
+// The arrays allocated by StatInit belong to this struct alone
+unsafe impl Send for SuperLUStat {}
+unsafe impl Sync for SuperLUStat {}
+
+impl Drop for SuperLUStat {
+    fn drop(&mut self) {
+	unsafe {
+	    StatFree(&mut self.raw);
+	}
+    }
+}
@@ -0,0 +1,558 @@
+//! Typed wrappers for SuperLU matrices
+//!
+//! In C, every SuperLU matrix is a SuperMatrix struct whose Store
+//! field is a void pointer, interpreted according to Stype (storage
+//! format) and Dtype (precision). Nothing stops a dense matrix being
+//! passed where a compressed-column matrix is expected. Here, the
+//! storage format and value type are type parameters of SuperMatrix,
+//! so that the safe routines in this crate can only be called with
+//! the right combinations (anything else is a compile error).
+//!
+//! The Store points to the arrays holding the matrix data. The
+//! Destroy_*_Matrix functions free all of it with SUPERLU_FREE, so
+//! they must only be called on matrices whose arrays were allocated by
+//! SuperLU. For matrices that wrap arrays owned by someone else, only
+//! Destroy_SuperMatrix_Store may be used. The two cases are kept
+//! apart by the ownership parameter:
+//!
+//! * Owned matrices hold arrays allocated with superlu_malloc (either
+//!   copied from Rust data, or created by a SuperLU routine). They are
+//!   destroyed with the Destroy_*_Matrix function for their format.
+//! * Borrowed matrices wrap Rust slices for the lifetime 'a, and only
+//!   the Store is freed when they are dropped.
+//!
+//! There is no way to turn one kind into the other, so Rust memory is
+//! never freed by the C allocator (or the other way round).
+
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+
+use crate::value_type::ValueType;
+use crate::{
+    superlu_malloc, Destroy_CompCol_Matrix, Destroy_CompCol_Permuted, Destroy_CompRow_Matrix,
+    Destroy_Dense_Matrix, Destroy_SuperMatrix_Store, Destroy_SuperNode_Matrix, DNformat,
+    Mtype_t, Mtype_t_SLU_GE, NCPformat, NCformat, NRformat, SCformat, Stype_t,
+    Stype_t_SLU_DN, Stype_t_SLU_NC, Stype_t_SLU_NCP, Stype_t_SLU_NR, Stype_t_SLU_SC,
+    SuperMatrix as c_SuperMatrix,
+};
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for super::Owned {}
+    impl<'a> Sealed for super::Borrowed<'a> {}
+    impl Sealed for super::NC {}
+    impl Sealed for super::NR {}
+    impl Sealed for super::NCP {}
+    impl Sealed for super::SC {}
+    impl Sealed for super::DN {}
+}
+
+/// Specifies who is responsible for freeing the matrix arrays
+pub trait Ownership: private::Sealed {
+    /// Free the matrix, including its arrays if they belong to the
+    /// matrix.
+    ///
+    /// # Safety
+    ///
+    /// A must be a valid SuperMatrix with storage format S.
+    unsafe fn destroy<S: Storage>(a: *mut c_SuperMatrix);
+}
+
+/// The matrix arrays were allocated by SuperLU, and are freed with
+/// the matrix.
+#[derive(Debug)]
+pub struct Owned;
+
+/// The matrix arrays are Rust slices borrowed for the lifetime 'a.
+/// Only the SuperLU Store is freed with the matrix.
+#[derive(Debug)]
+pub struct Borrowed<'a>(PhantomData<&'a mut ()>);
+
+impl Ownership for Owned {
+    unsafe fn destroy<S: Storage>(a: *mut c_SuperMatrix) {
+	S::destroy_owned(a);
+    }
+}
+
+impl<'a> Ownership for Borrowed<'a> {
+    unsafe fn destroy<S: Storage>(a: *mut c_SuperMatrix) {
+	Destroy_SuperMatrix_Store(a);
+    }
+}
+
+/// Storage format of a matrix (the Stype)
+pub trait Storage: private::Sealed {
+    /// The SuperLU Stype for this format
+    const STYPE: Stype_t;
+
+    /// The C struct pointed to by the Store field
+    type Format;
+
+    /// The SuperLU function that frees an owned matrix of this format
+    ///
+    /// # Safety
+    ///
+    /// A must be a valid matrix in this format.
+    unsafe fn destroy_owned(a: *mut c_SuperMatrix);
+}
+
+/// Compressed-column storage (SLU_NC), also known as Harwell-Boeing format
+#[derive(Debug)]
+pub struct NC;
+
+/// Compressed-row storage (SLU_NR)
+#[derive(Debug)]
+pub struct NR;
+
+/// Compressed-column storage, with the columns permuted (SLU_NCP)
+#[derive(Debug)]
+pub struct NCP;
+
+/// Supernodal, column-wise storage (SLU_SC), used for the L factor
+#[derive(Debug)]
+pub struct SC;
+
+/// Dense, column-major storage (SLU_DN)
+#[derive(Debug)]
+pub struct DN;
+
+impl Storage for NC {
+    const STYPE: Stype_t = Stype_t_SLU_NC;
+    type Format = NCformat;
+    unsafe fn destroy_owned(a: *mut c_SuperMatrix) {
+	Destroy_CompCol_Matrix(a);
+    }
+}
+
+impl Storage for NR {
+    const STYPE: Stype_t = Stype_t_SLU_NR;
+    type Format = NRformat;
+    unsafe fn destroy_owned(a: *mut c_SuperMatrix) {
+	Destroy_CompRow_Matrix(a);
+    }
+}
+
+impl Storage for NCP {
+    const STYPE: Stype_t = Stype_t_SLU_NCP;
+    type Format = NCPformat;
+    unsafe fn destroy_owned(a: *mut c_SuperMatrix) {
+	// This only frees colbeg and colend; the values and row
+	// indices belong to the matrix that was permuted.
+	Destroy_CompCol_Permuted(a);
+    }
+}
+
+impl Storage for SC {
+    const STYPE: Stype_t = Stype_t_SLU_SC;
+    type Format = SCformat;
+    unsafe fn destroy_owned(a: *mut c_SuperMatrix) {
+	Destroy_SuperNode_Matrix(a);
+    }
+}
+
+impl Storage for DN {
+    const STYPE: Stype_t = Stype_t_SLU_DN;
+    type Format = DNformat;
+    unsafe fn destroy_owned(a: *mut c_SuperMatrix) {
+	Destroy_Dense_Matrix(a);
+    }
+}
+
+/// A SuperLU matrix with storage format S and values of type T
+///
+/// O specifies whether the matrix arrays are owned by the matrix
+/// (allocated by SuperLU) or borrowed from Rust.
+///
+/// Functions that need a particular format or precision say so in
+/// their signature, so passing the wrong kind of matrix does not
+/// compile:
+///
+/// ```compile_fail
+/// use csuperlu_sys::super_matrix::{SuperMatrix, NC};
+/// use csuperlu_sys::DenseMatrix;
+///
+/// fn needs_comp_col(_a: &SuperMatrix<NC, f64>) {}
+///
+/// let b = DenseMatrix::new(1, 1, &[1.0]);
+/// needs_comp_col(&b);
+/// ```
+#[derive(Debug)]
+pub struct SuperMatrix<S: Storage, T: ValueType, O: Ownership = Owned> {
+    raw: c_SuperMatrix,
+    marker: PhantomData<(S, T, O)>,
+}
+
+/// Compressed-column matrix
+pub type CompColMatrix<T, O = Owned> = SuperMatrix<NC, T, O>;
+
+/// Compressed-row matrix
+pub type CompRowMatrix<T, O = Owned> = SuperMatrix<NR, T, O>;
+
+/// Dense matrix, stored in column-major order
+pub type DenseMatrix<T, O = Owned> = SuperMatrix<DN, T, O>;
+
+/// Supernodal matrix, as returned for the L factor
+pub type SuperNodeMatrix<T> = SuperMatrix<SC, T>;
+
+/// Copy a slice into a new array allocated with superlu_malloc, so
+/// that it can be freed by one of the SuperLU Destroy functions.
+fn superlu_copy<U: Copy>(src: &[U]) -> *mut U {
+    // Always allocate at least one element, so that the pointer is
+    // never null (SuperLU treats null as a failed allocation)
+    let size = std::mem::size_of_val(src).max(std::mem::size_of::<U>());
+    unsafe {
+	let dst = superlu_malloc(size as _) as *mut U;
+	assert!(!dst.is_null(), "superlu_malloc failed to allocate {} bytes", size);
+	std::ptr::copy_nonoverlapping(src.as_ptr(), dst, src.len());
+	dst
+    }
+}
+
+/// Check compressed arrays describe a valid matrix, so that SuperLU
+/// will not read out of bounds. For compressed-column storage, ptr is
+/// colptr (of length ncol + 1), index is rowind, and inner is the
+/// number of rows (and the other way round for compressed-row).
+/// Panics if the arrays are not consistent.
+fn check_compressed(outer: usize, inner: usize, nnz: usize, index: &[i32], ptr: &[i32]) {
+    assert!(outer <= i32::MAX as usize && inner <= i32::MAX as usize,
+	    "matrix dimensions do not fit in a C int");
+    assert_eq!(ptr.len(), outer + 1, "colptr (or rowptr) must have one more element than columns (rows)");
+    assert_eq!(ptr[0], 0, "colptr (or rowptr) must start at zero");
+    assert!(ptr.windows(2).all(|w| w[0] <= w[1]), "colptr (or rowptr) must be non-decreasing");
+    assert_eq!(ptr[outer] as usize, nnz, "colptr[ncol] (or rowptr[nrow]) must equal the number of non-zeros");
+    assert_eq!(index.len(), nnz, "rowind (or colind) and nzval must have the same length");
+    assert!(index.iter().all(|&i| i >= 0 && (i as usize) < inner), "row (or column) index out of range");
+}
+
+impl<S: Storage, T: ValueType, O: Ownership> SuperMatrix<S, T, O> {
+    /// Wrap a SuperMatrix created elsewhere
+    ///
+    /// # Safety
+    ///
+    /// The matrix must be valid, with Stype equal to S::STYPE and Dtype
+    /// equal to T::DTYPE. If O is Owned, all the arrays must have been
+    /// allocated by SuperLU; if O is Borrowed<'a>, the arrays must stay
+    /// valid for 'a, and only the Store may have been allocated by
+    /// SuperLU.
+    pub unsafe fn from_raw(raw: c_SuperMatrix) -> Self {
+	debug_assert_eq!(raw.Stype, S::STYPE, "wrong storage format for SuperMatrix");
+	debug_assert_eq!(raw.Dtype, T::DTYPE, "wrong value type for SuperMatrix");
+	Self {
+	    raw,
+	    marker: PhantomData,
+	}
+    }
+
+    /// The Store, interpreted according to the storage format
+    pub(crate) fn store(&self) -> &S::Format {
+	unsafe { &*(self.raw.Store as *const S::Format) }
+    }
+
+    /// Number of rows
+    pub fn nrow(&self) -> usize {
+	self.raw.nrow as usize
+    }
+
+    /// Number of columns
+    pub fn ncol(&self) -> usize {
+	self.raw.ncol as usize
+    }
+
+    /// The mathematical properties of the matrix (the Mtype)
+    pub fn mtype(&self) -> Mtype_t {
+	self.raw.Mtype
+    }
+
+    /// Change the recorded Mtype
+    ///
+    /// # Safety
+    ///
+    /// The matrix data must have the properties described by mtype.
+    pub(crate) unsafe fn set_mtype(&mut self, mtype: Mtype_t) {
+	self.raw.Mtype = mtype;
+    }
+
+    /// The underlying SuperMatrix struct
+    pub fn as_raw(&self) -> &c_SuperMatrix {
+	&self.raw
+    }
+
+    /// Pointer to the underlying SuperMatrix struct, for passing
+    /// to SuperLU routines.
+    pub fn as_mut_ptr(&mut self) -> *mut c_SuperMatrix {
+	&mut self.raw
+    }
+}
+
+impl<S: Storage, T: ValueType, O: Ownership> Drop for SuperMatrix<S, T, O> {
+    fn drop(&mut self) {
+	unsafe {
+	    O::destroy::<S>(&mut self.raw);
+	}
+    }
+}
+
+// The raw pointers in the Store are only reached through the matrix:
+// owned arrays belong to it alone, borrowed arrays are held as if by
+// a &'a mut slice, and the routines called through a shared
+// reference only read them.
+unsafe impl<S: Storage, T: ValueType, O: Ownership> Send for SuperMatrix<S, T, O> {}
+unsafe impl<S: Storage, T: ValueType, O: Ownership> Sync for SuperMatrix<S, T, O> {}
+
+impl<T: ValueType> SuperMatrix<NC, T> {
+    /// Create a compressed-column matrix by copying the arrays
+    ///
+    /// The non-zero values are stored column by column in nzval, with
+    /// the row index of each value in rowind. Column j occupies the
+    /// elements colptr[j] to colptr[j+1] - 1. The arrays are copied into
+    /// memory allocated by SuperLU, which is freed by
+    /// Destroy_CompCol_Matrix when the matrix is dropped.
+    pub fn new(nrow: usize, ncol: usize, nzval: &[T], rowind: &[i32], colptr: &[i32]) -> Self {
+	check_compressed(ncol, nrow, nzval.len(), rowind, colptr);
+	unsafe {
+	    let mut raw = MaybeUninit::<c_SuperMatrix>::uninit();
+	    T::c_create_comp_col_matrix(raw.as_mut_ptr(), nrow as i32, ncol as i32,
+					nzval.len() as i32, superlu_copy(nzval),
+					superlu_copy(rowind), superlu_copy(colptr),
+					Stype_t_SLU_NC, Mtype_t_SLU_GE);
+	    Self::from_raw(raw.assume_init())
+	}
+    }
+}
+
+impl<'a, T: ValueType> SuperMatrix<NC, T, Borrowed<'a>> {
+    /// Create a compressed-column matrix that uses the arrays in place
+    ///
+    /// No data is copied. The slices are borrowed mutably, because
+    /// some SuperLU routines (for example equilibration) modify the
+    /// matrix values.
+    pub fn from_slices(nrow: usize, ncol: usize, nzval: &'a mut [T],
+		       rowind: &'a mut [i32], colptr: &'a mut [i32]) -> Self {
+	check_compressed(ncol, nrow, nzval.len(), rowind, colptr);
+	unsafe {
+	    let mut raw = MaybeUninit::<c_SuperMatrix>::uninit();
+	    T::c_create_comp_col_matrix(raw.as_mut_ptr(), nrow as i32, ncol as i32,
+					nzval.len() as i32, nzval.as_mut_ptr(),
+					rowind.as_mut_ptr(), colptr.as_mut_ptr(),
+					Stype_t_SLU_NC, Mtype_t_SLU_GE);
+	    Self::from_raw(raw.assume_init())
+	}
+    }
+}
+
+impl<T: ValueType, O: Ownership> SuperMatrix<NC, T, O> {
+    /// Number of stored non-zero values
+    pub fn nnz(&self) -> usize {
+	self.store().nnz as usize
+    }
+
+    /// The non-zero values, column by column
+    pub fn nzval(&self) -> &[T] {
+	unsafe { std::slice::from_raw_parts(self.store().nzval as *const T, self.nnz()) }
+    }
+
+    /// The row index of each non-zero value
+    pub fn rowind(&self) -> &[i32] {
+	unsafe { std::slice::from_raw_parts(self.store().rowind, self.nnz()) }
+    }
+
+    /// The start of each column in nzval and rowind (ncol + 1 elements)
+    pub fn colptr(&self) -> &[i32] {
+	unsafe { std::slice::from_raw_parts(self.store().colptr, self.ncol() + 1) }
+    }
+
+    /// The transpose, as a compressed-row matrix using the same
+    /// arrays (the columns of A are the rows of A^T)
+    pub(crate) fn reinterpret_rows(&self) -> SuperMatrix<NR, T, Borrowed<'_>> {
+	let store = self.store();
+	unsafe {
+	    // The arrays are never written through the returned
+	    // matrix, which only has read-only accessors
+	    let mut raw = MaybeUninit::<c_SuperMatrix>::uninit();
+	    T::c_create_comp_row_matrix(raw.as_mut_ptr(), self.ncol() as i32, self.nrow() as i32,
+					store.nnz, store.nzval as *mut T, store.rowind,
+					store.colptr, Stype_t_SLU_NR, Mtype_t_SLU_GE);
+	    SuperMatrix::from_raw(raw.assume_init())
+	}
+    }
+}
+
+impl<T: ValueType> SuperMatrix<NR, T> {
+    /// Create a compressed-row matrix by copying the arrays
+    ///
+    /// This is the same as compressed-column storage with the roles
+    /// of rows and columns swapped: row i occupies the elements
+    /// rowptr[i] to rowptr[i+1] - 1 of nzval and colind.
+    pub fn new(nrow: usize, ncol: usize, nzval: &[T], colind: &[i32], rowptr: &[i32]) -> Self {
+	check_compressed(nrow, ncol, nzval.len(), colind, rowptr);
+	unsafe {
+	    let mut raw = MaybeUninit::<c_SuperMatrix>::uninit();
+	    T::c_create_comp_row_matrix(raw.as_mut_ptr(), nrow as i32, ncol as i32,
+					nzval.len() as i32, superlu_copy(nzval),
+					superlu_copy(colind), superlu_copy(rowptr),
+					Stype_t_SLU_NR, Mtype_t_SLU_GE);
+	    Self::from_raw(raw.assume_init())
+	}
+    }
+}
+
+impl<'a, T: ValueType> SuperMatrix<NR, T, Borrowed<'a>> {
+    /// Create a compressed-row matrix that uses the arrays in place
+    pub fn from_slices(nrow: usize, ncol: usize, nzval: &'a mut [T],
+		       colind: &'a mut [i32], rowptr: &'a mut [i32]) -> Self {
+	check_compressed(nrow, ncol, nzval.len(), colind, rowptr);
+	unsafe {
+	    let mut raw = MaybeUninit::<c_SuperMatrix>::uninit();
+	    T::c_create_comp_row_matrix(raw.as_mut_ptr(), nrow as i32, ncol as i32,
+					nzval.len() as i32, nzval.as_mut_ptr(),
+					colind.as_mut_ptr(), rowptr.as_mut_ptr(),
+					Stype_t_SLU_NR, Mtype_t_SLU_GE);
+	    Self::from_raw(raw.assume_init())
+	}
+    }
+}
+
+impl<T: ValueType, O: Ownership> SuperMatrix<NR, T, O> {
+    /// Number of stored non-zero values
+    pub fn nnz(&self) -> usize {
+	self.store().nnz as usize
+    }
+
+    /// The non-zero values, row by row
+    pub fn nzval(&self) -> &[T] {
+	unsafe { std::slice::from_raw_parts(self.store().nzval as *const T, self.nnz()) }
+    }
+
+    /// The column index of each non-zero value
+    pub fn colind(&self) -> &[i32] {
+	unsafe { std::slice::from_raw_parts(self.store().colind, self.nnz()) }
+    }
+
+    /// The start of each row in nzval and colind (nrow + 1 elements)
+    pub fn rowptr(&self) -> &[i32] {
+	unsafe { std::slice::from_raw_parts(self.store().rowptr, self.nrow() + 1) }
+    }
+
+    /// The transpose, as a compressed-column matrix using the same
+    /// arrays (the rows of A are the columns of A^T)
+    pub(crate) fn reinterpret_cols(&self) -> SuperMatrix<NC, T, Borrowed<'_>> {
+	let store = self.store();
+	unsafe {
+	    let mut raw = MaybeUninit::<c_SuperMatrix>::uninit();
+	    T::c_create_comp_col_matrix(raw.as_mut_ptr(), self.ncol() as i32, self.nrow() as i32,
+					store.nnz, store.nzval as *mut T, store.colind,
+					store.rowptr, Stype_t_SLU_NC, Mtype_t_SLU_GE);
+	    SuperMatrix::from_raw(raw.assume_init())
+	}
+    }
+}
+
+impl<T: ValueType, O: Ownership> SuperMatrix<NCP, T, O> {
+    /// Number of stored non-zero values
+    pub fn nnz(&self) -> usize {
+	self.store().nnz as usize
+    }
+
+    /// The start of each (permuted) column in the value and row
+    /// index arrays
+    pub fn colbeg(&self) -> &[i32] {
+	unsafe { std::slice::from_raw_parts(self.store().colbeg, self.ncol()) }
+    }
+
+    /// One past the end of each (permuted) column in the value and
+    /// row index arrays
+    pub fn colend(&self) -> &[i32] {
+	unsafe { std::slice::from_raw_parts(self.store().colend, self.ncol()) }
+    }
+}
+
+impl<T: ValueType> SuperMatrix<SC, T> {
+    /// Number of stored non-zero values
+    pub fn nnz(&self) -> usize {
+	self.store().nnz as usize
+    }
+
+    /// Number of supernodes
+    pub fn num_supernodes(&self) -> usize {
+	// The Store records the index of the last supernode
+	self.store().nsuper as usize + 1
+    }
+
+    /// The diagonal of U, which SuperLU stores in the diagonal blocks
+    /// of the supernodes of L
+    ///
+    /// This does the same as dGetDiagU (which SuperLU only provides
+    /// in double precision).
+    pub fn diag_u(&self) -> Vec<T> {
+	let store = self.store();
+	let ncol = self.ncol();
+	let nsuper = self.num_supernodes();
+	unsafe {
+	    let nzval = store.nzval as *const T;
+	    let nzval_colptr = std::slice::from_raw_parts(store.nzval_colptr, ncol + 1);
+	    let rowind_colptr = std::slice::from_raw_parts(store.rowind_colptr, ncol + 1);
+	    let sup_to_col = std::slice::from_raw_parts(store.sup_to_col, nsuper + 1);
+	    let mut diag = Vec::with_capacity(ncol);
+	    for k in 0..nsuper {
+		let fsupc = sup_to_col[k] as usize;
+		let nsupc = sup_to_col[k + 1] as usize - fsupc;
+		// Each supernode is a dense column-major block with nsupr rows
+		let nsupr = (rowind_colptr[fsupc + 1] - rowind_colptr[fsupc]) as usize;
+		let luptr = nzval_colptr[fsupc] as usize;
+		diag.extend((0..nsupc).map(|i| *nzval.add(luptr + i * (nsupr + 1))));
+	    }
+	    diag
+	}
+    }
+}
+
+impl<T: ValueType> SuperMatrix<DN, T> {
+    /// Create a dense matrix by copying column-major values
+    ///
+    /// The values are copied into memory allocated by SuperLU, which
+    /// is freed by Destroy_Dense_Matrix when the matrix is dropped.
+    pub fn new(nrow: usize, ncol: usize, values: &[T]) -> Self {
+	assert_eq!(values.len(), nrow * ncol, "values must have nrow * ncol elements");
+	unsafe {
+	    let mut raw = MaybeUninit::<c_SuperMatrix>::uninit();
+	    T::c_create_dense_matrix(raw.as_mut_ptr(), nrow as i32, ncol as i32,
+				     superlu_copy(values), nrow as i32,
+				     Stype_t_SLU_DN, Mtype_t_SLU_GE);
+	    Self::from_raw(raw.assume_init())
+	}
+    }
+}
+
+impl<'a, T: ValueType> SuperMatrix<DN, T, Borrowed<'a>> {
+    /// Create a dense matrix that uses column-major values in place
+    pub fn from_slice(nrow: usize, ncol: usize, values: &'a mut [T]) -> Self {
+	assert_eq!(values.len(), nrow * ncol, "values must have nrow * ncol elements");
+	unsafe {
+	    let mut raw = MaybeUninit::<c_SuperMatrix>::uninit();
+	    T::c_create_dense_matrix(raw.as_mut_ptr(), nrow as i32, ncol as i32,
+				     values.as_mut_ptr(), nrow as i32,
+				     Stype_t_SLU_DN, Mtype_t_SLU_GE);
+	    Self::from_raw(raw.assume_init())
+	}
+    }
+}
+
+impl<T: ValueType, O: Ownership> SuperMatrix<DN, T, O> {
+    /// Leading dimension (distance between the starts of columns)
+    pub fn lda(&self) -> usize {
+	self.store().lda as usize
+    }
+
+    /// All the values, in column-major order
+    pub fn values(&self) -> &[T] {
+	let len = self.lda() * self.ncol();
+	unsafe { std::slice::from_raw_parts(self.store().nzval as *const T, len) }
+    }
+
+    /// All the values, in column-major order
+    pub fn values_mut(&mut self) -> &mut [T] {
+	let len = self.lda() * self.ncol();
+	unsafe { std::slice::from_raw_parts_mut(self.store().nzval as *mut T, len) }
+    }
+}
@@ -0,0 +1,141 @@
+//! Symbolic analysis reused across numeric factorizations
+//!
+//! When a sequence of matrices share one sparsity pattern (for
+//! example, the Jacobians in a Newton iteration), the column ordering
+//! and the column elimination tree only need to be computed once.
+//! SuperLU supports this with the SamePattern and
+//! SamePattern_SameRowPerm values of the Fact option, which skip the
+//! ordering (and, for SamePattern_SameRowPerm, the choice of pivots
+//! and the allocation of L and U) on the later factorizations.
+
+use crate::error::SolveError;
+use crate::lu_factorization::LuFactorization;
+use crate::options::{Options, Trans};
+use crate::ordering::Permutation;
+use crate::preorder::{column_permutation, preorder};
+use crate::simple_driver::SystemMatrix;
+use crate::super_matrix::CompColMatrix;
+use crate::value_type::ValueType;
+use crate::fact_t_SamePattern;
+
+/// The column ordering and elimination tree of a sparsity pattern,
+/// and the latest numeric factorization of a matrix with that pattern
+#[derive(Debug)]
+pub struct SymbolicLu<T: ValueType> {
+    options: Options,
+    trans: Trans,
+    rowind: Vec<i32>,
+    colptr: Vec<i32>,
+    perm_c: Vec<i32>,
+    etree: Vec<i32>,
+    lu: Option<LuFactorization<T>>,
+}
+
+impl<T: ValueType> SymbolicLu<T> {
+    /// Analyze the sparsity pattern of A (the values are not used)
+    ///
+    /// The options set the column ordering, and are also used for
+    /// every later factorization (the Fact option is ignored).
+    pub fn new<A: SystemMatrix<T>>(a: &A, options: &Options) -> Self {
+	let (a, trans) = a.comp_col_copy();
+	assert_eq!(a.nrow(), a.ncol(), "A must be square");
+	let perm_c = column_permutation(options, &a);
+	Self::analyze(a, trans, &perm_c, options)
+    }
+
+    /// Analyze the sparsity pattern of A with the column permutation
+    /// perm_c, instead of the ordering in the options
+    ///
+    /// See factorize_with_permutation for how perm_c is used. Panics if
+    /// it does not have one element for each column of A.
+    pub fn with_permutation<A: SystemMatrix<T>>(a: &A, perm_c: &Permutation, options: &Options) -> Self {
+	let (a, trans) = a.comp_col_copy();
+	assert_eq!(a.nrow(), a.ncol(), "A must be square");
+	assert_eq!(perm_c.len(), a.ncol(), "perm_c must have one element for each column");
+	Self::analyze(a, trans, perm_c.as_slice(), &options.user_column_ordering())
+    }
+
+    /// Postorder the elimination tree of A with the column permutation
+    /// perm_c
+    fn analyze(a: CompColMatrix<T>, trans: Trans, perm_c: &[i32], options: &Options) -> Self {
+	let (perm_c, etree) = {
+	    let ac = preorder(options, &a, perm_c);
+	    (ac.perm_c().to_vec(), ac.etree().to_vec())
+	};
+	Self {
+	    options: *options,
+	    trans,
+	    rowind: a.rowind().to_vec(),
+	    colptr: a.colptr().to_vec(),
+	    perm_c,
+	    etree,
+	    lu: None,
+	}
+    }
+
+    /// The column permutation (after postordering)
+    pub fn perm_c(&self) -> &[i32] {
+	&self.perm_c
+    }
+
+    /// The column elimination tree
+    pub fn etree(&self) -> &[i32] {
+	&self.etree
+    }
+
+    /// The latest factorization, if there is one
+    pub fn factorization(&mut self) -> Option<&mut LuFactorization<T>> {
+	self.lu.as_mut()
+    }
+
+    /// Copy A, checking it has the analyzed pattern
+    fn checked_copy<A: SystemMatrix<T>>(&self, a: &A) -> Result<CompColMatrix<T>, SolveError> {
+	let (a, trans) = a.comp_col_copy();
+	if trans != self.trans || a.colptr() != &self.colptr[..] || a.rowind() != &self.rowind[..] {
+	    return Err(SolveError::PatternMismatch);
+	}
+	Ok(a)
+    }
+
+    /// Factorize A, which must have the analyzed pattern, using the
+    /// stored column ordering and elimination tree (Fact = SamePattern)
+    ///
+    /// The rows are pivoted as usual. Returns
+    /// SolveError::PatternMismatch if the pattern of A is different
+    /// (including the order of the row indices in each column).
+    pub fn refactor<A: SystemMatrix<T>>(&mut self, a: &A) -> Result<&mut LuFactorization<T>, SolveError> {
+	let a = self.checked_copy(a)?;
+	// Free the old factors first, since new ones are allocated
+	self.lu = None;
+	let lu = LuFactorization::compute(a, self.trans, &self.options, fact_t_SamePattern,
+					  self.perm_c.clone(), self.etree.clone())?;
+	Ok(self.lu.insert(lu))
+    }
+
+    /// Factorize A, which must have the analyzed pattern, reusing the
+    /// row permutation and the storage of the latest factorization
+    /// (Fact = SamePattern_SameRowPerm)
+    ///
+    /// This is the cheapest refactorization, but the old pivots may be
+    /// unstable for the new values; if in doubt, check the pivot
+    /// growth or the residual. If there is no earlier factorization
+    /// (or the last one failed), this is the same as refactor.
+    pub fn refactor_same_row_perm<A: SystemMatrix<T>>(&mut self, a: &A)
+						      -> Result<&mut LuFactorization<T>, SolveError> {
+	let a = self.checked_copy(a)?;
+	match self.lu.as_mut() {
+	    Some(lu) => {
+		if let Err(err) = lu.refactor_same_row_perm(a, &self.options) {
+		    self.lu = None;
+		    return Err(err);
+		}
+		Ok(self.lu.as_mut().unwrap())
+	    }
+	    None => {
+		let lu = LuFactorization::compute(a, self.trans, &self.options, fact_t_SamePattern,
+						  self.perm_c.clone(), self.etree.clone())?;
+		Ok(self.lu.insert(lu))
+	    }
+	}
+    }
+}
@@ -0,0 +1,97 @@
+//! Symmetric and Hermitian matrices stored as one triangle
+//!
+//! SuperLU can record that a compressed-column matrix holds only the
+//! lower (or upper) half of a symmetric or Hermitian matrix, using the
+//! Mtype values SLU_SYL, SLU_SYU, SLU_HEL and SLU_HEU. The LU drivers
+//! only work with general (SLU_GE) matrices, so half-stored matrices
+//! are expanded to full storage before they are factorized.
+
+use crate::super_matrix::{CompColMatrix, Ownership, SuperMatrix, NC};
+use crate::value_type::ValueType;
+use crate::{Mtype_t, Mtype_t_SLU_GE, Mtype_t_SLU_HEL, Mtype_t_SLU_HEU, Mtype_t_SLU_SYL, Mtype_t_SLU_SYU};
+
+impl<T: ValueType> SuperMatrix<NC, T> {
+    /// Create a symmetric matrix from its lower triangle
+    ///
+    /// The arrays describe an n x n compressed-column matrix holding
+    /// the diagonal and the elements below it (row >= column). They
+    /// are copied, and the matrix is recorded as SLU_SYL.
+    pub fn symmetric_lower(n: usize, nzval: &[T], rowind: &[i32], colptr: &[i32]) -> Self {
+	Self::lower_triangle(n, nzval, rowind, colptr, Mtype_t_SLU_SYL)
+    }
+
+    /// Create a Hermitian matrix from its lower triangle
+    ///
+    /// The same as symmetric_lower, except that the upper triangle is
+    /// the conjugate of the lower triangle. The matrix is recorded as
+    /// SLU_HEL.
+    pub fn hermitian_lower(n: usize, nzval: &[T], rowind: &[i32], colptr: &[i32]) -> Self {
+	Self::lower_triangle(n, nzval, rowind, colptr, Mtype_t_SLU_HEL)
+    }
+
+    fn lower_triangle(n: usize, nzval: &[T], rowind: &[i32], colptr: &[i32], mtype: Mtype_t) -> Self {
+	let mut a = Self::new(n, n, nzval, rowind, colptr);
+	for j in 0..n {
+	    let (start, end) = (colptr[j] as usize, colptr[j + 1] as usize);
+	    assert!(rowind[start..end].iter().all(|&i| i as usize >= j),
+		    "element above the diagonal in column {} of a lower-triangle matrix", j);
+	}
+	unsafe {
+	    a.set_mtype(mtype);
+	}
+	a
+    }
+}
+
+impl<T: ValueType, O: Ownership> SuperMatrix<NC, T, O> {
+    /// True if only one triangle of a symmetric or Hermitian matrix
+    /// is stored
+    pub fn is_half_stored(&self) -> bool {
+	matches!(self.mtype(), Mtype_t_SLU_SYL | Mtype_t_SLU_SYU | Mtype_t_SLU_HEL | Mtype_t_SLU_HEU)
+    }
+
+    /// Copy the matrix into full (SLU_GE) storage
+    ///
+    /// Half-stored symmetric and Hermitian matrices are expanded by
+    /// mirroring each off-diagonal element (conjugated, for Hermitian
+    /// matrices). Other matrices are copied unchanged. The row indices
+    /// in each column of the result are sorted.
+    pub fn to_general(&self) -> CompColMatrix<T> {
+	let (nzval, rowind, colptr) = (self.nzval(), self.rowind(), self.colptr());
+	if !self.is_half_stored() {
+	    let mut a = CompColMatrix::new(self.nrow(), self.ncol(), nzval, rowind, colptr);
+	    unsafe {
+		a.set_mtype(Mtype_t_SLU_GE);
+	    }
+	    return a;
+	}
+
+	let hermitian = matches!(self.mtype(), Mtype_t_SLU_HEL | Mtype_t_SLU_HEU);
+	let n = self.ncol();
+	let mut columns = vec![Vec::<(i32, T)>::new(); n];
+	for j in 0..n {
+	    for k in colptr[j] as usize..colptr[j + 1] as usize {
+		let (i, value) = (rowind[k] as usize, nzval[k]);
+		columns[j].push((i as i32, value));
+		if i != j {
+		    let mirror = if hermitian { value.conj() } else { value };
+		    columns[i].push((j as i32, mirror));
+		}
+	    }
+	}
+
+	let mut full_nzval = Vec::with_capacity(2 * nzval.len());
+	let mut full_rowind = Vec::with_capacity(2 * nzval.len());
+	let mut full_colptr = Vec::with_capacity(n + 1);
+	full_colptr.push(0);
+	for mut column in columns {
+	    column.sort_by_key(|&(i, _)| i);
+	    for (i, value) in column {
+		full_rowind.push(i);
+		full_nzval.push(value);
+	    }
+	    full_colptr.push(full_rowind.len() as i32);
+	}
+	CompColMatrix::new(n, n, &full_nzval, &full_rowind, &full_colptr)
+    }
+}
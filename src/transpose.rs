@@ -0,0 +1,171 @@
+//! Transposed and conjugate-transposed matrices
+//!
+//! The arrays of a compressed-column matrix A, read as compressed-row
+//! arrays, describe A^T. The transpose and adjoint methods use this
+//! to make views without copying anything; the view records whether
+//! it stands for A^T or A^H, so that a solve using it picks TRANS or
+//! CONJ. The to_transpose and to_adjoint methods make a new
+//! compressed-column matrix instead, for when a real copy is needed.
+
+use crate::error::SolveError;
+use crate::factorization::Factorization;
+use crate::options::{Options, Trans};
+use crate::preorder::{column_permutation, preorder};
+use crate::simple_driver::SystemMatrix;
+use crate::super_matrix::{Borrowed, CompColMatrix, CompRowMatrix, Ownership, SuperMatrix, NC};
+use crate::value_type::ValueType;
+use crate::{
+    Mtype_t, Mtype_t_SLU_HEL, Mtype_t_SLU_HEU, Mtype_t_SLU_SYL, Mtype_t_SLU_SYU,
+    Mtype_t_SLU_TRL, Mtype_t_SLU_TRLU, Mtype_t_SLU_TRU, Mtype_t_SLU_TRUU,
+};
+
+/// The transpose (A^T) or adjoint (A^H) of a compressed-column matrix
+/// A, stored as a compressed-row matrix that uses the arrays of A
+#[derive(Debug)]
+pub struct Transposed<'a, T: ValueType> {
+    matrix: CompRowMatrix<T, Borrowed<'a>>,
+    trans: Trans,
+}
+
+impl<'a, T: ValueType> Transposed<'a, T> {
+    /// The transpose of A as a compressed-row matrix (this holds the
+    /// values of A, which are not conjugated for an adjoint view)
+    pub fn matrix(&self) -> &CompRowMatrix<T, Borrowed<'a>> {
+	&self.matrix
+    }
+
+    /// Trans for a transpose view, or Conj for an adjoint view
+    pub fn trans(&self) -> Trans {
+	self.trans
+    }
+
+    /// Number of rows (the number of columns of A)
+    pub fn nrow(&self) -> usize {
+	self.matrix.nrow()
+    }
+
+    /// Number of columns (the number of rows of A)
+    pub fn ncol(&self) -> usize {
+	self.matrix.ncol()
+    }
+
+    /// Factorize A, returning factors that solve the transposed (or
+    /// conjugate-transposed) system
+    ///
+    /// The columns are ordered as set in the options. The solve
+    /// method of the returned Factorization uses TRANS for a
+    /// transpose view, and CONJ for an adjoint view. Panics if A is
+    /// not square.
+    pub fn factorize(&self, options: &Options) -> Result<Factorization<T>, SolveError> {
+	let a = self.matrix.reinterpret_cols();
+	assert_eq!(a.nrow(), a.ncol(), "A must be square");
+	let perm_c = column_permutation(options, &a);
+	let factors = preorder(options, &a, &perm_c).factorize(options)?;
+	Ok(factors.with_trans(self.trans))
+    }
+}
+
+impl<T: ValueType, O: Ownership> SuperMatrix<NC, T, O> {
+    /// A^T, without copying
+    ///
+    /// A must be stored in full (see to_general), otherwise this
+    /// function panics.
+    pub fn transpose(&self) -> Transposed<'_, T> {
+	assert!(!self.is_half_stored(), "transpose needs a matrix stored in full (see to_general)");
+	Transposed {
+	    matrix: self.reinterpret_rows(),
+	    trans: Trans::Trans,
+	}
+    }
+
+    /// A^H (the conjugate transpose), without copying
+    ///
+    /// A must be stored in full (see to_general), otherwise this
+    /// function panics.
+    pub fn adjoint(&self) -> Transposed<'_, T> {
+	assert!(!self.is_half_stored(), "adjoint needs a matrix stored in full (see to_general)");
+	Transposed {
+	    matrix: self.reinterpret_rows(),
+	    trans: Trans::Conj,
+	}
+    }
+
+    /// A new compressed-column matrix holding A^T
+    pub fn to_transpose(&self) -> CompColMatrix<T> {
+	self.transposed_copy(|x| x)
+    }
+
+    /// A new compressed-column matrix holding A^H
+    pub fn to_adjoint(&self) -> CompColMatrix<T> {
+	self.transposed_copy(T::conj)
+    }
+
+    /// Copy the transpose of A, applying f to each value. The rows of
+    /// each column of the result are in increasing order.
+    fn transposed_copy(&self, f: impl Fn(T) -> T) -> CompColMatrix<T> {
+	let (nrow, ncol, nnz) = (self.nrow(), self.ncol(), self.nnz());
+	let (nzval, rowind, colptr) = (self.nzval(), self.rowind(), self.colptr());
+
+	// Column i of the result holds row i of A
+	let mut new_colptr = vec![0; nrow + 1];
+	for &i in rowind {
+	    new_colptr[i as usize + 1] += 1;
+	}
+	for i in 0..nrow {
+	    new_colptr[i + 1] += new_colptr[i];
+	}
+	let mut next = new_colptr.clone();
+	let mut new_nzval = vec![T::default(); nnz];
+	let mut new_rowind = vec![0; nnz];
+	for j in 0..ncol {
+	    for k in colptr[j] as usize..colptr[j + 1] as usize {
+		let dest = &mut next[rowind[k] as usize];
+		new_nzval[*dest as usize] = f(nzval[k]);
+		new_rowind[*dest as usize] = j as i32;
+		*dest += 1;
+	    }
+	}
+	let mut result = CompColMatrix::new(ncol, nrow, &new_nzval, &new_rowind, &new_colptr);
+	unsafe {
+	    // The transpose of a half-stored matrix is stored in the
+	    // other half
+	    result.set_mtype(transposed_mtype(self.mtype()));
+	}
+	result
+    }
+}
+
+/// A general compressed-column copy of A, oriented like a factorized
+/// matrix M with A = M, A = M^T or A = M^H (depending on trans)
+pub(crate) fn oriented_copy<T: ValueType, A: SystemMatrix<T>>(a: &A, trans: Trans) -> CompColMatrix<T> {
+    let (m, stored) = a.comp_col_copy();
+    if stored == trans {
+	return m;
+    }
+    // A = op(M) for both, and op is its own inverse
+    let a = match stored {
+	Trans::NoTrans => m,
+	Trans::Trans => m.to_transpose(),
+	Trans::Conj => m.to_adjoint(),
+    };
+    match trans {
+	Trans::NoTrans => a,
+	Trans::Trans => a.to_transpose(),
+	Trans::Conj => a.to_adjoint(),
+    }
+}
+
+/// The Mtype of the transpose of a matrix
+fn transposed_mtype(mtype: Mtype_t) -> Mtype_t {
+    match mtype {
+	Mtype_t_SLU_SYL => Mtype_t_SLU_SYU,
+	Mtype_t_SLU_SYU => Mtype_t_SLU_SYL,
+	Mtype_t_SLU_HEL => Mtype_t_SLU_HEU,
+	Mtype_t_SLU_HEU => Mtype_t_SLU_HEL,
+	Mtype_t_SLU_TRL => Mtype_t_SLU_TRU,
+	Mtype_t_SLU_TRU => Mtype_t_SLU_TRL,
+	Mtype_t_SLU_TRLU => Mtype_t_SLU_TRUU,
+	Mtype_t_SLU_TRUU => Mtype_t_SLU_TRLU,
+	other => other,
+    }
+}
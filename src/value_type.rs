@@ -0,0 +1,1490 @@
+//! Matrix value types
+//!
+//! SuperLU is compiled in four precisions (single and double, real
+//! and complex), and each routine exists in four versions whose names
+//! differ only in the first letter (s, d, c or z). The ValueType trait
+//! picks the right version based on the Rust type of the matrix
+//! values, so that the safe wrappers in this crate can be written
+//! once for all precisions.
+
+use num::Complex;
+
+use crate::{
+    cCreate_CompCol_Matrix, cCreate_CompRow_Matrix, cCreate_Dense_Matrix, complex,
+    dCreate_CompCol_Matrix, dCreate_CompRow_Matrix, dCreate_Dense_Matrix, doublecomplex,
+    sCreate_CompCol_Matrix, sCreate_CompRow_Matrix, sCreate_Dense_Matrix,
+    zCreate_CompCol_Matrix, zCreate_CompRow_Matrix, zCreate_Dense_Matrix, Dtype_t,
+    Dtype_t_SLU_C, Dtype_t_SLU_D, Dtype_t_SLU_S, Dtype_t_SLU_Z, Mtype_t, Stype_t,
+    SuperMatrix as c_SuperMatrix, cgssv, cgssvx, cgstrf, cgstrs, dgssv, dgssvx, dgstrf,
+    dgstrs, mem_usage_t, sgssv, sgssvx, sgstrf, sgstrs, zgssv, zgssvx, zgstrf, zgstrs,
+    superlu_options_t, trans_t, GlobalLU_t, SuperLUStat_t,
+    cgsisx, dgsisx, sgsisx, zgsisx, ilu_cQuerySpace, ilu_dQuerySpace, ilu_sQuerySpace,
+    ilu_zQuerySpace, sp_cgemv, sp_dgemv, sp_sgemv, sp_zgemv, cgscon, dgscon, sgscon, zgscon,
+    cgsequ, dgsequ, sgsequ, zgsequ, claqgs, dlaqgs, slaqgs, zlaqgs,
+    cgsrfs, dgsrfs, sgsrfs, zgsrfs, cPivotGrowth, dPivotGrowth, sPivotGrowth, zPivotGrowth,
+    cldperm, dldperm, sldperm, zldperm,
+};
+
+// The xlangs routines are compiled into the library, but are not
+// declared in the SuperLU headers, so bindgen does not see them
+extern "C" {
+    fn slangs(norm: *mut libc::c_char, a: *mut c_SuperMatrix) -> f32;
+    fn dlangs(norm: *mut libc::c_char, a: *mut c_SuperMatrix) -> f64;
+    fn clangs(norm: *mut libc::c_char, a: *mut c_SuperMatrix) -> f32;
+    fn zlangs(norm: *mut libc::c_char, a: *mut c_SuperMatrix) -> f64;
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+    impl Sealed for num::Complex<f32> {}
+    impl Sealed for num::Complex<f64> {}
+}
+
+/// The type of the values stored in a matrix
+///
+/// This trait is implemented for f32, f64, Complex<f32> and
+/// Complex<f64>, which have the same memory layout as the SuperLU
+/// types float, double, complex and doublecomplex. It is sealed,
+/// because the safe wrappers rely on the dispatch below being
+/// correct.
+///
+/// The methods are thin wrappers around the C functions, with the
+/// Dtype argument filled in from the implementing type (passing any
+/// other Dtype to the C functions leads to memory errors).
+pub trait ValueType:
+    private::Sealed + Copy + Default + PartialEq + std::fmt::Debug + num::traits::NumAssign
+    + Send + Sync + 'static
+{
+    /// The SuperLU Dtype for this value type
+    const DTYPE: Dtype_t;
+
+    /// The real type of the same precision (f32 or f64), used by
+    /// SuperLU for scale factors, norms and error bounds
+    type Real: num::Float + Default + std::fmt::Debug + Into<f64> + Send + Sync + 'static;
+
+    /// Call (s,d,c,z)Create_CompCol_Matrix
+    ///
+    /// # Safety
+    ///
+    /// The arrays must be valid for nnz (nzval, rowind) and n + 1
+    /// (colptr) elements for as long as the matrix is in use.
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn c_create_comp_col_matrix(
+	a: *mut c_SuperMatrix,
+	m: i32,
+	n: i32,
+	nnz: i32,
+	nzval: *mut Self,
+	rowind: *mut i32,
+	colptr: *mut i32,
+	stype: Stype_t,
+	mtype: Mtype_t,
+    );
+
+    /// Call (s,d,c,z)Create_Dense_Matrix
+    ///
+    /// # Safety
+    ///
+    /// The values array must be valid for ldx * n elements for as
+    /// long as the matrix is in use.
+    unsafe fn c_create_dense_matrix(
+	x: *mut c_SuperMatrix,
+	m: i32,
+	n: i32,
+	values: *mut Self,
+	ldx: i32,
+	stype: Stype_t,
+	mtype: Mtype_t,
+    );
+
+    /// Call (s,d,c,z)Create_CompRow_Matrix
+    ///
+    /// # Safety
+    ///
+    /// The arrays must be valid for nnz (nzval, colind) and m + 1
+    /// (rowptr) elements for as long as the matrix is in use.
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn c_create_comp_row_matrix(
+	a: *mut c_SuperMatrix,
+	m: i32,
+	n: i32,
+	nnz: i32,
+	nzval: *mut Self,
+	colind: *mut i32,
+	rowptr: *mut i32,
+	stype: Stype_t,
+	mtype: Mtype_t,
+    );
+
+    /// Complex conjugate (the identity for real types)
+    fn conj(self) -> Self;
+
+    /// Call (s,d,c,z)gstrf
+    ///
+    /// # Safety
+    ///
+    /// A must be an SLU_NCP matrix of this value type, and the arrays
+    /// must have the lengths described in the SuperLU documentation.
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn c_gstrf(
+	options: *mut superlu_options_t,
+	a: *mut c_SuperMatrix,
+	relax: i32,
+	panel_size: i32,
+	etree: *mut i32,
+	work: *mut libc::c_void,
+	lwork: i32,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	glu: *mut GlobalLU_t,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    );
+
+    /// Call (s,d,c,z)gstrs
+    ///
+    /// # Safety
+    ///
+    /// L and U must be factors computed by c_gstrf for this value
+    /// type, with the matching permutations, and B must be a dense
+    /// matrix with as many rows as L.
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn c_gstrs(
+	trans: trans_t,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	b: *mut c_SuperMatrix,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    );
+
+    /// Call (s,d,c,z)gssv
+    ///
+    /// # Safety
+    ///
+    /// A must be an SLU_NC or SLU_NR matrix and B an SLU_DN matrix of
+    /// this value type, and perm_c and perm_r must have room for the
+    /// number of columns and rows of A.
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn c_gssv(
+	options: *mut superlu_options_t,
+	a: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	b: *mut c_SuperMatrix,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    );
+
+    /// Call (s,d,c,z)gssvx
+    ///
+    /// # Safety
+    ///
+    /// A must be an SLU_NC or SLU_NR matrix and B and X SLU_DN
+    /// matrices of this value type, and the arrays must have the
+    /// lengths described in the SuperLU documentation.
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn c_gssvx(
+	options: *mut superlu_options_t,
+	a: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	etree: *mut i32,
+	equed: *mut libc::c_char,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	work: *mut libc::c_void,
+	lwork: i32,
+	b: *mut c_SuperMatrix,
+	x: *mut c_SuperMatrix,
+	recip_pivot_growth: *mut Self::Real,
+	rcond: *mut Self::Real,
+	ferr: *mut Self::Real,
+	berr: *mut Self::Real,
+	glu: *mut GlobalLU_t,
+	mem_usage: *mut mem_usage_t,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    );
+
+    /// Multiply by a real scale factor
+    fn scale(self, factor: Self::Real) -> Self;
+
+    /// Call (s,d,c,z)gsisx
+    ///
+    /// # Safety
+    ///
+    /// A must be an SLU_NC or SLU_NR matrix and B and X SLU_DN
+    /// matrices of this value type, and the arrays must have the
+    /// lengths described in the SuperLU documentation.
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn c_gsisx(
+	options: *mut superlu_options_t,
+	a: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	etree: *mut i32,
+	equed: *mut libc::c_char,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	work: *mut libc::c_void,
+	lwork: i32,
+	b: *mut c_SuperMatrix,
+	x: *mut c_SuperMatrix,
+	recip_pivot_growth: *mut Self::Real,
+	rcond: *mut Self::Real,
+	glu: *mut GlobalLU_t,
+	mem_usage: *mut mem_usage_t,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    );
+
+    /// Call ilu_(s,d,c,z)QuerySpace
+    ///
+    /// # Safety
+    ///
+    /// L and U must be the factors computed by c_gsisx.
+    unsafe fn c_ilu_query_space(
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	mem_usage: *mut mem_usage_t,
+    ) -> i32;
+
+    /// Call sp_(s,d,c,z)gemv, which computes
+    /// y = alpha op(A) x + beta y
+    ///
+    /// # Safety
+    ///
+    /// A must be an SLU_NC matrix of this value type, trans must
+    /// point to "N", "T" or "C", and x and y must be long enough for
+    /// op(A) and the increments.
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn c_sp_gemv(
+	trans: *mut libc::c_char,
+	alpha: Self,
+	a: *mut c_SuperMatrix,
+	x: *const Self,
+	incx: i32,
+	beta: Self,
+	y: *mut Self,
+	incy: i32,
+    ) -> i32;
+
+    /// The absolute value (the modulus for complex types)
+    fn abs(self) -> Self::Real;
+
+    /// The value with real part re (and imaginary part zero)
+    fn from_real(re: Self::Real) -> Self;
+
+    /// Call (s,d,c,z)langs, which returns a norm of A
+    ///
+    /// # Safety
+    ///
+    /// A must be an SLU_NC matrix of this value type, and norm must
+    /// point to "1", "O", "I", "M" or "F".
+    unsafe fn c_langs(norm: *mut libc::c_char, a: *mut c_SuperMatrix) -> Self::Real;
+
+    /// Call (s,d,c,z)gscon
+    ///
+    /// # Safety
+    ///
+    /// L and U must be the factors of a matrix of this value type,
+    /// and norm must point to "1", "O" or "I".
+    unsafe fn c_gscon(
+	norm: *mut libc::c_char,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	anorm: Self::Real,
+	rcond: *mut Self::Real,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    );
+
+    /// Call (s,d,c,z)gsequ, which computes row and column scale
+    /// factors for A
+    ///
+    /// # Safety
+    ///
+    /// A must be an SLU_NC, SLU_GE matrix of this value type, r must
+    /// have A.nrow elements and c must have A.ncol elements.
+    unsafe fn c_gsequ(
+	a: *mut c_SuperMatrix,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	rowcnd: *mut Self::Real,
+	colcnd: *mut Self::Real,
+	amax: *mut Self::Real,
+	info: *mut i32,
+    );
+
+    /// Call (s,d,c,z)laqgs, which scales A in place with the factors
+    /// computed by c_gsequ (if that is worthwhile) and sets equed
+    ///
+    /// # Safety
+    ///
+    /// A must be an SLU_NC matrix of this value type whose values can
+    /// be written, r must have A.nrow elements and c must have A.ncol
+    /// elements.
+    unsafe fn c_laqgs(
+	a: *mut c_SuperMatrix,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	rowcnd: Self::Real,
+	colcnd: Self::Real,
+	amax: Self::Real,
+	equed: *mut libc::c_char,
+    );
+    /// Call (s,d,c,z)gsrfs, which refines the solutions X of A X = B
+    /// and bounds their errors
+    ///
+    /// # Safety
+    ///
+    /// A must be an SLU_NC, SLU_GE matrix of this value type, L and U
+    /// its factors computed by c_gstrf (or c_gssvx) with the matching
+    /// permutations, equed must point to "N", "R", "C" or "B", r and c
+    /// must have A.nrow and A.ncol elements, B and X must be dense
+    /// matrices with A.nrow rows and the same number of columns, and
+    /// ferr and berr must have one element for each column.
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn c_gsrfs(
+	trans: trans_t,
+	a: *mut c_SuperMatrix,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	equed: *mut libc::c_char,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	b: *mut c_SuperMatrix,
+	x: *mut c_SuperMatrix,
+	ferr: *mut Self::Real,
+	berr: *mut Self::Real,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    );
+    /// Call (s,d,c,z)PivotGrowth, which returns the reciprocal pivot
+    /// growth factor of the first ncols columns
+    ///
+    /// # Safety
+    ///
+    /// A must be an SLU_NC matrix of this value type, and L and U its
+    /// factors computed with the column permutation perm_c.
+    unsafe fn c_pivot_growth(
+	ncols: i32,
+	a: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+    ) -> Self::Real;
+
+    /// Call (s,d,c,z)ldperm, which runs the MC64 matching code to find
+    /// a row permutation with large diagonal entries, returning the
+    /// MC64 info code
+    ///
+    /// # Safety
+    ///
+    /// colptr (n + 1 elements), adjncy and nzval (nnz elements each)
+    /// must describe a square compressed-column matrix, which is
+    /// changed during the call but restored before it returns. perm,
+    /// u and v must have n elements.
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn c_ldperm(
+	job: i32,
+	n: i32,
+	nnz: i32,
+	colptr: *mut i32,
+	adjncy: *mut i32,
+	nzval: *mut Self,
+	perm: *mut i32,
+	u: *mut Self::Real,
+	v: *mut Self::Real,
+    ) -> i32;
+}
+
+impl ValueType for f32 {
+    const DTYPE: Dtype_t = Dtype_t_SLU_S;
+    type Real = f32;
+
+    unsafe fn c_create_comp_col_matrix(
+	a: *mut c_SuperMatrix,
+	m: i32,
+	n: i32,
+	nnz: i32,
+	nzval: *mut Self,
+	rowind: *mut i32,
+	colptr: *mut i32,
+	stype: Stype_t,
+	mtype: Mtype_t,
+    ) {
+	sCreate_CompCol_Matrix(a, m, n, nnz, nzval, rowind, colptr,
+			       stype, Self::DTYPE, mtype);
+    }
+
+    unsafe fn c_create_dense_matrix(
+	x: *mut c_SuperMatrix,
+	m: i32,
+	n: i32,
+	values: *mut Self,
+	ldx: i32,
+	stype: Stype_t,
+	mtype: Mtype_t,
+    ) {
+	sCreate_Dense_Matrix(x, m, n, values, ldx, stype, Self::DTYPE, mtype);
+    }
+
+    unsafe fn c_create_comp_row_matrix(
+	a: *mut c_SuperMatrix,
+	m: i32,
+	n: i32,
+	nnz: i32,
+	nzval: *mut Self,
+	colind: *mut i32,
+	rowptr: *mut i32,
+	stype: Stype_t,
+	mtype: Mtype_t,
+    ) {
+	sCreate_CompRow_Matrix(a, m, n, nnz, nzval, colind, rowptr,
+			       stype, Self::DTYPE, mtype);
+    }
+
+    fn conj(self) -> Self {
+	self
+    }
+
+    unsafe fn c_gstrf(
+	options: *mut superlu_options_t,
+	a: *mut c_SuperMatrix,
+	relax: i32,
+	panel_size: i32,
+	etree: *mut i32,
+	work: *mut libc::c_void,
+	lwork: i32,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	glu: *mut GlobalLU_t,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	sgstrf(options, a, relax, panel_size, etree, work, lwork, perm_c, perm_r,
+	       l, u, glu, stat, info);
+    }
+
+    unsafe fn c_gstrs(
+	trans: trans_t,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	b: *mut c_SuperMatrix,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	sgstrs(trans, l, u, perm_c, perm_r, b, stat, info);
+    }
+
+    unsafe fn c_gssv(
+	options: *mut superlu_options_t,
+	a: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	b: *mut c_SuperMatrix,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	sgssv(options, a, perm_c, perm_r, l, u, b, stat, info);
+    }
+
+    unsafe fn c_gssvx(
+	options: *mut superlu_options_t,
+	a: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	etree: *mut i32,
+	equed: *mut libc::c_char,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	work: *mut libc::c_void,
+	lwork: i32,
+	b: *mut c_SuperMatrix,
+	x: *mut c_SuperMatrix,
+	recip_pivot_growth: *mut Self::Real,
+	rcond: *mut Self::Real,
+	ferr: *mut Self::Real,
+	berr: *mut Self::Real,
+	glu: *mut GlobalLU_t,
+	mem_usage: *mut mem_usage_t,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	sgssvx(options, a, perm_c, perm_r, etree, equed, r, c, l, u, work, lwork, b, x,
+	       recip_pivot_growth, rcond, ferr, berr, glu, mem_usage, stat, info);
+    }
+
+    fn scale(self, factor: Self::Real) -> Self {
+	self * factor
+    }
+
+    unsafe fn c_gsisx(
+	options: *mut superlu_options_t,
+	a: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	etree: *mut i32,
+	equed: *mut libc::c_char,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	work: *mut libc::c_void,
+	lwork: i32,
+	b: *mut c_SuperMatrix,
+	x: *mut c_SuperMatrix,
+	recip_pivot_growth: *mut Self::Real,
+	rcond: *mut Self::Real,
+	glu: *mut GlobalLU_t,
+	mem_usage: *mut mem_usage_t,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	sgsisx(options, a, perm_c, perm_r, etree, equed, r, c, l, u, work, lwork, b, x,
+	       recip_pivot_growth, rcond, glu, mem_usage, stat, info);
+    }
+
+    unsafe fn c_ilu_query_space(
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	mem_usage: *mut mem_usage_t,
+    ) -> i32 {
+	ilu_sQuerySpace(l, u, mem_usage)
+    }
+
+    unsafe fn c_sp_gemv(
+	trans: *mut libc::c_char,
+	alpha: Self,
+	a: *mut c_SuperMatrix,
+	x: *const Self,
+	incx: i32,
+	beta: Self,
+	y: *mut Self,
+	incy: i32,
+    ) -> i32 {
+	sp_sgemv(trans, alpha, a, x as *mut Self, incx, beta, y, incy)
+    }
+
+    fn abs(self) -> Self::Real {
+	f32::abs(self)
+    }
+
+    fn from_real(re: Self::Real) -> Self {
+	re
+    }
+
+    unsafe fn c_langs(norm: *mut libc::c_char, a: *mut c_SuperMatrix) -> Self::Real {
+	slangs(norm, a)
+    }
+
+    unsafe fn c_gscon(
+	norm: *mut libc::c_char,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	anorm: Self::Real,
+	rcond: *mut Self::Real,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	sgscon(norm, l, u, anorm, rcond, stat, info);
+    }
+
+    unsafe fn c_gsequ(
+	a: *mut c_SuperMatrix,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	rowcnd: *mut Self::Real,
+	colcnd: *mut Self::Real,
+	amax: *mut Self::Real,
+	info: *mut i32,
+    ) {
+	sgsequ(a, r, c, rowcnd, colcnd, amax, info);
+    }
+
+    unsafe fn c_laqgs(
+	a: *mut c_SuperMatrix,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	rowcnd: Self::Real,
+	colcnd: Self::Real,
+	amax: Self::Real,
+	equed: *mut libc::c_char,
+    ) {
+	slaqgs(a, r, c, rowcnd, colcnd, amax, equed);
+    }
+    unsafe fn c_gsrfs(
+	trans: trans_t,
+	a: *mut c_SuperMatrix,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	equed: *mut libc::c_char,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	b: *mut c_SuperMatrix,
+	x: *mut c_SuperMatrix,
+	ferr: *mut Self::Real,
+	berr: *mut Self::Real,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	sgsrfs(trans, a, l, u, perm_c, perm_r, equed, r, c, b, x, ferr, berr, stat, info);
+    }
+    unsafe fn c_pivot_growth(
+	ncols: i32,
+	a: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+    ) -> Self::Real {
+	sPivotGrowth(ncols, a, perm_c, l, u)
+    }
+
+    unsafe fn c_ldperm(
+	job: i32,
+	n: i32,
+	nnz: i32,
+	colptr: *mut i32,
+	adjncy: *mut i32,
+	nzval: *mut Self,
+	perm: *mut i32,
+	u: *mut Self::Real,
+	v: *mut Self::Real,
+    ) -> i32 {
+	sldperm(job, n, nnz, colptr, adjncy, nzval, perm, u, v)
+    }
+}
+
+impl ValueType for f64 {
+    const DTYPE: Dtype_t = Dtype_t_SLU_D;
+    type Real = f64;
+
+    unsafe fn c_create_comp_col_matrix(
+	a: *mut c_SuperMatrix,
+	m: i32,
+	n: i32,
+	nnz: i32,
+	nzval: *mut Self,
+	rowind: *mut i32,
+	colptr: *mut i32,
+	stype: Stype_t,
+	mtype: Mtype_t,
+    ) {
+	dCreate_CompCol_Matrix(a, m, n, nnz, nzval, rowind, colptr,
+			       stype, Self::DTYPE, mtype);
+    }
+
+    unsafe fn c_create_dense_matrix(
+	x: *mut c_SuperMatrix,
+	m: i32,
+	n: i32,
+	values: *mut Self,
+	ldx: i32,
+	stype: Stype_t,
+	mtype: Mtype_t,
+    ) {
+	dCreate_Dense_Matrix(x, m, n, values, ldx, stype, Self::DTYPE, mtype);
+    }
+
+    unsafe fn c_create_comp_row_matrix(
+	a: *mut c_SuperMatrix,
+	m: i32,
+	n: i32,
+	nnz: i32,
+	nzval: *mut Self,
+	colind: *mut i32,
+	rowptr: *mut i32,
+	stype: Stype_t,
+	mtype: Mtype_t,
+    ) {
+	dCreate_CompRow_Matrix(a, m, n, nnz, nzval, colind, rowptr,
+			       stype, Self::DTYPE, mtype);
+    }
+
+    fn conj(self) -> Self {
+	self
+    }
+
+    unsafe fn c_gstrf(
+	options: *mut superlu_options_t,
+	a: *mut c_SuperMatrix,
+	relax: i32,
+	panel_size: i32,
+	etree: *mut i32,
+	work: *mut libc::c_void,
+	lwork: i32,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	glu: *mut GlobalLU_t,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	dgstrf(options, a, relax, panel_size, etree, work, lwork, perm_c, perm_r,
+	       l, u, glu, stat, info);
+    }
+
+    unsafe fn c_gstrs(
+	trans: trans_t,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	b: *mut c_SuperMatrix,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	dgstrs(trans, l, u, perm_c, perm_r, b, stat, info);
+    }
+
+    unsafe fn c_gssv(
+	options: *mut superlu_options_t,
+	a: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	b: *mut c_SuperMatrix,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	dgssv(options, a, perm_c, perm_r, l, u, b, stat, info);
+    }
+
+    unsafe fn c_gssvx(
+	options: *mut superlu_options_t,
+	a: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	etree: *mut i32,
+	equed: *mut libc::c_char,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	work: *mut libc::c_void,
+	lwork: i32,
+	b: *mut c_SuperMatrix,
+	x: *mut c_SuperMatrix,
+	recip_pivot_growth: *mut Self::Real,
+	rcond: *mut Self::Real,
+	ferr: *mut Self::Real,
+	berr: *mut Self::Real,
+	glu: *mut GlobalLU_t,
+	mem_usage: *mut mem_usage_t,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	dgssvx(options, a, perm_c, perm_r, etree, equed, r, c, l, u, work, lwork, b, x,
+	       recip_pivot_growth, rcond, ferr, berr, glu, mem_usage, stat, info);
+    }
+
+    fn scale(self, factor: Self::Real) -> Self {
+	self * factor
+    }
+
+    unsafe fn c_gsisx(
+	options: *mut superlu_options_t,
+	a: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	etree: *mut i32,
+	equed: *mut libc::c_char,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	work: *mut libc::c_void,
+	lwork: i32,
+	b: *mut c_SuperMatrix,
+	x: *mut c_SuperMatrix,
+	recip_pivot_growth: *mut Self::Real,
+	rcond: *mut Self::Real,
+	glu: *mut GlobalLU_t,
+	mem_usage: *mut mem_usage_t,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	dgsisx(options, a, perm_c, perm_r, etree, equed, r, c, l, u, work, lwork, b, x,
+	       recip_pivot_growth, rcond, glu, mem_usage, stat, info);
+    }
+
+    unsafe fn c_ilu_query_space(
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	mem_usage: *mut mem_usage_t,
+    ) -> i32 {
+	ilu_dQuerySpace(l, u, mem_usage)
+    }
+
+    unsafe fn c_sp_gemv(
+	trans: *mut libc::c_char,
+	alpha: Self,
+	a: *mut c_SuperMatrix,
+	x: *const Self,
+	incx: i32,
+	beta: Self,
+	y: *mut Self,
+	incy: i32,
+    ) -> i32 {
+	sp_dgemv(trans, alpha, a, x as *mut Self, incx, beta, y, incy)
+    }
+
+    fn abs(self) -> Self::Real {
+	f64::abs(self)
+    }
+
+    fn from_real(re: Self::Real) -> Self {
+	re
+    }
+
+    unsafe fn c_langs(norm: *mut libc::c_char, a: *mut c_SuperMatrix) -> Self::Real {
+	dlangs(norm, a)
+    }
+
+    unsafe fn c_gscon(
+	norm: *mut libc::c_char,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	anorm: Self::Real,
+	rcond: *mut Self::Real,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	dgscon(norm, l, u, anorm, rcond, stat, info);
+    }
+
+    unsafe fn c_gsequ(
+	a: *mut c_SuperMatrix,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	rowcnd: *mut Self::Real,
+	colcnd: *mut Self::Real,
+	amax: *mut Self::Real,
+	info: *mut i32,
+    ) {
+	dgsequ(a, r, c, rowcnd, colcnd, amax, info);
+    }
+
+    unsafe fn c_laqgs(
+	a: *mut c_SuperMatrix,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	rowcnd: Self::Real,
+	colcnd: Self::Real,
+	amax: Self::Real,
+	equed: *mut libc::c_char,
+    ) {
+	dlaqgs(a, r, c, rowcnd, colcnd, amax, equed);
+    }
+    unsafe fn c_gsrfs(
+	trans: trans_t,
+	a: *mut c_SuperMatrix,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	equed: *mut libc::c_char,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	b: *mut c_SuperMatrix,
+	x: *mut c_SuperMatrix,
+	ferr: *mut Self::Real,
+	berr: *mut Self::Real,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	dgsrfs(trans, a, l, u, perm_c, perm_r, equed, r, c, b, x, ferr, berr, stat, info);
+    }
+    unsafe fn c_pivot_growth(
+	ncols: i32,
+	a: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+    ) -> Self::Real {
+	dPivotGrowth(ncols, a, perm_c, l, u)
+    }
+
+    unsafe fn c_ldperm(
+	job: i32,
+	n: i32,
+	nnz: i32,
+	colptr: *mut i32,
+	adjncy: *mut i32,
+	nzval: *mut Self,
+	perm: *mut i32,
+	u: *mut Self::Real,
+	v: *mut Self::Real,
+    ) -> i32 {
+	dldperm(job, n, nnz, colptr, adjncy, nzval, perm, u, v)
+    }
+}
+
+impl ValueType for Complex<f32> {
+    const DTYPE: Dtype_t = Dtype_t_SLU_C;
+    type Real = f32;
+
+    unsafe fn c_create_comp_col_matrix(
+	a: *mut c_SuperMatrix,
+	m: i32,
+	n: i32,
+	nnz: i32,
+	nzval: *mut Self,
+	rowind: *mut i32,
+	colptr: *mut i32,
+	stype: Stype_t,
+	mtype: Mtype_t,
+    ) {
+	cCreate_CompCol_Matrix(a, m, n, nnz, nzval as *mut complex, rowind, colptr,
+			       stype, Self::DTYPE, mtype);
+    }
+
+    unsafe fn c_create_dense_matrix(
+	x: *mut c_SuperMatrix,
+	m: i32,
+	n: i32,
+	values: *mut Self,
+	ldx: i32,
+	stype: Stype_t,
+	mtype: Mtype_t,
+    ) {
+	cCreate_Dense_Matrix(x, m, n, values as *mut complex, ldx,
+			     stype, Self::DTYPE, mtype);
+    }
+
+    unsafe fn c_create_comp_row_matrix(
+	a: *mut c_SuperMatrix,
+	m: i32,
+	n: i32,
+	nnz: i32,
+	nzval: *mut Self,
+	colind: *mut i32,
+	rowptr: *mut i32,
+	stype: Stype_t,
+	mtype: Mtype_t,
+    ) {
+	cCreate_CompRow_Matrix(a, m, n, nnz, nzval as *mut complex, colind, rowptr,
+			       stype, Self::DTYPE, mtype);
+    }
+
+    fn conj(self) -> Self {
+	Complex::conj(&self)
+    }
+
+    unsafe fn c_gstrf(
+	options: *mut superlu_options_t,
+	a: *mut c_SuperMatrix,
+	relax: i32,
+	panel_size: i32,
+	etree: *mut i32,
+	work: *mut libc::c_void,
+	lwork: i32,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	glu: *mut GlobalLU_t,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	cgstrf(options, a, relax, panel_size, etree, work, lwork, perm_c, perm_r,
+	       l, u, glu, stat, info);
+    }
+
+    unsafe fn c_gstrs(
+	trans: trans_t,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	b: *mut c_SuperMatrix,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	cgstrs(trans, l, u, perm_c, perm_r, b, stat, info);
+    }
+
+    unsafe fn c_gssv(
+	options: *mut superlu_options_t,
+	a: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	b: *mut c_SuperMatrix,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	cgssv(options, a, perm_c, perm_r, l, u, b, stat, info);
+    }
+
+    unsafe fn c_gssvx(
+	options: *mut superlu_options_t,
+	a: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	etree: *mut i32,
+	equed: *mut libc::c_char,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	work: *mut libc::c_void,
+	lwork: i32,
+	b: *mut c_SuperMatrix,
+	x: *mut c_SuperMatrix,
+	recip_pivot_growth: *mut Self::Real,
+	rcond: *mut Self::Real,
+	ferr: *mut Self::Real,
+	berr: *mut Self::Real,
+	glu: *mut GlobalLU_t,
+	mem_usage: *mut mem_usage_t,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	cgssvx(options, a, perm_c, perm_r, etree, equed, r, c, l, u, work, lwork, b, x,
+	       recip_pivot_growth, rcond, ferr, berr, glu, mem_usage, stat, info);
+    }
+
+    fn scale(self, factor: Self::Real) -> Self {
+	self * factor
+    }
+
+    unsafe fn c_gsisx(
+	options: *mut superlu_options_t,
+	a: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	etree: *mut i32,
+	equed: *mut libc::c_char,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	work: *mut libc::c_void,
+	lwork: i32,
+	b: *mut c_SuperMatrix,
+	x: *mut c_SuperMatrix,
+	recip_pivot_growth: *mut Self::Real,
+	rcond: *mut Self::Real,
+	glu: *mut GlobalLU_t,
+	mem_usage: *mut mem_usage_t,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	cgsisx(options, a, perm_c, perm_r, etree, equed, r, c, l, u, work, lwork, b, x,
+	       recip_pivot_growth, rcond, glu, mem_usage, stat, info);
+    }
+
+    unsafe fn c_ilu_query_space(
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	mem_usage: *mut mem_usage_t,
+    ) -> i32 {
+	ilu_cQuerySpace(l, u, mem_usage)
+    }
+
+    unsafe fn c_sp_gemv(
+	trans: *mut libc::c_char,
+	alpha: Self,
+	a: *mut c_SuperMatrix,
+	x: *const Self,
+	incx: i32,
+	beta: Self,
+	y: *mut Self,
+	incy: i32,
+    ) -> i32 {
+	let alpha = complex { r: alpha.re, i: alpha.im };
+	let beta = complex { r: beta.re, i: beta.im };
+	sp_cgemv(trans, alpha, a, x as *mut complex, incx, beta, y as *mut complex, incy)
+    }
+
+    fn abs(self) -> Self::Real {
+	self.norm()
+    }
+
+    fn from_real(re: Self::Real) -> Self {
+	Complex::new(re, 0.0)
+    }
+
+    unsafe fn c_langs(norm: *mut libc::c_char, a: *mut c_SuperMatrix) -> Self::Real {
+	clangs(norm, a)
+    }
+
+    unsafe fn c_gscon(
+	norm: *mut libc::c_char,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	anorm: Self::Real,
+	rcond: *mut Self::Real,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	cgscon(norm, l, u, anorm, rcond, stat, info);
+    }
+
+    unsafe fn c_gsequ(
+	a: *mut c_SuperMatrix,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	rowcnd: *mut Self::Real,
+	colcnd: *mut Self::Real,
+	amax: *mut Self::Real,
+	info: *mut i32,
+    ) {
+	cgsequ(a, r, c, rowcnd, colcnd, amax, info);
+    }
+
+    unsafe fn c_laqgs(
+	a: *mut c_SuperMatrix,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	rowcnd: Self::Real,
+	colcnd: Self::Real,
+	amax: Self::Real,
+	equed: *mut libc::c_char,
+    ) {
+	claqgs(a, r, c, rowcnd, colcnd, amax, equed);
+    }
+    unsafe fn c_gsrfs(
+	trans: trans_t,
+	a: *mut c_SuperMatrix,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	equed: *mut libc::c_char,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	b: *mut c_SuperMatrix,
+	x: *mut c_SuperMatrix,
+	ferr: *mut Self::Real,
+	berr: *mut Self::Real,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	cgsrfs(trans, a, l, u, perm_c, perm_r, equed, r, c, b, x, ferr, berr, stat, info);
+    }
+    unsafe fn c_pivot_growth(
+	ncols: i32,
+	a: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+    ) -> Self::Real {
+	cPivotGrowth(ncols, a, perm_c, l, u)
+    }
+
+    unsafe fn c_ldperm(
+	job: i32,
+	n: i32,
+	nnz: i32,
+	colptr: *mut i32,
+	adjncy: *mut i32,
+	nzval: *mut Self,
+	perm: *mut i32,
+	u: *mut Self::Real,
+	v: *mut Self::Real,
+    ) -> i32 {
+	cldperm(job, n, nnz, colptr, adjncy, nzval as *mut complex, perm, u, v)
+    }
+}
+
+impl ValueType for Complex<f64> {
+    const DTYPE: Dtype_t = Dtype_t_SLU_Z;
+    type Real = f64;
+
+    unsafe fn c_create_comp_col_matrix(
+	a: *mut c_SuperMatrix,
+	m: i32,
+	n: i32,
+	nnz: i32,
+	nzval: *mut Self,
+	rowind: *mut i32,
+	colptr: *mut i32,
+	stype: Stype_t,
+	mtype: Mtype_t,
+    ) {
+	zCreate_CompCol_Matrix(a, m, n, nnz, nzval as *mut doublecomplex, rowind, colptr,
+			       stype, Self::DTYPE, mtype);
+    }
+
+    unsafe fn c_create_dense_matrix(
+	x: *mut c_SuperMatrix,
+	m: i32,
+	n: i32,
+	values: *mut Self,
+	ldx: i32,
+	stype: Stype_t,
+	mtype: Mtype_t,
+    ) {
+	zCreate_Dense_Matrix(x, m, n, values as *mut doublecomplex, ldx,
+			     stype, Self::DTYPE, mtype);
+    }
+
+    unsafe fn c_create_comp_row_matrix(
+	a: *mut c_SuperMatrix,
+	m: i32,
+	n: i32,
+	nnz: i32,
+	nzval: *mut Self,
+	colind: *mut i32,
+	rowptr: *mut i32,
+	stype: Stype_t,
+	mtype: Mtype_t,
+    ) {
+	zCreate_CompRow_Matrix(a, m, n, nnz, nzval as *mut doublecomplex, colind, rowptr,
+			       stype, Self::DTYPE, mtype);
+    }
+
+    fn conj(self) -> Self {
+	Complex::conj(&self)
+    }
+
+    unsafe fn c_gstrf(
+	options: *mut superlu_options_t,
+	a: *mut c_SuperMatrix,
+	relax: i32,
+	panel_size: i32,
+	etree: *mut i32,
+	work: *mut libc::c_void,
+	lwork: i32,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	glu: *mut GlobalLU_t,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	zgstrf(options, a, relax, panel_size, etree, work, lwork, perm_c, perm_r,
+	       l, u, glu, stat, info);
+    }
+
+    unsafe fn c_gstrs(
+	trans: trans_t,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	b: *mut c_SuperMatrix,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	zgstrs(trans, l, u, perm_c, perm_r, b, stat, info);
+    }
+
+    unsafe fn c_gssv(
+	options: *mut superlu_options_t,
+	a: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	b: *mut c_SuperMatrix,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	zgssv(options, a, perm_c, perm_r, l, u, b, stat, info);
+    }
+
+    unsafe fn c_gssvx(
+	options: *mut superlu_options_t,
+	a: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	etree: *mut i32,
+	equed: *mut libc::c_char,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	work: *mut libc::c_void,
+	lwork: i32,
+	b: *mut c_SuperMatrix,
+	x: *mut c_SuperMatrix,
+	recip_pivot_growth: *mut Self::Real,
+	rcond: *mut Self::Real,
+	ferr: *mut Self::Real,
+	berr: *mut Self::Real,
+	glu: *mut GlobalLU_t,
+	mem_usage: *mut mem_usage_t,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	zgssvx(options, a, perm_c, perm_r, etree, equed, r, c, l, u, work, lwork, b, x,
+	       recip_pivot_growth, rcond, ferr, berr, glu, mem_usage, stat, info);
+    }
+
+    fn scale(self, factor: Self::Real) -> Self {
+	self * factor
+    }
+
+    unsafe fn c_gsisx(
+	options: *mut superlu_options_t,
+	a: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	etree: *mut i32,
+	equed: *mut libc::c_char,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	work: *mut libc::c_void,
+	lwork: i32,
+	b: *mut c_SuperMatrix,
+	x: *mut c_SuperMatrix,
+	recip_pivot_growth: *mut Self::Real,
+	rcond: *mut Self::Real,
+	glu: *mut GlobalLU_t,
+	mem_usage: *mut mem_usage_t,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	zgsisx(options, a, perm_c, perm_r, etree, equed, r, c, l, u, work, lwork, b, x,
+	       recip_pivot_growth, rcond, glu, mem_usage, stat, info);
+    }
+
+    unsafe fn c_ilu_query_space(
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	mem_usage: *mut mem_usage_t,
+    ) -> i32 {
+	ilu_zQuerySpace(l, u, mem_usage)
+    }
+
+    unsafe fn c_sp_gemv(
+	trans: *mut libc::c_char,
+	alpha: Self,
+	a: *mut c_SuperMatrix,
+	x: *const Self,
+	incx: i32,
+	beta: Self,
+	y: *mut Self,
+	incy: i32,
+    ) -> i32 {
+	let alpha = doublecomplex { r: alpha.re, i: alpha.im };
+	let beta = doublecomplex { r: beta.re, i: beta.im };
+	sp_zgemv(trans, alpha, a, x as *mut doublecomplex, incx, beta, y as *mut doublecomplex, incy)
+    }
+
+    fn abs(self) -> Self::Real {
+	self.norm()
+    }
+
+    fn from_real(re: Self::Real) -> Self {
+	Complex::new(re, 0.0)
+    }
+
+    unsafe fn c_langs(norm: *mut libc::c_char, a: *mut c_SuperMatrix) -> Self::Real {
+	zlangs(norm, a)
+    }
+
+    unsafe fn c_gscon(
+	norm: *mut libc::c_char,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	anorm: Self::Real,
+	rcond: *mut Self::Real,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	zgscon(norm, l, u, anorm, rcond, stat, info);
+    }
+
+    unsafe fn c_gsequ(
+	a: *mut c_SuperMatrix,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	rowcnd: *mut Self::Real,
+	colcnd: *mut Self::Real,
+	amax: *mut Self::Real,
+	info: *mut i32,
+    ) {
+	zgsequ(a, r, c, rowcnd, colcnd, amax, info);
+    }
+
+    unsafe fn c_laqgs(
+	a: *mut c_SuperMatrix,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	rowcnd: Self::Real,
+	colcnd: Self::Real,
+	amax: Self::Real,
+	equed: *mut libc::c_char,
+    ) {
+	zlaqgs(a, r, c, rowcnd, colcnd, amax, equed);
+    }
+    unsafe fn c_gsrfs(
+	trans: trans_t,
+	a: *mut c_SuperMatrix,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	perm_r: *mut i32,
+	equed: *mut libc::c_char,
+	r: *mut Self::Real,
+	c: *mut Self::Real,
+	b: *mut c_SuperMatrix,
+	x: *mut c_SuperMatrix,
+	ferr: *mut Self::Real,
+	berr: *mut Self::Real,
+	stat: *mut SuperLUStat_t,
+	info: *mut i32,
+    ) {
+	zgsrfs(trans, a, l, u, perm_c, perm_r, equed, r, c, b, x, ferr, berr, stat, info);
+    }
+    unsafe fn c_pivot_growth(
+	ncols: i32,
+	a: *mut c_SuperMatrix,
+	perm_c: *mut i32,
+	l: *mut c_SuperMatrix,
+	u: *mut c_SuperMatrix,
+    ) -> Self::Real {
+	zPivotGrowth(ncols, a, perm_c, l, u)
+    }
+
+    unsafe fn c_ldperm(
+	job: i32,
+	n: i32,
+	nnz: i32,
+	colptr: *mut i32,
+	adjncy: *mut i32,
+	nzval: *mut Self,
+	perm: *mut i32,
+	u: *mut Self::Real,
+	v: *mut Self::Real,
+    ) -> i32 {
+	zldperm(job, n, nnz, colptr, adjncy, nzval as *mut doublecomplex, perm, u, v)
+    }
+}
@@ -0,0 +1,115 @@
+//! Check the batch solvers, and that the factorizations can be used
+//! from several threads.
+
+mod common;
+
+use common::example_arrays;
+use csuperlu_sys::{
+    factorize, factorize_batch, solve_batch, CompColMatrix, CompRowMatrix, DenseMatrix,
+    Factorization, IluOptions, IncompleteLu, LuFactorization, Options, PermutedMatrix,
+    SolveError, SuperLUStat, SymbolicLu, Transposed,
+};
+use num::Complex;
+
+/// The example matrix, with the diagonal shifted by k
+fn shifted_matrix(k: usize) -> CompColMatrix<f64> {
+    let (mut a, asub, xa) = example_arrays();
+    for j in 0..5 {
+	for i in xa[j] as usize..xa[j + 1] as usize {
+	    if asub[i] as usize == j {
+		a[i] += k as f64;
+	    }
+	}
+    }
+    CompColMatrix::new(5, 5, &a, &asub, &xa)
+}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn thread_safety_guarantees() {
+    assert_send_sync::<CompColMatrix<f64>>();
+    assert_send_sync::<CompRowMatrix<Complex<f32>>>();
+    assert_send_sync::<CompColMatrix<f64, csuperlu_sys::Borrowed<'static>>>();
+    assert_send_sync::<DenseMatrix<Complex<f64>>>();
+    assert_send_sync::<Transposed<'static, f32>>();
+    assert_send_sync::<Options>();
+    assert_send_sync::<IluOptions>();
+    assert_send_sync::<SuperLUStat>();
+    assert_send_sync::<Factorization<f64>>();
+    assert_send_sync::<LuFactorization<Complex<f64>>>();
+    assert_send_sync::<IncompleteLu<f32>>();
+    assert_send_sync::<SymbolicLu<f64>>();
+    assert_send_sync::<PermutedMatrix<'static, f64>>();
+}
+
+#[test]
+fn solve_batch_matches_sequential_solves() {
+    let systems: Vec<_> = (0..200).map(shifted_matrix).collect();
+    let mut rhs: Vec<Vec<f64>> = (0..200).map(|k| (0..5).map(|i| (i + k) as f64).collect()).collect();
+    let expected: Vec<Vec<f64>> = systems.iter().zip(&rhs).map(|(a, b)| {
+	let mut x = b.clone();
+	factorize(a, &Options::new()).unwrap().solve(&mut x).unwrap();
+	x
+    }).collect();
+
+    let results = solve_batch(&systems, &mut rhs, &Options::new());
+    assert!(results.iter().all(Result::is_ok));
+    for (x, y) in rhs.iter().zip(&expected) {
+	assert!(x.iter().zip(y).all(|(xi, yi)| (xi - yi).abs() < 1e-10));
+    }
+}
+
+#[test]
+fn failures_only_affect_their_own_system() {
+    // The second matrix has an empty column
+    let singular = CompColMatrix::new(2, 2, &[1.0, 1.0], &[0, 1], &[0, 2, 2]);
+    let regular = CompColMatrix::new(2, 2, &[2.0, 4.0], &[0, 1], &[0, 1, 2]);
+    let systems = [regular, singular];
+    let mut rhs = [[2.0, 8.0], [1.0, 1.0]];
+    let results = solve_batch(&systems, &mut rhs, &Options::new());
+    assert_eq!(results[0], Ok(()));
+    assert!(matches!(results[1], Err(SolveError::Singular(_))));
+    assert_eq!(rhs[0], [1.0, 2.0]);
+    assert_eq!(rhs[1], [1.0, 1.0]);
+
+    let factors = factorize_batch(&systems, &Options::new());
+    assert!(factors[0].is_ok());
+    assert!(matches!(factors[1], Err(SolveError::Singular(_))));
+}
+
+#[test]
+fn shared_factorization_solves_in_parallel() {
+    let a = shifted_matrix(0);
+    let lu = factorize(&a, &Options::new()).unwrap();
+    let mut expected = vec![1.0; 5];
+    lu.solve(&mut expected).unwrap();
+    std::thread::scope(|scope| {
+	for _ in 0..8 {
+	    scope.spawn(|| {
+		for _ in 0..100 {
+		    let mut x = vec![1.0; 5];
+		    lu.solve(&mut x).unwrap();
+		    assert_eq!(x, expected);
+		}
+	    });
+	}
+    });
+}
+
+#[test]
+fn incomplete_factorizations_in_parallel() {
+    let a = shifted_matrix(0);
+    let expected = IncompleteLu::new(&a, &IluOptions::new()).unwrap();
+    std::thread::scope(|scope| {
+	for _ in 0..8 {
+	    scope.spawn(|| {
+		for _ in 0..20 {
+		    let ilu = IncompleteLu::new(&a, &IluOptions::new()).unwrap();
+		    assert_eq!(ilu.perm_r(), expected.perm_r());
+		    assert_eq!(ilu.u().nzval(), expected.u().nzval());
+		}
+	    });
+	}
+    });
+}
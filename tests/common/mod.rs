@@ -0,0 +1,36 @@
+//! Matrices and helpers shared by the tests.
+
+// Each test crate only uses some of these
+#![allow(dead_code)]
+
+use csuperlu_sys::{CompColMatrix, ValueType};
+use num::Complex;
+
+/// The 5x5 matrix from section 2.2 of the SuperLU manual, as arrays
+pub fn example_arrays() -> (Vec<f64>, Vec<i32>, Vec<i32>) {
+    let (s, u, p, e, r, l) = (19.0, 21.0, 16.0, 5.0, 18.0, 12.0);
+    let a = vec![s, l, l, u, l, l, u, p, u, e, u, r];
+    let asub = vec![0, 1, 4, 1, 2, 4, 0, 2, 0, 3, 3, 4];
+    let xa = vec![0, 3, 6, 8, 10, 12];
+    (a, asub, xa)
+}
+
+/// The 5x5 matrix from section 2.2 of the SuperLU manual
+pub fn example_matrix() -> CompColMatrix<f64> {
+    let (a, asub, xa) = example_arrays();
+    CompColMatrix::new(5, 5, &a, &asub, &xa)
+}
+
+/// The maximum of |A x - b|
+pub fn residual<T>(a: &CompColMatrix<T>, x: &[T], b: &[T]) -> f64
+where
+    T: ValueType + Into<Complex<f64>>,
+{
+    let mut ax = vec![Complex::new(0.0, 0.0); a.nrow()];
+    for (j, &xj) in x.iter().enumerate() {
+	for k in a.colptr()[j] as usize..a.colptr()[j + 1] as usize {
+	    ax[a.rowind()[k] as usize] += a.nzval()[k].into() * xj.into();
+	}
+    }
+    ax.iter().zip(b).map(|(lhs, &rhs)| (lhs - rhs.into()).norm()).fold(0.0, f64::max)
+}
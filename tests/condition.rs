@@ -0,0 +1,71 @@
+//! Check the condition number estimates on stored factorizations.
+
+mod common;
+
+use common::example_matrix;
+use csuperlu_sys::{
+    factorize, solve_expert, CompColMatrix, DenseMatrix, Norm, Options, SymbolicLu,
+};
+use num::Complex;
+
+/// [[1, 2, 3], [0, d, 0], [0, 0, 1]], whose inverse is
+/// [[1, -2/d, -3], [0, 1/d, 0], [0, 0, 1]]
+fn triangular(d: f64) -> CompColMatrix<f64> {
+    CompColMatrix::new(3, 3, &[1.0, 2.0, d, 3.0, 1.0], &[0, 0, 1, 0, 2], &[0, 1, 3, 5])
+}
+
+#[test]
+fn matches_the_expert_driver() {
+    let a = example_matrix();
+    let options = Options::new().condition_number(true);
+    let b = DenseMatrix::new(5, 1, &[1.0; 5]);
+    let expected = solve_expert(&a, &b, &options).unwrap().rcond().unwrap();
+    let lu = factorize(&a, &options).unwrap();
+    let rcond = lu.rcond(Norm::One);
+    assert!((rcond - expected).abs() <= 1e-12 * expected);
+    // The second call uses the cached value
+    assert_eq!(lu.rcond(Norm::One), rcond);
+}
+
+#[test]
+fn one_and_infinity_norms() {
+    // kappa_1 = 4 * 4 = 16 and kappa_inf = 6 * 6 = 36
+    let a = triangular(1.0);
+    let lu = factorize(&a, &Options::new().equilibrate(false)).unwrap();
+    assert!((lu.rcond(Norm::One) - 1.0 / 16.0).abs() < 1e-12);
+    assert!((lu.rcond(Norm::Inf) - 1.0 / 36.0).abs() < 1e-12);
+
+    // The one-norm of A^T is the infinity-norm of A
+    let lu = factorize(&a.transpose(), &Options::new().equilibrate(false)).unwrap();
+    assert!((lu.rcond(Norm::One) - 1.0 / 36.0).abs() < 1e-12);
+    assert!((lu.rcond(Norm::Inf) - 1.0 / 16.0).abs() < 1e-12);
+}
+
+#[test]
+fn estimate_is_updated_by_refactorization() {
+    let options = Options::new().equilibrate(false);
+    let mut symbolic = SymbolicLu::new(&triangular(1.0), &options);
+    let lu = symbolic.refactor_same_row_perm(&triangular(1.0)).unwrap();
+    assert!((lu.rcond(Norm::One) - 1.0 / 16.0).abs() < 1e-12);
+    // kappa_1 = 6 * 4 = 24 for d = 4
+    let lu = symbolic.refactor_same_row_perm(&triangular(4.0)).unwrap();
+    assert!((lu.rcond(Norm::One) - 1.0 / 24.0).abs() < 1e-12);
+}
+
+#[test]
+fn complex_matrix() {
+    // A unitary matrix (times 2) has condition number one
+    let i = Complex::new(0.0, 2.0);
+    let one = Complex::new(2.0, 0.0);
+    let a = CompColMatrix::new(2, 2, &[one, i], &[0, 1], &[0, 1, 2]);
+    let lu = factorize(&a, &Options::new()).unwrap();
+    assert!((lu.rcond(Norm::One) - 1.0).abs() < 1e-12);
+    assert!((lu.rcond(Norm::Inf) - 1.0).abs() < 1e-12);
+}
+
+#[test]
+#[should_panic(expected = "one- or infinity-norm")]
+fn no_estimate_in_the_frobenius_norm() {
+    let lu = factorize(&triangular(1.0), &Options::new()).unwrap();
+    lu.rcond(Norm::Frobenius);
+}
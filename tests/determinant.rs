@@ -0,0 +1,113 @@
+//! Check determinants computed from the factorizations against dense
+//! elimination.
+
+mod common;
+
+use common::example_matrix;
+use csuperlu_sys::{factorize, solve, CompColMatrix, DenseMatrix, Options};
+use num::Complex;
+
+/// The example matrix with complex values
+fn complex_matrix() -> CompColMatrix<Complex<f64>> {
+    let a = example_matrix();
+    let values: Vec<Complex<f64>> = a.nzval().iter().enumerate()
+	.map(|(k, &v)| Complex::new(v, (k as f64) - 4.0)).collect();
+    CompColMatrix::new(5, 5, &values, a.rowind(), a.colptr())
+}
+
+/// The determinant by dense Gaussian elimination with partial
+/// pivoting
+fn dense_determinant<T>(a: &CompColMatrix<T>) -> Complex<f64>
+where
+    T: csuperlu_sys::ValueType + Into<Complex<f64>>,
+{
+    let n = a.nrow();
+    let mut m = vec![vec![Complex::new(0.0, 0.0); n]; n];
+    for j in 0..n {
+	for k in a.colptr()[j] as usize..a.colptr()[j + 1] as usize {
+	    m[a.rowind()[k] as usize][j] = a.nzval()[k].into();
+	}
+    }
+    let mut det = Complex::new(1.0, 0.0);
+    for j in 0..n {
+	let p = (j..n).max_by(|&x, &y| m[x][j].norm().total_cmp(&m[y][j].norm())).unwrap();
+	if p != j {
+	    m.swap(p, j);
+	    det = -det;
+	}
+	det *= m[j][j];
+	for i in j + 1..n {
+	    let factor = m[i][j] / m[j][j];
+	    for k in j..n {
+		let mjk = m[j][k];
+		m[i][k] -= factor * mjk;
+	    }
+	}
+    }
+    det
+}
+
+#[test]
+fn real_determinant() {
+    let a = example_matrix();
+    let lu = factorize(&a, &Options::new()).unwrap();
+    let expected = dense_determinant(&a).re;
+    assert!((lu.determinant() - expected).abs() < 1e-10 * expected.abs());
+    assert_eq!(lu.sign(), expected.signum());
+    assert!((lu.log_abs_determinant() - expected.abs().ln()).abs() < 1e-12);
+}
+
+#[test]
+fn complex_determinant_and_adjoint() {
+    let a = complex_matrix();
+    let expected = dense_determinant(&a);
+    let lu = factorize(&a, &Options::new()).unwrap();
+    assert!((lu.determinant() - expected).norm() < 1e-10 * expected.norm());
+    assert!((lu.sign() - expected / expected.norm()).norm() < 1e-12);
+
+    // det(A^T) = det(A) and det(A^H) = conj(det(A))
+    let lu = factorize(&a.transpose(), &Options::new()).unwrap();
+    assert!((lu.determinant() - expected).norm() < 1e-10 * expected.norm());
+    let lu = factorize(&a.adjoint(), &Options::new()).unwrap();
+    assert!((lu.determinant() - expected.conj()).norm() < 1e-10 * expected.norm());
+}
+
+#[test]
+fn equilibration_is_undone() {
+    // The rows of this matrix have very different sizes, so it is
+    // equilibrated; its determinant is -1e10
+    let a = CompColMatrix::new(3, 3, &[1e10f64, 1.0, 1.0, 1e-10], &[0, 2, 1, 2], &[0, 1, 2, 4]);
+    let lu = factorize(&a, &Options::new()).unwrap();
+    let expected = dense_determinant(&a).re;
+    assert!((lu.determinant() - expected).abs() < 1e-12 * expected.abs());
+}
+
+#[test]
+fn no_overflow_in_log_determinant() {
+    // det = -10^400, which does not fit in an f64
+    let n = 400;
+    let mut values = vec![10.0; n];
+    values[7] = -10.0;
+    let rowind: Vec<i32> = (0..n as i32).collect();
+    let colptr: Vec<i32> = (0..=n as i32).collect();
+    let a = CompColMatrix::new(n, n, &values, &rowind, &colptr);
+    let lu = factorize(&a, &Options::new()).unwrap();
+    assert_eq!(lu.sign(), -1.0);
+    assert!((lu.log_abs_determinant() - 400.0 * 10f64.ln()).abs() < 1e-9);
+    assert_eq!(lu.determinant(), f64::NEG_INFINITY);
+
+    // The same in single precision, where 10^40 already overflows
+    let values: Vec<f32> = values.iter().map(|&v| v as f32).collect();
+    let a = CompColMatrix::new(n, n, &values, &rowind, &colptr);
+    let lu = factorize(&a, &Options::new()).unwrap();
+    assert!((lu.log_abs_determinant() - 400.0 * 10f32.ln()).abs() < 1e-2);
+}
+
+#[test]
+fn simple_driver_factorization() {
+    let a = example_matrix();
+    let mut b = DenseMatrix::new(5, 1, &[1.0; 5]);
+    let factors = solve(&a, &mut b, &Options::new()).unwrap();
+    let expected = dense_determinant(&a).re;
+    assert!((factors.determinant() - expected).abs() < 1e-10 * expected.abs());
+}
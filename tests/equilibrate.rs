@@ -0,0 +1,111 @@
+//! Check the equilibration of badly scaled matrices.
+
+mod common;
+
+use common::example_matrix;
+use csuperlu_sys::{
+    solve, CompColMatrix, DenseMatrix, Equilibration, Options, ScaleFactors, SolveError,
+};
+
+/// [[1e10, 0, 1e4], [0, 1, 0], [3e-10, 0, 1e-16]] (with the first
+/// and last values multiplied by factor), whose rows and columns
+/// have very different sizes
+fn badly_scaled(factor: f64) -> CompColMatrix<f64> {
+    let values = [1e10 * factor, 3e-10, 1.0, 1e4, 1e-16 * factor];
+    CompColMatrix::new(3, 3, &values, &[0, 2, 1, 0, 2], &[0, 2, 3, 5])
+}
+
+#[test]
+fn well_scaled_matrix_is_left_alone() {
+    let mut a = example_matrix();
+    let factors = ScaleFactors::new(&a).unwrap();
+    assert_eq!(factors.amax(), 21.0);
+    assert!(factors.rowcnd() > 0.1 && factors.colcnd() > 0.1);
+    assert_eq!(factors.equilibration(), Equilibration::None);
+    assert_eq!(factors.apply(&mut a), Equilibration::None);
+    assert_eq!(a.nzval(), example_matrix().nzval());
+    let mut b = [1.0; 5];
+    factors.scale_rhs(&mut b);
+    assert_eq!(b, [1.0; 5]);
+}
+
+#[test]
+fn scaled_values() {
+    let original = badly_scaled(1.0);
+    let mut a = badly_scaled(1.0);
+    let factors = ScaleFactors::new(&a).unwrap();
+    assert_eq!(factors.amax(), 1e10);
+    assert_eq!(factors.equilibration(), Equilibration::Both);
+    assert_eq!(factors.apply(&mut a), Equilibration::Both);
+    let (r, c) = (factors.row_scale(), factors.col_scale());
+    for j in 0..3 {
+	for k in a.colptr()[j] as usize..a.colptr()[j + 1] as usize {
+	    let i = a.rowind()[k] as usize;
+	    assert_eq!(a.nzval()[k], original.nzval()[k] * (c[j] * r[i]));
+	    assert!(a.nzval()[k].abs() <= 1.0 + 1e-15);
+	}
+    }
+}
+
+#[test]
+fn solve_scaled_system() {
+    let mut a = badly_scaled(1.0);
+    let x_true = [1.0, -2.0, 3.0];
+    // b = A x
+    let b_true = [1e10 + 3e4, -2.0, 3e-10 + 3e-16];
+    let mut b = b_true;
+    let factors = ScaleFactors::new(&a).unwrap();
+    factors.apply(&mut a);
+    factors.scale_rhs(&mut b);
+    let mut rhs = DenseMatrix::new(3, 1, &b);
+    solve(&a, &mut rhs, &Options::new()).unwrap();
+    let mut x = rhs.values().to_vec();
+    factors.unscale_solution(&mut x);
+    for (x, y) in x.iter().zip(&x_true) {
+	assert!((x - y).abs() < 1e-12 * y.abs(), "{} != {}", x, y);
+    }
+
+    // The reverse operations undo the scaling
+    factors.scale_solution(&mut x);
+    for (x, y) in x.iter().zip(rhs.values()) {
+	assert!((x - y).abs() < 1e-15 * y.abs(), "{} != {}", x, y);
+    }
+    factors.unscale_rhs(&mut b);
+    for (b, y) in b.iter().zip(&b_true) {
+	assert!((b - y).abs() < 1e-15 * y.abs(), "{} != {}", b, y);
+    }
+}
+
+#[test]
+fn reuse_for_another_matrix() {
+    let factors = ScaleFactors::new(&badly_scaled(1.0)).unwrap();
+    let mut a = badly_scaled(2.0);
+    assert_eq!(factors.apply(&mut a), factors.equilibration());
+    let r = factors.row_scale();
+    let c = factors.col_scale();
+    assert_eq!(a.nzval()[0], 2e10 * (c[0] * r[0]));
+
+    // Several right-hand sides, one after the other
+    let mut b = [1.0; 6];
+    factors.scale_rhs(&mut b);
+    assert_eq!(&b[..3], r);
+    assert_eq!(&b[3..], r);
+}
+
+#[test]
+fn zero_row_or_column() {
+    let a = CompColMatrix::new(3, 3, &[1.0, 2.0, 3.0], &[0, 2, 0], &[0, 2, 3, 3]);
+    assert_eq!(ScaleFactors::new(&a).unwrap_err(), SolveError::ZeroRow(1));
+    let a = CompColMatrix::new(2, 2, &[1.0, 2.0], &[0, 1], &[0, 0, 2]);
+    assert_eq!(ScaleFactors::new(&a).unwrap_err(), SolveError::ZeroColumn(0));
+}
+
+#[test]
+fn half_stored_matrix() {
+    // [[4, 1e-6], [1e-6, 1e-8]]
+    let a = CompColMatrix::symmetric_lower(2, &[4.0, 1e-6, 1e-8], &[0, 1, 1], &[0, 2, 3]);
+    let factors = ScaleFactors::new(&a).unwrap();
+    let expected = ScaleFactors::new(&a.to_general()).unwrap();
+    assert_eq!(factors.row_scale(), expected.row_scale());
+    assert_eq!(factors.col_scale(), expected.col_scale());
+}
@@ -0,0 +1,98 @@
+//! Check the predicted sizes of factorizations against real ones.
+
+mod common;
+
+use common::example_matrix;
+use csuperlu_sys::{
+    analyze, factorize, factorize_with_permutation, get_tuning_params, ColumnOrdering,
+    CompColMatrix, Options, Permutation, SymbolicLu,
+};
+
+/// An n x n tridiagonal matrix, with a large diagonal
+fn tridiagonal(n: usize) -> CompColMatrix<f64> {
+    let (mut values, mut rowind, mut colptr) = (Vec::new(), Vec::new(), vec![0]);
+    for j in 0..n {
+	for i in j.saturating_sub(1)..(j + 2).min(n) {
+	    values.push(if i == j { 4.0 } else { -1.0 });
+	    rowind.push(i as i32);
+	}
+	colptr.push(rowind.len() as i32);
+    }
+    CompColMatrix::new(n, n, &values, &rowind, &colptr)
+}
+
+/// A dense n x n matrix
+fn dense(n: usize) -> CompColMatrix<f64> {
+    let values: Vec<f64> = (0..n * n).map(|k| if k % (n + 1) == 0 { n as f64 } else { 1.0 }).collect();
+    let rowind: Vec<i32> = (0..n * n).map(|k| (k % n) as i32).collect();
+    let colptr: Vec<i32> = (0..=n).map(|j| (j * n) as i32).collect();
+    CompColMatrix::new(n, n, &values, &rowind, &colptr)
+}
+
+#[test]
+fn dense_matrix_is_exact() {
+    let options = Options::new().column_ordering(ColumnOrdering::Natural);
+    let estimate = analyze(&dense(4), &options);
+    assert_eq!(estimate.nnz_l(), 10);
+    assert_eq!(estimate.nnz_u(), 10);
+    assert_eq!(estimate.supernodes(), 1);
+    // 3 + 2 + 1 divisions, and 2 (9 + 4 + 1) for the updates
+    assert_eq!(estimate.flops(), 34.0);
+
+    let lu = factorize(&dense(4), &options).unwrap();
+    assert_eq!(lu.l().nnz(), estimate.nnz_l());
+    assert_eq!(lu.u().nnz(), estimate.nnz_u());
+    assert_eq!(lu.l().num_supernodes(), estimate.supernodes());
+}
+
+#[test]
+fn bounds_hold() {
+    for ordering in [ColumnOrdering::Natural, ColumnOrdering::MmdAtA, ColumnOrdering::Colamd] {
+	let options = Options::new().column_ordering(ordering);
+	for a in [example_matrix(), tridiagonal(30)] {
+	    let estimate = analyze(&a, &options);
+	    let lu = factorize(&a, &options).unwrap();
+	    assert!(lu.l().nnz() <= estimate.nnz_l());
+	    assert!(lu.u().nnz() <= estimate.nnz_u());
+	    assert!(estimate.bytes() > 0);
+	    assert!(estimate.initial_bytes() > 0);
+	}
+    }
+}
+
+#[test]
+fn initial_allocation_follows_the_fill_ratio() {
+    // dLUMemInit's guess, from the fill ratio and the number of
+    // entries of A, plus its work arrays
+    let a = tridiagonal(30);
+    let estimate = analyze(&a, &Options::new());
+    let tuning = get_tuning_params();
+    let (n, annz, w, fill) = (30, a.nnz(), tuning.panel_size, tuning.estimated_fills);
+    let (int, double) = (std::mem::size_of::<i32>(), std::mem::size_of::<f64>());
+    let expected = (5 * n + 5) * int + (2 * w + 7) * n * int + (w + 1) * n * double
+	+ (fill.max(4) * annz / 4 + fill * annz) * int + 2 * fill * annz * double;
+    assert_eq!(estimate.initial_bytes(), expected);
+}
+
+#[test]
+fn permutation_matches_the_symbolic_analysis() {
+    let a = example_matrix();
+    let options = Options::new();
+    let estimate = analyze(&a, &options);
+    assert_eq!(estimate.perm_c(), SymbolicLu::<f64>::new(&a, &options).perm_c());
+
+    // The permutation is already postordered, so it is used as given
+    let perm = Permutation::new(estimate.perm_c().to_vec()).unwrap();
+    let lu = factorize_with_permutation(&a, &perm, &options).unwrap();
+    assert_eq!(lu.perm_c(), estimate.perm_c());
+}
+
+#[test]
+fn fill_grows_with_the_bandwidth() {
+    let options = Options::new().column_ordering(ColumnOrdering::Natural);
+    let narrow = analyze(&tridiagonal(50), &options);
+    let wide = analyze(&dense(50), &options);
+    assert!(narrow.nnz_l() < wide.nnz_l());
+    assert!(narrow.flops() < wide.flops());
+    assert!(narrow.bytes() < wide.bytes());
+}
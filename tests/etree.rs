@@ -0,0 +1,101 @@
+//! Check the column elimination tree and supernode analysis.
+
+mod common;
+
+use common::example_matrix;
+use csuperlu_sys::{preorder, CompColMatrix, EliminationTree, Options, Permutation};
+
+/// The pattern of an n x n tridiagonal matrix, whose column
+/// elimination tree is a chain
+fn tridiagonal(n: usize) -> CompColMatrix<f64> {
+    let (mut rowind, mut colptr) = (Vec::new(), vec![0]);
+    for j in 0..n {
+	for i in j.saturating_sub(1)..(j + 2).min(n) {
+	    rowind.push(i as i32);
+	}
+	colptr.push(rowind.len() as i32);
+    }
+    CompColMatrix::new(n, n, &vec![1.0; rowind.len()], &rowind, &colptr)
+}
+
+#[test]
+fn chain() {
+    let tree = EliminationTree::new(&tridiagonal(4));
+    assert_eq!(tree.parents(), &[1, 2, 3, 4]);
+    assert_eq!(tree.parent(2), Some(3));
+    assert_eq!(tree.parent(3), None);
+    assert_eq!(tree.children(2), &[1]);
+    assert!(tree.children(0).is_empty());
+    assert_eq!(tree.roots(), &[3]);
+    assert_eq!(tree.subtree_sizes(), vec![1, 2, 3, 4]);
+    assert_eq!(tree.height(), 4);
+    assert!(tree.is_postordered());
+}
+
+#[test]
+fn diagonal_matrix_is_a_forest() {
+    let a = CompColMatrix::new(3, 3, &[1.0; 3], &[0, 1, 2], &[0, 1, 2, 3]);
+    let tree = EliminationTree::new(&a);
+    assert_eq!(tree.roots(), &[0, 1, 2]);
+    assert_eq!(tree.height(), 1);
+    assert_eq!(tree.relaxed_supernodes(10), vec![0..1, 1..2, 2..3]);
+}
+
+#[test]
+fn matches_preorder() {
+    let a = example_matrix();
+    let tree = EliminationTree::new(&a);
+    // Starting from the natural order, preorder's column permutation
+    // is the postorder, and its tree is the renumbered one
+    let ac = preorder(&Options::new(), &a, &[0, 1, 2, 3, 4]);
+    let post = tree.postorder();
+    assert_eq!(post.as_slice(), ac.perm_c());
+    let postordered = EliminationTree::from_parents(ac.etree().to_vec());
+    assert!(postordered.is_postordered());
+    assert_eq!(postordered, EliminationTree::with_permutation(&a, &post));
+    assert_eq!(postordered.height(), tree.height());
+}
+
+#[test]
+fn permuted_columns() {
+    let a = tridiagonal(5);
+    let identity = Permutation::identity(5);
+    assert_eq!(EliminationTree::with_permutation(&a, &identity), EliminationTree::new(&a));
+    // Reversing the columns leaves A^T A pentadiagonal
+    let reverse = Permutation::new(vec![4, 3, 2, 1, 0]).unwrap();
+    assert_eq!(EliminationTree::with_permutation(&a, &reverse).parents(), &[1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn relaxed_supernodes_of_a_chain() {
+    let tree = EliminationTree::new(&tridiagonal(4));
+    assert_eq!(tree.relaxed_supernodes(2), vec![0..2]);
+    assert_eq!(tree.relaxed_supernodes(10), vec![0..4]);
+}
+
+#[test]
+fn tree_that_is_not_postordered() {
+    // 3 has children 1 and 2, and 2 has child 0
+    let tree = EliminationTree::from_parents(vec![2, 3, 3, 4]);
+    assert!(!tree.is_postordered());
+    assert_eq!(tree.postorder().as_slice(), &[1, 0, 2, 3]);
+    assert_eq!(tree.subtree_sizes(), vec![1, 1, 2, 4]);
+    assert_eq!(tree.height(), 3);
+    assert_eq!(tree.relaxed_supernodes(10), vec![0..4]);
+    assert_eq!(tree.relaxed_supernodes(2), vec![0..1, 1..2]);
+}
+
+#[test]
+#[should_panic(expected = "must come after it")]
+fn parent_before_child() {
+    EliminationTree::from_parents(vec![2, 0, 3]);
+}
+
+#[test]
+fn empty_tree() {
+    let tree = EliminationTree::from_parents(Vec::new());
+    assert!(tree.is_empty());
+    assert!(tree.roots().is_empty());
+    assert_eq!(tree.height(), 0);
+    assert!(tree.relaxed_supernodes(10).is_empty());
+}
@@ -0,0 +1,91 @@
+//! Check the expert driver, and the quantities it returns.
+
+mod common;
+
+use common::{example_matrix, residual};
+use csuperlu_sys::{
+    solve_expert, CompColMatrix, DenseMatrix, Equilibration, Options, SolveError, SolveWarning,
+};
+use num::Complex;
+
+#[test]
+fn expert_driver_statistics() {
+    let a = example_matrix();
+    let rhs = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+    let b = DenseMatrix::new(5, 1, &rhs);
+    let options = Options::new()
+	.condition_number(true)
+	.pivot_growth(true)
+	.iterative_refinement(true);
+    let solution = solve_expert(&a, &b, &options).unwrap();
+
+    assert!(residual(&a, solution.x().values(), &rhs) < 1e-12);
+    assert_eq!(b.values(), &rhs[..]);
+    let rcond = solution.rcond().unwrap();
+    assert!(rcond > 0.0 && rcond <= 1.0);
+    assert!(solution.recip_pivot_growth().unwrap() > 0.0);
+    assert_eq!(solution.ferr().len(), 1);
+    assert!(solution.berr()[0] < 1e-12);
+    assert!(solution.memory_usage().for_lu > 0.0);
+    assert_eq!(solution.warning(), None);
+}
+
+#[test]
+fn optional_quantities_are_not_reported() {
+    let a = example_matrix();
+    let b = DenseMatrix::new(5, 1, &[1.0; 5]);
+    let solution = solve_expert(&a, &b, &Options::new()).unwrap();
+    assert_eq!(solution.rcond(), None);
+    assert_eq!(solution.recip_pivot_growth(), None);
+}
+
+#[test]
+fn badly_scaled_matrix_is_equilibrated() {
+    // diag(1e10, 1, 1e-10), with a small off-diagonal element
+    let a = CompColMatrix::new(3, 3, &[1e10, 1.0, 1.0, 1e-10], &[0, 1, 2, 2], &[0, 1, 3, 4]);
+    let b = DenseMatrix::new(3, 1, &[1.0; 3]);
+    let solution = solve_expert(&a, &b, &Options::new()).unwrap();
+    assert_ne!(solution.equilibration(), Equilibration::None);
+    assert_eq!(solution.row_scale().is_some(), solution.equilibration().rows());
+    assert!(residual(&a, solution.x().values(), &[1.0; 3]) < 1e-6);
+}
+
+#[test]
+fn ill_conditioned_matrix_gives_warning() {
+    // [[1, 1], [1, 1 + 1e-7]] is singular to single precision
+    let a = CompColMatrix::new(2, 2, &[1.0f32, 1.0, 1.0, 1.0 + 1e-7], &[0, 1, 0, 1], &[0, 2, 4]);
+    let b = DenseMatrix::new(2, 1, &[1.0, 2.0]);
+    let options = Options::new().equilibrate(false).condition_number(true);
+    let solution = solve_expert(&a, &b, &options).unwrap();
+    assert_eq!(solution.warning(), Some(SolveWarning::IllConditioned));
+}
+
+#[test]
+fn expert_driver_adjoint() {
+    let values: Vec<Complex<f64>> = [4.0, 1.0, 2.0, 5.0].iter()
+	.enumerate().map(|(k, &x)| Complex::new(x, k as f64)).collect();
+    let a = CompColMatrix::new(2, 2, &values, &[0, 1, 0, 1], &[0, 2, 4]);
+    let rhs = [Complex::new(1.0, 1.0), Complex::new(0.0, 2.0)];
+    let b = DenseMatrix::new(2, 1, &rhs);
+    let solution = solve_expert(&a.adjoint(), &b, &Options::new()).unwrap();
+
+    // Check A^H x = b, where A = [[a0, a2], [a1, a3]]
+    let x = solution.x().values();
+    let lhs = [
+	values[0].conj() * x[0] + values[1].conj() * x[1],
+	values[2].conj() * x[0] + values[3].conj() * x[1],
+    ];
+    for (lhs, rhs) in lhs.iter().zip(&rhs) {
+	assert!((lhs - rhs).norm() < 1e-12);
+    }
+}
+
+#[test]
+fn expert_driver_singular() {
+    let a = CompColMatrix::new(2, 2, &[1.0f64, 1.0], &[0, 1], &[0, 2, 2]);
+    let b = DenseMatrix::new(2, 1, &[1.0, 1.0]);
+    match solve_expert(&a, &b, &Options::new()) {
+	Err(SolveError::Singular(1)) => (),
+	other => panic!("expected a singular matrix, got {:?}", other.map(|_| ())),
+    }
+}
@@ -0,0 +1,173 @@
+//! Check FGMRES, with and without the incomplete LU preconditioner,
+//! against the ditersol.c example.
+
+mod common;
+
+use common::example_matrix;
+use csuperlu_sys::{
+    CompColMatrix, Fgmres, IluOptions, IncompleteLu, LinearOperator, MatrixFree, Termination,
+};
+use num::Complex;
+
+/// The field width of a Fortran format such as (16I5) or (5E15.8)
+fn field_width(format: &str) -> usize {
+    let spec = format.trim().trim_start_matches('(').trim_end_matches(')');
+    let start = spec.find(|ch: char| ch.is_ascii_alphabetic()).unwrap() + 1;
+    let end = spec[start..].find(|ch: char| !ch.is_ascii_digit()).map_or(spec.len(), |k| start + k);
+    spec[start..end].parse().unwrap()
+}
+
+/// Read a real, unsymmetric, assembled (RUA) matrix in Harwell-Boeing
+/// format
+fn read_rua(path: &str) -> CompColMatrix<f64> {
+    let text = std::fs::read_to_string(path).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    let counts: Vec<usize> = lines[1].split_whitespace().map(|x| x.parse().unwrap()).collect();
+    let (ptrcrd, indcrd, valcrd) = (counts[1], counts[2], counts[3]);
+    let sizes: Vec<&str> = lines[2].split_whitespace().collect();
+    assert_eq!(sizes[0], "RUA");
+    let (nrow, ncol): (usize, usize) = (sizes[1].parse().unwrap(), sizes[2].parse().unwrap());
+    let formats: Vec<&str> = lines[3].split_whitespace().collect();
+    // The fields of each card have a fixed width, and may not be
+    // separated by spaces
+    let card = |start: usize, count: usize, width: usize| -> Vec<String> {
+	lines[start..start + count].iter().flat_map(|line| {
+	    let chars: Vec<char> = line.trim_end().chars().collect();
+	    chars.chunks(width).map(|field| field.iter().collect::<String>().trim().to_string())
+		.collect::<Vec<_>>()
+	}).collect()
+    };
+    let start = if counts.len() > 4 && counts[4] > 0 { 5 } else { 4 };
+    let colptr: Vec<i32> = card(start, ptrcrd, field_width(formats[0])).iter()
+	.map(|x| x.parse::<i32>().unwrap() - 1).collect();
+    let rowind: Vec<i32> = card(start + ptrcrd, indcrd, field_width(formats[1])).iter()
+	.map(|x| x.parse::<i32>().unwrap() - 1).collect();
+    let values: Vec<f64> = card(start + ptrcrd + indcrd, valcrd, field_width(formats[2])).iter()
+	.map(|x| x.replace(['D', 'd'], "E").parse().unwrap()).collect();
+    assert_eq!(colptr.len(), ncol + 1);
+    CompColMatrix::new(nrow, ncol, &values, &rowind, &colptr)
+}
+
+/// The 400x400 matrix used by the SuperLU examples
+fn g20() -> CompColMatrix<f64> {
+    read_rua(concat!(env!("CARGO_MANIFEST_DIR"), "/superlu-5.3.0/EXAMPLE/g20.rua"))
+}
+
+#[test]
+fn reproduces_ditersol_on_g20() {
+    // ditersol.c factorizes A (which equilibrates it in place), and
+    // then runs FGMRES on the scaled system diag(R) A diag(C) y =
+    // diag(R) b, with b = A [1, ..., 1]^T, preconditioned by the
+    // incomplete factors of the scaled matrix
+    let a = g20();
+    let n = a.ncol();
+    let mut b = vec![0.0; n];
+    a.apply(&vec![1.0; n], &mut b);
+    let ilu = IncompleteLu::new(&a, &IluOptions::new()).unwrap();
+    let (r, c) = (ilu.row_scale().unwrap(), ilu.col_scale().unwrap());
+
+    let mut scaled_values = a.nzval().to_vec();
+    for (j, &cj) in c.iter().enumerate() {
+	for k in a.colptr()[j] as usize..a.colptr()[j + 1] as usize {
+	    scaled_values[k] *= r[a.rowind()[k] as usize] * cj;
+	}
+    }
+    let scaled = CompColMatrix::new(n, n, &scaled_values, a.rowind(), a.colptr());
+    let scaled_b: Vec<f64> = b.iter().zip(r).map(|(bi, ri)| bi * ri).collect();
+    // The incomplete factors of the scaled matrix, without the
+    // scaling that IncompleteLu::apply adds
+    let precondition = |v: &[f64], z: &mut [f64]| {
+	for ((zi, vi), ri) in z.iter_mut().zip(v).zip(r) {
+	    *zi = vi / ri;
+	}
+	ilu.apply(z).unwrap();
+	for (zi, ci) in z.iter_mut().zip(c) {
+	    *zi /= ci;
+	}
+    };
+
+    let mut y = vec![0.0; n];
+    let fgmres = Fgmres::new().restart((n / 3 + 1).min(50)).tolerance(1e-8).max_iterations(1000);
+    let report = fgmres.solve_preconditioned(&scaled, &precondition, &scaled_b, &mut y).unwrap();
+
+    // The history printed by ditersol (to three digits)
+    assert_eq!(report.termination(), Termination::Converged);
+    assert_eq!(report.iterations(), 3);
+    let expected = [9.38e+00, 6.16e-03, 1.32e-06, 2.05e-10];
+    assert_eq!(report.history().len(), expected.len());
+    for (actual, expected) in report.history().iter().zip(&expected) {
+	assert!((actual - expected).abs() < 0.01 * expected, "{} != {}", actual, expected);
+    }
+    assert!(report.residual_norm() / report.history()[0] < 1e-10);
+    for (yi, ci) in y.iter().zip(c) {
+	assert!((yi * ci - 1.0).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn incomplete_lu_preconditioner() {
+    let a = g20();
+    let n = a.ncol();
+    let mut b = vec![0.0; n];
+    a.apply(&vec![1.0; n], &mut b);
+    let ilu = IncompleteLu::new(&a, &IluOptions::new()).unwrap();
+    let mut x = vec![0.0; n];
+    let report = Fgmres::new().solve_preconditioned(&a, &ilu, &b, &mut x).unwrap();
+    assert!(report.converged());
+    assert!(report.iterations() <= 5);
+    assert!(x.iter().all(|xi| (xi - 1.0).abs() < 1e-8));
+}
+
+#[test]
+fn unpreconditioned_gmres_is_exact_after_n_steps() {
+    let a = example_matrix();
+    let b = [1.0, 2.0, 3.0, 4.0, 5.0];
+    let mut x = [0.0; 5];
+    let report = Fgmres::new().tolerance(1e-12).solve(&a, &b, &mut x);
+    assert!(report.converged());
+    assert!(report.iterations() <= 5);
+    let mut ax = [0.0; 5];
+    a.apply(&x, &mut ax);
+    assert!(ax.iter().zip(&b).all(|(lhs, rhs)| (lhs - rhs).abs() < 1e-10));
+}
+
+#[test]
+fn matrix_free_complex_operator() {
+    // The tridiagonal matrix with 4 + i on the diagonal and -1 either
+    // side, applied without storing it (it is its own transpose)
+    let n = 50;
+    let tridiagonal = |x: &[Complex<f64>], y: &mut [Complex<f64>]| {
+	for i in 0..x.len() {
+	    y[i] = Complex::new(4.0, 1.0) * x[i];
+	    if i > 0 {
+		y[i] -= x[i - 1];
+	    }
+	    if i + 1 < x.len() {
+		y[i] -= x[i + 1];
+	    }
+	}
+    };
+    let a = MatrixFree::new(n, n, tridiagonal, tridiagonal);
+    let b: Vec<Complex<f64>> = (0..n).map(|k| Complex::new(1.0, k as f64)).collect();
+    let mut x = vec![Complex::new(0.0, 0.0); n];
+    let report = Fgmres::new().restart(10).tolerance(1e-10).solve(&a, &b, &mut x);
+    assert!(report.converged());
+    let mut ax = vec![Complex::new(0.0, 0.0); n];
+    a.apply(&x, &mut ax);
+    let error = ax.iter().zip(&b).map(|(lhs, rhs)| (lhs - rhs).norm()).fold(0.0, f64::max);
+    assert!(error < 1e-8);
+    assert!(report.residual_norm() <= 1e-10 * report.history()[0]);
+}
+
+#[test]
+fn stops_at_max_iterations() {
+    let a = g20();
+    let b = vec![1.0f64; a.nrow()];
+    let mut x = vec![0.0; a.ncol()];
+    let report = Fgmres::new().restart(2).max_iterations(3).solve(&a, &b, &mut x);
+    assert_eq!(report.termination(), Termination::MaxIterations);
+    assert_eq!(report.iterations(), 3);
+    // The initial residual and one estimate per iteration
+    assert_eq!(report.history().len(), 4);
+    assert!(report.residual_norm() < report.history()[0]);
+}
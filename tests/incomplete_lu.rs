@@ -0,0 +1,120 @@
+//! Check the incomplete LU factorization, and solving with it.
+
+mod common;
+
+use common::{example_matrix, residual};
+use csuperlu_sys::{
+    factorize, CompColMatrix, DropRule, IluNorm, IluOptions, IncompleteLu, Milu, Options,
+};
+use num::Complex;
+
+/// The five-point Laplacian on a k x k grid, which fills in when
+/// factorized exactly
+fn laplacian(k: usize) -> CompColMatrix<f64> {
+    let (mut values, mut rowind, mut colptr) = (vec![], vec![], vec![0]);
+    for j in 0..k * k {
+	let (x, y) = (j % k, j / k);
+	let mut entry = |row: usize, value: f64| {
+	    rowind.push(row as i32);
+	    values.push(value);
+	};
+	if y > 0 { entry(j - k, -1.0) }
+	if x > 0 { entry(j - 1, -1.0) }
+	entry(j, 4.0);
+	if x + 1 < k { entry(j + 1, -1.0) }
+	if y + 1 < k { entry(j + k, -1.0) }
+	colptr.push(rowind.len() as i32);
+    }
+    CompColMatrix::new(k * k, k * k, &values, &rowind, &colptr)
+}
+
+#[test]
+fn no_dropping_gives_exact_solution() {
+    let a = example_matrix();
+    let ilu = IncompleteLu::new(&a, &IluOptions::new().drop_tol(0.0)).unwrap();
+    assert_eq!(ilu.n(), 5);
+    assert_eq!(ilu.zero_pivots(), 0);
+    let rhs = [1.0, 2.0, 3.0, 4.0, 5.0];
+    let mut x = rhs;
+    ilu.apply(&mut x).unwrap();
+    assert!(residual(&a, &x, &rhs) < 1e-12);
+}
+
+#[test]
+fn dropping_reduces_fill() {
+    let a = laplacian(20);
+    let exact = factorize(&a, &Options::new()).unwrap();
+    let exact_nnz = exact.l().nnz() + exact.u().nnz();
+
+    let ilu = IncompleteLu::new(&a, &IluOptions::new()).unwrap();
+    let fill = ilu.fill_statistics();
+    assert_eq!(fill.nnz_a, a.nnz());
+    assert!(fill.nnz_l + fill.nnz_u < exact_nnz);
+    assert!(fill.fill_ratio() > 1.0);
+    assert!(fill.memory_usage.for_lu > 0.0);
+    assert!(fill.memory_usage.total_needed >= fill.memory_usage.for_lu);
+
+    // The approximate solution is close (dgsisx gives the same)
+    let rhs = vec![1.0; a.nrow()];
+    let mut x = rhs.clone();
+    ilu.apply(&mut x).unwrap();
+    assert!(residual(&a, &x, &rhs) < 0.1);
+
+    // Dropping more gives even sparser factors
+    let options = IluOptions::new()
+	.drop_tol(1e-2)
+	.fill_factor(3.0)
+	.drop_rule(DropRule::BASIC | DropRule::AREA);
+    let sparse = IncompleteLu::new(&a, &options).unwrap().fill_statistics();
+    assert!(sparse.nnz_l + sparse.nnz_u < fill.nnz_l + fill.nnz_u);
+}
+
+#[test]
+fn every_variant_is_exact_without_dropping() {
+    let a = example_matrix();
+    let rhs = [1.0, -1.0, 2.0, -2.0, 3.0];
+    for milu in [Milu::Silu, Milu::Smilu1, Milu::Smilu2, Milu::Smilu3] {
+	for norm in [IluNorm::One, IluNorm::Two, IluNorm::Infinity] {
+	    let options = IluOptions::new().drop_tol(0.0).milu(milu).norm(norm);
+	    let mut x = rhs;
+	    IncompleteLu::new(&a, &options).unwrap().apply(&mut x).unwrap();
+	    assert!(residual(&a, &x, &rhs) < 1e-12);
+	}
+    }
+}
+
+#[test]
+fn complex_incomplete_lu() {
+    let values: Vec<Complex<f32>> = example_matrix().nzval().iter().enumerate()
+	.map(|(k, &x)| Complex::new(x as f32, k as f32)).collect();
+    let a = CompColMatrix::new(5, 5, &values, example_matrix().rowind(), example_matrix().colptr());
+    let ilu = IncompleteLu::new(&a, &IluOptions::new().drop_tol(0.0)).unwrap();
+    let rhs = [Complex::new(1.0, -1.0); 5];
+    let mut x = rhs;
+    ilu.apply(&mut x).unwrap();
+    let mut ax = [Complex::new(0.0, 0.0); 5];
+    for (j, &xj) in x.iter().enumerate() {
+	for k in a.colptr()[j] as usize..a.colptr()[j + 1] as usize {
+	    ax[a.rowind()[k] as usize] += a.nzval()[k] * xj;
+	}
+    }
+    for (lhs, rhs) in ax.iter().zip(&rhs) {
+	assert!((lhs - rhs).norm() < 1e-4);
+    }
+}
+
+#[test]
+fn incomplete_lu_of_transpose() {
+    let a = example_matrix();
+    let ilu = IncompleteLu::new(&a.transpose(), &IluOptions::new().drop_tol(0.0)).unwrap();
+    let rhs = [1.0, 0.0, 2.0, 0.0, 3.0];
+    let mut x = rhs;
+    ilu.apply(&mut x).unwrap();
+    assert!(residual(&a.to_transpose(), &x, &rhs) < 1e-12);
+}
+
+#[test]
+#[should_panic(expected = "mutually exclusive")]
+fn exclusive_drop_rules_are_rejected() {
+    IluOptions::new().drop_rule(DropRule::PROWS | DropRule::COLUMN);
+}
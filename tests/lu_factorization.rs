@@ -0,0 +1,57 @@
+//! Check factorizing once and solving many times.
+
+mod common;
+
+use common::{example_matrix, residual};
+use csuperlu_sys::{factorize, CompColMatrix, DenseMatrix, Equilibration, Options, SolveError};
+use num::Complex;
+
+#[test]
+fn factor_once_solve_many_times() {
+    let a = example_matrix();
+    let lu = factorize(&a, &Options::new()).unwrap();
+    assert_eq!(lu.n(), 5);
+    for k in 0..10 {
+	let rhs: Vec<f64> = (0..5).map(|i| (i * k) as f64 + 1.0).collect();
+	let mut x = rhs.clone();
+	lu.solve(&mut x).unwrap();
+	assert!(residual(&a, &x, &rhs) < 1e-12);
+    }
+}
+
+#[test]
+fn solve_many_right_hand_sides() {
+    let values: Vec<Complex<f64>> = example_matrix().nzval().iter().enumerate()
+	.map(|(k, &x)| Complex::new(x, -(k as f64))).collect();
+    let a = CompColMatrix::new(5, 5, &values, example_matrix().rowind(), example_matrix().colptr());
+    let lu = factorize(&a, &Options::new()).unwrap();
+
+    let rhs: Vec<Complex<f64>> = (0..15).map(|k| Complex::new(k as f64, 1.0)).collect();
+    let mut b = DenseMatrix::new(5, 3, &rhs);
+    lu.solve_many(&mut b).unwrap();
+    for col in 0..3 {
+	let range = 5 * col..5 * col + 5;
+	assert!(residual(&a, &b.values()[range.clone()], &rhs[range]) < 1e-12);
+    }
+}
+
+#[test]
+fn scaling_is_reapplied() {
+    let a = CompColMatrix::new(3, 3, &[1e10f64, 1.0, 1.0, 1e-10], &[0, 1, 2, 2], &[0, 1, 3, 4]);
+    let lu = factorize(&a, &Options::new()).unwrap();
+    assert_ne!(lu.equilibration(), Equilibration::None);
+    assert_eq!(lu.row_scale().is_some(), lu.equilibration().rows());
+
+    let rhs = [2e10, 1.0, 1.0 + 3e-10];
+    let mut x = rhs;
+    lu.solve(&mut x).unwrap();
+    for (xi, expected) in x.iter().zip(&[2.0, 1.0, 3.0]) {
+	assert!((xi - expected).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn factorize_singular() {
+    let a = CompColMatrix::new(2, 2, &[1.0f32, 1.0], &[0, 1], &[0, 2, 2]);
+    assert_eq!(factorize(&a, &Options::new()).err(), Some(SolveError::Singular(1)));
+}
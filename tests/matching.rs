@@ -0,0 +1,119 @@
+//! Check the MC64 large-diagonal row permutations and scale factors.
+
+use csuperlu_sys::{
+    factorize, large_diagonal_matching, CompColMatrix, MatchingJob, Options, SolveError,
+};
+use num::Complex;
+
+const JOBS: [MatchingJob; 5] = [
+    MatchingJob::MaxCardinality,
+    MatchingJob::MaxSmallest,
+    MatchingJob::MaxSmallestAlt,
+    MatchingJob::MaxSum,
+    MatchingJob::MaxProduct,
+];
+
+/// [[0, 0, 1], [0, 2, 0], [3, 0, 0]]
+fn anti_diagonal() -> CompColMatrix<f64> {
+    CompColMatrix::new(3, 3, &[3.0, 2.0, 1.0], &[2, 1, 0], &[0, 1, 2, 3])
+}
+
+/// The saddle-point matrix [[4, 1, 1], [1, 4, 1], [1, 1, 0]], with
+/// no entry stored at (2, 2)
+fn saddle_point() -> CompColMatrix<f64> {
+    let values = [4.0, 1.0, 1.0, 1.0, 4.0, 1.0, 1.0, 1.0];
+    CompColMatrix::new(3, 3, &values, &[0, 1, 2, 0, 1, 2, 0, 1], &[0, 3, 6, 8])
+}
+
+/// The stored diagonal entries of A (None where there is no entry)
+fn diagonal(a: &CompColMatrix<f64>) -> Vec<Option<f64>> {
+    let (colptr, rowind, values) = (a.colptr(), a.rowind(), a.nzval());
+    (0..a.ncol())
+	.map(|j| (colptr[j] as usize..colptr[j + 1] as usize)
+	     .find(|&k| rowind[k] as usize == j)
+	     .map(|k| values[k]))
+	.collect()
+}
+
+#[test]
+fn only_one_perfect_matching() {
+    for job in JOBS {
+	let matching = large_diagonal_matching(&anti_diagonal(), job).unwrap();
+	assert_eq!(matching.perm_r().as_slice(), &[2, 1, 0]);
+	assert_eq!(matching.row_scale().is_some(), job == MatchingJob::MaxProduct);
+	assert_eq!(matching.col_scale().is_some(), job == MatchingJob::MaxProduct);
+    }
+    let matching = large_diagonal_matching(&anti_diagonal(), MatchingJob::MaxSum).unwrap();
+    let permuted = matching.apply(&anti_diagonal());
+    assert_eq!(diagonal(&permuted), vec![Some(3.0), Some(2.0), Some(1.0)]);
+}
+
+#[test]
+fn saddle_point_diagonal_is_filled() {
+    let a = saddle_point();
+    for job in JOBS {
+	let matching = large_diagonal_matching(&a, job).unwrap();
+	let permuted = matching.apply(&a);
+	assert!(diagonal(&permuted).iter().all(|d| d.is_some()));
+    }
+}
+
+#[test]
+fn max_product_scaling() {
+    // [[1e-3, 2, 0], [3, 0, 1e4], [0, 1, 5]]
+    let a = CompColMatrix::new(3, 3, &[1e-3, 3.0, 2.0, 1.0, 1e4, 5.0], &[0, 1, 0, 2, 1, 2],
+			       &[0, 2, 4, 6]);
+    let scaled = large_diagonal_matching(&a, MatchingJob::MaxProduct).unwrap().apply(&a);
+    for d in diagonal(&scaled) {
+	assert!((d.unwrap().abs() - 1.0).abs() < 1e-12);
+    }
+    for v in scaled.nzval() {
+	assert!(v.abs() <= 1.0 + 1e-12);
+    }
+}
+
+#[test]
+fn solve_the_preprocessed_system() {
+    // A x = b with x = [1, 2, 3]
+    let a = saddle_point();
+    let b = [9.0, 12.0, 3.0];
+    let matching = large_diagonal_matching(&a, MatchingJob::MaxProduct).unwrap();
+    let m = matching.apply(&a);
+    let (perm, scaling) = matching.into_parts();
+    let (r, c) = scaling.unwrap();
+
+    // Solve (P R A C) y = P R b, then x = C y
+    let mut y = [0.0; 3];
+    for i in 0..3 {
+	y[perm.as_slice()[i] as usize] = r[i] * b[i];
+    }
+    factorize(&m, &Options::new()).unwrap().solve(&mut y).unwrap();
+    for j in 0..3 {
+	assert!((c[j] * y[j] - (j + 1) as f64).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn structurally_singular() {
+    // [[1, 1], [0, 0]]
+    let a = CompColMatrix::new(2, 2, &[1.0, 1.0], &[0, 0], &[0, 1, 2]);
+    for job in JOBS {
+	assert_eq!(large_diagonal_matching(&a, job).unwrap_err(), SolveError::StructurallySingular);
+    }
+    let empty = CompColMatrix::<f64>::new(2, 2, &[], &[], &[0, 0, 0]);
+    assert_eq!(large_diagonal_matching(&empty, MatchingJob::MaxSum).unwrap_err(),
+	       SolveError::StructurallySingular);
+}
+
+#[test]
+fn complex_matrix() {
+    // [[0, i], [2, 0]]
+    let a = CompColMatrix::new(2, 2, &[Complex::new(2.0, 0.0), Complex::new(0.0, 1.0)], &[1, 0],
+			       &[0, 1, 2]);
+    let matching = large_diagonal_matching(&a, MatchingJob::MaxProduct).unwrap();
+    assert_eq!(matching.perm_r().as_slice(), &[1, 0]);
+    let scaled = matching.apply(&a);
+    for v in scaled.nzval() {
+	assert!((v.norm() - 1.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,99 @@
+//! Check single-precision factorization with double-precision
+//! refinement.
+
+mod common;
+
+use common::example_matrix;
+use csuperlu_sys::{
+    CompColMatrix, CompRowMatrix, LinearOperator, MixedPrecisionLu, Options, SolveError,
+};
+use num::Complex;
+
+/// The n x n Hilbert matrix, with entries 1 / (i + j + 1)
+fn hilbert(n: usize) -> CompColMatrix<f64> {
+    let values: Vec<f64> = (0..n * n).map(|k| 1.0 / (k / n + k % n + 1) as f64).collect();
+    let rowind: Vec<i32> = (0..n * n).map(|k| (k % n) as i32).collect();
+    let colptr: Vec<i32> = (0..=n).map(|j| (j * n) as i32).collect();
+    CompColMatrix::new(n, n, &values, &rowind, &colptr)
+}
+
+#[test]
+fn refines_to_double_precision() {
+    let a = example_matrix();
+    let x_true = [1.0 / 3.0, -2.0, 0.1, 7.0, 1e-3];
+    let mut b = vec![0.0; 5];
+    a.apply(&x_true, &mut b);
+    let mut lu = MixedPrecisionLu::new(&a, &Options::new()).unwrap();
+    assert!(lu.is_single_precision());
+    let report = lu.solve(&mut b).unwrap();
+    assert!(!report.used_double_precision());
+    assert!(report.iterations() >= 1);
+    assert!(report.backward_error() <= f64::EPSILON * 5f64.sqrt());
+    for (x, y) in b.iter().zip(&x_true) {
+	assert!((x - y).abs() < 1e-13 * y.abs().max(1.0), "{} != {}", x, y);
+    }
+}
+
+#[test]
+fn complex_compressed_row_matrix() {
+    // A compressed-row matrix is factorized as its transpose
+    let values: Vec<Complex<f64>> = example_matrix().nzval().iter().enumerate()
+	.map(|(k, &v)| Complex::new(v, 0.5 * k as f64)).collect();
+    let (rowind, colptr) = (example_matrix().rowind().to_vec(), example_matrix().colptr().to_vec());
+    let a = CompRowMatrix::new(5, 5, &values, &rowind, &colptr);
+    let x_true: Vec<Complex<f64>> = (0..5).map(|k| Complex::new(1.0 / (k + 1) as f64, -0.3)).collect();
+    let mut b = vec![Complex::new(0.0, 0.0); 5];
+    CompColMatrix::new(5, 5, &values, &rowind, &colptr).apply_transpose(&x_true, &mut b);
+    let mut lu = MixedPrecisionLu::new(&a, &Options::new()).unwrap();
+    let report = lu.solve(&mut b).unwrap();
+    assert!(!report.used_double_precision());
+    for (x, y) in b.iter().zip(&x_true) {
+	assert!((x - y).norm() < 1e-13, "{} != {}", x, y);
+    }
+}
+
+#[test]
+fn falls_back_when_single_precision_is_singular() {
+    // 1 + 1e-9 rounds to 1 in single precision
+    let a = CompColMatrix::new(2, 2, &[1.0, 1.0, 1.0, 1.0 + 1e-9], &[0, 1, 0, 1], &[0, 2, 4]);
+    let mut lu = MixedPrecisionLu::new(&a, &Options::new()).unwrap();
+    assert!(!lu.is_single_precision());
+    let mut b = [2.0, 2.0 + 1e-9];
+    let report = lu.solve(&mut b).unwrap();
+    assert!(report.used_double_precision());
+    assert!((b[0] - 1.0).abs() < 1e-6 && (b[1] - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn falls_back_when_the_refinement_stalls() {
+    // cond(A) is about 5e8, too large for the single-precision factors
+    // to refine, although they can be computed
+    let a = hilbert(7);
+    let mut lu = MixedPrecisionLu::new(&a, &Options::new()).unwrap();
+    assert!(lu.is_single_precision());
+    let mut b = vec![0.0; 7];
+    a.apply(&[1.0; 7], &mut b);
+    let report = lu.solve(&mut b).unwrap();
+    assert!(report.used_double_precision());
+    assert!(!lu.is_single_precision());
+    assert!(report.backward_error() < 1e-12);
+    for x in &b {
+	assert!((x - 1.0).abs() < 1e-5, "{} != 1", x);
+    }
+}
+
+#[test]
+fn falls_back_when_values_overflow_single_precision() {
+    let a = CompColMatrix::new(2, 2, &[1e300, 1.0], &[0, 1], &[0, 1, 2]);
+    let mut lu = MixedPrecisionLu::new(&a, &Options::new()).unwrap();
+    assert!(!lu.is_single_precision());
+    let mut b = [1e300, 3.0];
+    lu.solve(&mut b).unwrap();
+    assert!((b[0] - 1.0).abs() < 1e-15 && (b[1] - 3.0).abs() < 1e-15);
+}
+
+#[test]
+fn singular_in_double_precision() {
+    let a = CompColMatrix::new(2, 2, &[1.0, 1.0], &[0, 1], &[0, 2, 2]);
+    assert!(matches!(MixedPrecisionLu::new(&a, &Options::new()), Err(SolveError::Singular(_))));
+}
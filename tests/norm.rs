@@ -0,0 +1,83 @@
+//! Check matrix norms against values computed by hand.
+
+mod common;
+
+use common::example_matrix;
+use csuperlu_sys::{CompColMatrix, CompRowMatrix, DenseMatrix, MatrixNorm, Norm};
+use num::Complex;
+
+/// The example matrix in column-major order
+fn dense_values() -> Vec<f64> {
+    let a = example_matrix();
+    let mut values = vec![0.0; 25];
+    for j in 0..5 {
+	for k in a.colptr()[j] as usize..a.colptr()[j + 1] as usize {
+	    values[5 * j + a.rowind()[k] as usize] = a.nzval()[k];
+	}
+    }
+    values
+}
+
+#[test]
+fn compressed_column() {
+    // Column sums 43, 45, 37, 26, 39; row sums 61, 33, 28, 26, 42
+    let a = example_matrix();
+    assert_eq!(a.norm(Norm::One), 45.0);
+    assert_eq!(a.norm(Norm::Inf), 61.0);
+    assert_eq!(a.norm(Norm::Max), 21.0);
+    assert!((a.norm(Norm::Frobenius) - 3306f64.sqrt()).abs() < 1e-12);
+}
+
+#[test]
+fn all_matrix_types_agree() {
+    let a = example_matrix();
+    let dense = DenseMatrix::new(5, 5, &dense_values());
+    // The arrays of A, read by rows, describe A^T
+    let row = CompRowMatrix::new(5, 5, a.nzval(), a.rowind(), a.colptr());
+    for (norm, transposed) in [(Norm::One, Norm::Inf), (Norm::Inf, Norm::One),
+			       (Norm::Max, Norm::Max),
+			       (Norm::Frobenius, Norm::Frobenius)] {
+	let expected = a.norm(norm);
+	assert!((dense.norm(norm) - expected).abs() < 1e-12, "{:?}", norm);
+	assert!((row.norm(transposed) - expected).abs() < 1e-12, "{:?}", norm);
+	assert!((a.transpose().norm(transposed) - expected).abs() < 1e-12, "{:?}", norm);
+	assert!((a.adjoint().norm(transposed) - expected).abs() < 1e-12, "{:?}", norm);
+    }
+}
+
+#[test]
+fn borrowed_dense_matrix() {
+    // [[1, -2], [3, 4]]
+    let mut values = [1.0, 3.0, -2.0, 4.0];
+    let a = DenseMatrix::from_slice(2, 2, &mut values);
+    assert_eq!(a.norm(Norm::One), 6.0);
+    assert_eq!(a.norm(Norm::Inf), 7.0);
+    assert_eq!(a.norm(Norm::Max), 4.0);
+    assert!((a.norm(Norm::Frobenius) - 30f64.sqrt()).abs() < 1e-15);
+}
+
+#[test]
+fn half_stored_symmetric_matrix() {
+    // [[2, -3], [-3, 4]]
+    let a = CompColMatrix::symmetric_lower(2, &[2.0, -3.0, 4.0], &[0, 1, 1], &[0, 2, 3]);
+    assert_eq!(a.norm(Norm::One), 7.0);
+    assert_eq!(a.norm(Norm::Inf), 7.0);
+    assert!((a.norm(Norm::Frobenius) - 38f64.sqrt()).abs() < 1e-15);
+}
+
+#[test]
+fn complex_values() {
+    // [[3 + 4i, 0], [i, -2]]
+    let values = [Complex::new(3.0f32, 4.0), Complex::new(0.0, 1.0), Complex::new(-2.0, 0.0)];
+    let a = CompColMatrix::new(2, 2, &values, &[0, 1, 1], &[0, 2, 3]);
+    assert_eq!(a.norm(Norm::One), 6.0);
+    assert_eq!(a.norm(Norm::Inf), 5.0);
+    assert_eq!(a.norm(Norm::Max), 5.0);
+    assert!((a.norm(Norm::Frobenius) - 30f32.sqrt()).abs() < 1e-6);
+}
+
+#[test]
+fn no_overflow_in_frobenius_norm() {
+    let a = CompColMatrix::new(2, 2, &[3e200, 4e200], &[0, 1], &[0, 1, 2]);
+    assert!((a.norm(Norm::Frobenius) / 5e200 - 1.0).abs() < 1e-15);
+}
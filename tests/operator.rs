@@ -0,0 +1,124 @@
+//! Check the operator and preconditioner implementations for matrices
+//! and factorizations.
+
+mod common;
+
+use common::example_matrix;
+use csuperlu_sys::{
+    factorize, solve, CompColMatrix, DenseMatrix, Fgmres, IluOptions, IncompleteLu,
+    LinearOperator, MatrixFree, Options, Preconditioner,
+};
+use num::Complex;
+
+/// Check that B A x = x and B^T A^T x = x, where B is meant to be
+/// the inverse of A, using only the operator traits
+fn check_inverse<T, A, B>(a: &A, b: &B, x: &[T])
+where
+    T: csuperlu_sys::ValueType + Into<Complex<f64>>,
+    A: LinearOperator<T> + ?Sized,
+    B: LinearOperator<T> + ?Sized,
+{
+    assert_eq!((b.nrows(), b.ncols()), (a.ncols(), a.nrows()));
+    let mut ax = vec![T::default(); a.nrows()];
+    let mut bax = vec![T::default(); b.nrows()];
+    a.apply(x, &mut ax);
+    b.apply(&ax, &mut bax);
+    a.apply_transpose(x, &mut ax);
+    let mut btatx = vec![T::default(); b.nrows()];
+    b.apply_transpose(&ax, &mut btatx);
+    for ((&xi, &y), &z) in x.iter().zip(&bax).zip(&btatx) {
+	assert!((xi.into() - y.into()).norm() < 1e-10);
+	assert!((xi.into() - z.into()).norm() < 1e-10);
+    }
+}
+
+#[test]
+fn matrix_apply_and_transpose() {
+    let a = example_matrix();
+    assert_eq!((a.nrows(), a.ncols()), (5, 5));
+    let x = [1.0, 2.0, 3.0, 4.0, 5.0];
+    let mut y = [0.0; 5];
+    a.apply(&x, &mut y);
+    assert_eq!(y, [19.0 + 63.0 + 84.0, 12.0 + 42.0, 24.0 + 48.0, 20.0 + 105.0, 12.0 + 24.0 + 90.0]);
+    a.apply_transpose(&x, &mut y);
+    let mut expected = [0.0; 5];
+    a.to_transpose().apply(&x, &mut expected);
+    assert_eq!(y, expected);
+
+    // The transpose is not conjugated
+    let values: Vec<Complex<f64>> = a.nzval().iter().map(|&v| Complex::new(v, 1.0)).collect();
+    let a = CompColMatrix::new(5, 5, &values, a.rowind(), a.colptr());
+    let x: Vec<Complex<f64>> = (0..5).map(|k| Complex::new(1.0, k as f64)).collect();
+    let mut y = vec![Complex::new(0.0, 0.0); 5];
+    let mut expected = y.clone();
+    a.apply_transpose(&x, &mut y);
+    a.to_transpose().apply(&x, &mut expected);
+    assert_eq!(y, expected);
+}
+
+#[test]
+fn lu_factorization_is_the_inverse() {
+    let a = example_matrix();
+    let lu = factorize(&a, &Options::new()).unwrap();
+    check_inverse(&a, &lu, &[1.0, -2.0, 3.0, -4.0, 5.0]);
+
+    // Also through a trait object
+    let inverse: &dyn LinearOperator<f64> = &lu;
+    check_inverse(&a, inverse, &[0.5; 5]);
+}
+
+#[test]
+fn simple_driver_factorization_is_the_inverse() {
+    let values: Vec<Complex<f64>> = example_matrix().nzval().iter().enumerate()
+	.map(|(k, &v)| Complex::new(v, k as f64)).collect();
+    let a = CompColMatrix::new(5, 5, &values, example_matrix().rowind(), example_matrix().colptr());
+    let mut b = DenseMatrix::new(5, 1, &[Complex::new(1.0, 0.0); 5]);
+    let factorization = solve(&a, &mut b, &Options::new()).unwrap();
+    let x: Vec<Complex<f64>> = (0..5).map(|k| Complex::new(k as f64, -1.0)).collect();
+    check_inverse(&a, &factorization, &x);
+}
+
+#[test]
+fn incomplete_lu_without_dropping_is_the_inverse() {
+    let a = example_matrix();
+    let ilu = IncompleteLu::new(&a, &IluOptions::new().drop_tol(0.0)).unwrap();
+    check_inverse(&a, &ilu, &[2.0, 0.0, -1.0, 0.0, 3.0]);
+}
+
+#[test]
+fn exact_preconditioner_converges_at_once() {
+    let a = example_matrix();
+    let lu = factorize(&a, &Options::new()).unwrap();
+    let b = [1.0, 2.0, 3.0, 4.0, 5.0];
+    let mut z = [0.0; 5];
+    Preconditioner::apply(&lu, &b, &mut z).unwrap();
+    let mut x = [0.0; 5];
+    let report = Fgmres::new().solve_preconditioned(&a, &lu, &b, &mut x).unwrap();
+    assert!(report.converged());
+    assert_eq!(report.iterations(), 1);
+    assert!(x.iter().zip(&z).all(|(xi, zi)| (xi - zi).abs() < 1e-12));
+}
+
+#[test]
+fn matrix_free_transpose() {
+    // The 2x3 matrix [[1, 2, 0], [0, 1, 3]]
+    let a = MatrixFree::new(
+	2, 3,
+	|x: &[f64], y: &mut [f64]| {
+	    y[0] = x[0] + 2.0 * x[1];
+	    y[1] = x[1] + 3.0 * x[2];
+	},
+	|x: &[f64], y: &mut [f64]| {
+	    y[0] = x[0];
+	    y[1] = 2.0 * x[0] + x[1];
+	    y[2] = 3.0 * x[1];
+	},
+    );
+    assert_eq!((a.nrows(), a.ncols()), (2, 3));
+    let mut y = [0.0; 2];
+    a.apply(&[1.0, 1.0, 1.0], &mut y);
+    assert_eq!(y, [3.0, 4.0]);
+    let mut x = [0.0; 3];
+    a.apply_transpose(&[1.0, 1.0], &mut x);
+    assert_eq!(x, [1.0, 3.0, 3.0]);
+}
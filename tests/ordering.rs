@@ -0,0 +1,98 @@
+//! Check the column orderings and factorizations with user permutations.
+
+mod common;
+
+use common::example_matrix;
+use csuperlu_sys::{
+    column_ordering, factorize, factorize_with_permutation, ColumnOrdering, Options,
+    Permutation, SolveError, SymbolicLu,
+};
+
+/// Solve A x = b with b = A [1, 2, 3, 4, 5], and check the solution
+fn check_solution(solve: impl FnOnce(&mut [f64])) {
+    let mut x = [19.0 + 63.0 + 84.0, 12.0 + 42.0, 24.0 + 48.0, 20.0 + 105.0, 12.0 + 24.0 + 90.0];
+    solve(&mut x);
+    for (i, v) in x.iter().enumerate() {
+	assert!((v - (i + 1) as f64).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn available_orderings_are_permutations() {
+    let a = example_matrix();
+    for ordering in [ColumnOrdering::MmdAtA, ColumnOrdering::MmdAtPlusA, ColumnOrdering::Colamd] {
+	let perm = column_ordering(&a, ordering).unwrap();
+	assert_eq!(perm.len(), 5);
+	assert!(Permutation::new(perm.into_vec()).is_ok());
+    }
+    assert_eq!(column_ordering(&a, ColumnOrdering::Natural).unwrap(), Permutation::identity(5));
+}
+
+#[test]
+fn unavailable_orderings_are_errors() {
+    let a = example_matrix();
+    for ordering in [ColumnOrdering::MetisAtPlusA, ColumnOrdering::ParMetis, ColumnOrdering::Zoltan] {
+	assert!(!ordering.is_available());
+	assert_eq!(column_ordering(&a, ordering), Err(SolveError::UnavailableOrdering(ordering)));
+    }
+}
+
+#[test]
+#[should_panic(expected = "not available")]
+fn options_reject_unavailable_orderings() {
+    Options::new().column_ordering(ColumnOrdering::MetisAtPlusA);
+}
+
+#[test]
+fn invalid_permutations() {
+    assert_eq!(Permutation::new(vec![0, 2, 0]), Err(SolveError::InvalidPermutation(2)));
+    assert_eq!(Permutation::new(vec![0, 3, 1]), Err(SolveError::InvalidPermutation(1)));
+    assert_eq!(Permutation::new(vec![-1, 0]), Err(SolveError::InvalidPermutation(0)));
+    assert!(Permutation::new(vec![]).unwrap().is_empty());
+}
+
+#[test]
+fn inverse() {
+    let perm = Permutation::new(vec![2, 0, 3, 1]).unwrap();
+    assert_eq!(perm.inverse().as_slice(), &[1, 3, 0, 2]);
+    assert_eq!(perm.inverse().inverse(), perm);
+}
+
+#[test]
+fn factorize_with_user_permutation() {
+    let a = example_matrix();
+    let perm = Permutation::new(vec![4, 3, 2, 1, 0]).unwrap();
+    let lu = factorize_with_permutation(&a, &perm, &Options::new()).unwrap();
+    check_solution(|x| lu.solve(x).unwrap());
+
+    // The same as factorizing with the ordering in the options
+    let perm = column_ordering(&a, ColumnOrdering::Colamd).unwrap();
+    let lu = factorize_with_permutation(&a, &perm, &Options::new()).unwrap();
+    assert_eq!(lu.perm_c(), factorize(&a, &Options::new()).unwrap().perm_c());
+}
+
+#[test]
+fn transposed_view() {
+    // The permutation is of the columns of the stored transpose
+    let at = example_matrix().to_transpose();
+    let a = at.transpose();
+    let perm = column_ordering(&a, ColumnOrdering::MmdAtA).unwrap();
+    let lu = factorize_with_permutation(&a, &perm, &Options::new()).unwrap();
+    check_solution(|x| lu.solve(x).unwrap());
+}
+
+#[test]
+fn symbolic_analysis_with_user_permutation() {
+    let a = example_matrix();
+    let perm = Permutation::new(vec![1, 0, 2, 4, 3]).unwrap();
+    let mut symbolic = SymbolicLu::with_permutation(&a, &perm, &Options::new());
+    let lu = symbolic.refactor(&a).unwrap();
+    check_solution(|x| lu.solve(x).unwrap());
+}
+
+#[test]
+#[should_panic(expected = "one element for each column")]
+fn permutation_of_the_wrong_length() {
+    let perm = Permutation::identity(4);
+    let _ = factorize_with_permutation(&example_matrix(), &perm, &Options::new());
+}
@@ -0,0 +1,62 @@
+//! Check that owned and borrowed matrices free their memory correctly.
+//!
+//! Owned matrices copy the input arrays into SuperLU memory, while
+//! borrowed matrices use the Rust arrays in place. If either kind
+//! freed the wrong memory, these tests would crash (or be reported
+//! by a memory checker such as valgrind).
+
+mod common;
+
+use common::example_arrays;
+use csuperlu_sys::{CompColMatrix, DenseMatrix};
+
+#[test]
+fn owned_comp_col_outlives_input() {
+    let (a, asub, xa) = example_arrays();
+    let matrix = CompColMatrix::new(5, 5, &a, &asub, &xa);
+    let (a_copy, asub_copy, xa_copy) = (a.clone(), asub.clone(), xa.clone());
+    drop((a, asub, xa));
+
+    assert_eq!(matrix.nrow(), 5);
+    assert_eq!(matrix.ncol(), 5);
+    assert_eq!(matrix.nnz(), 12);
+    assert_eq!(matrix.nzval(), &a_copy[..]);
+    assert_eq!(matrix.rowind(), &asub_copy[..]);
+    assert_eq!(matrix.colptr(), &xa_copy[..]);
+}
+
+#[test]
+fn borrowed_comp_col_uses_rust_arrays() {
+    let (mut a, mut asub, mut xa) = example_arrays();
+    let a_ptr = a.as_ptr();
+    {
+	let matrix = CompColMatrix::from_slices(5, 5, &mut a, &mut asub, &mut xa);
+	assert_eq!(matrix.nzval().as_ptr(), a_ptr);
+	assert_eq!(matrix.nnz(), 12);
+    }
+    // The vectors are still owned (and later freed) by Rust
+    assert_eq!(a.len(), 12);
+    assert_eq!(xa, vec![0, 3, 6, 8, 10, 12]);
+}
+
+#[test]
+fn owned_and_borrowed_dense() {
+    let mut values = vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0];
+    let owned = DenseMatrix::new(3, 2, &values);
+    assert_ne!(owned.values().as_ptr(), values.as_ptr());
+    {
+	let mut borrowed = DenseMatrix::from_slice(3, 2, &mut values);
+	borrowed.values_mut()[0] = 10.0;
+	assert_eq!(borrowed.lda(), 3);
+    }
+    assert_eq!(values[0], 10.0);
+    assert_eq!(owned.values()[0], 1.0);
+}
+
+#[test]
+#[should_panic(expected = "colptr[ncol]")]
+fn inconsistent_arrays_are_rejected() {
+    let (a, asub, _) = example_arrays();
+    let xa = vec![0, 3, 6, 8, 10, 11];
+    CompColMatrix::new(5, 5, &a, &asub, &xa);
+}
@@ -0,0 +1,81 @@
+//! Check the reciprocal pivot growth factors of the factorizations.
+
+mod common;
+
+use common::example_matrix;
+use csuperlu_sys::{
+    factorize, solve, solve_expert, ColumnOrdering, CompColMatrix, DenseMatrix, IluOptions,
+    IncompleteLu, MixedPrecisionLu, Options, PivotGrowthPolicy,
+};
+
+/// [[1e-12, 1], [1, 1]], which needs a row interchange
+fn small_pivot() -> CompColMatrix<f64> {
+    CompColMatrix::new(2, 2, &[1e-12, 1.0, 1.0, 1.0], &[0, 1, 0, 1], &[0, 2, 4])
+}
+
+#[test]
+fn matches_the_expert_driver() {
+    let a = example_matrix();
+    let options = Options::new().pivot_growth(true);
+    let b = DenseMatrix::new(5, 1, &[1.0; 5]);
+    let expected = solve_expert(&a, &b, &options).unwrap().recip_pivot_growth().unwrap();
+    let lu = factorize(&a, &options).unwrap();
+    assert!((lu.pivot_growth() - expected).abs() <= 1e-15 * expected);
+}
+
+#[test]
+fn diagonal_pivoting_and_the_policy() {
+    let a = small_pivot();
+    // Always pivoting on the diagonal makes U(1,1) = 1 - 1e12
+    let options = Options::new().column_ordering(ColumnOrdering::Natural).diag_pivot_thresh(0.0);
+    let lu = factorize(&a, &options).unwrap();
+    assert!((lu.pivot_growth() - 1e-12).abs() < 1e-15);
+
+    let policy = PivotGrowthPolicy::new(1e-3);
+    assert!(policy.is_unstable(lu.pivot_growth()));
+    let stricter = policy.stricter(&options);
+    assert_eq!(stricter.as_raw().DiagPivotThresh, 0.1);
+    let lu = factorize(&a, &stricter).unwrap();
+    assert!(!policy.is_unstable(lu.pivot_growth()));
+}
+
+#[test]
+fn policy() {
+    let policy = PivotGrowthPolicy::new(0.01);
+    assert_eq!(policy.threshold(), 0.01);
+    assert!(policy.is_unstable(0.001f32));
+    assert!(!policy.is_unstable(0.5f64));
+    assert!(policy.is_unstable(f64::NAN));
+
+    let options = Options::new().diag_pivot_thresh(0.05);
+    assert_eq!(policy.stricter(&options).as_raw().DiagPivotThresh, 0.5);
+    // Partial pivoting (the default) is as strict as it gets
+    assert_eq!(policy.stricter(&Options::new()).as_raw().DiagPivotThresh, 1.0);
+}
+
+#[test]
+fn simple_driver_and_transposed_views() {
+    let a = example_matrix();
+    let options = Options::new().equilibrate(false);
+    let expected = factorize(&a, &options).unwrap().pivot_growth();
+    let mut b = DenseMatrix::new(5, 1, &[1.0; 5]);
+    let factors = solve(&a, &mut b, &options).unwrap();
+    assert!((factors.pivot_growth(&a) - expected).abs() <= 1e-15 * expected);
+
+    let expected = factorize(&a.to_transpose(), &options).unwrap().pivot_growth();
+    let mut b = DenseMatrix::new(5, 1, &[1.0; 5]);
+    let factors = solve(&a.transpose(), &mut b, &options).unwrap();
+    assert!((factors.pivot_growth(&a.transpose()) - expected).abs() <= 1e-15 * expected);
+}
+
+#[test]
+fn incomplete_and_mixed_precision_factors() {
+    let a = example_matrix();
+    let ilu = IncompleteLu::new(&a, &IluOptions::new()).unwrap();
+    let growth = ilu.pivot_growth(&a);
+    assert!(growth.is_finite() && growth > 0.0);
+
+    let expected = factorize(&a, &Options::new()).unwrap().pivot_growth();
+    let lu = MixedPrecisionLu::new(&a, &Options::new()).unwrap();
+    assert!((lu.pivot_growth() - expected).abs() < 1e-6 * expected);
+}
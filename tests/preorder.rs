@@ -0,0 +1,88 @@
+//! Check the preordering step and the low-level factorization.
+//!
+//! The permuted matrix AC shares the values of A, so these tests
+//! also check that dropping it (with Destroy_CompCol_Permuted) leaves
+//! A intact.
+
+mod common;
+
+use common::example_matrix;
+use csuperlu_sys::{preorder, CompColMatrix, Options, SolveError};
+
+/// Check that v is a permutation of 0..n
+fn is_permutation(v: &[i32], n: usize) -> bool {
+    let mut sorted = v.to_vec();
+    sorted.sort_unstable();
+    sorted == (0..n as i32).collect::<Vec<_>>()
+}
+
+#[test]
+fn preorder_permutes_columns() {
+    let a = example_matrix();
+    let options = Options::new();
+    let perm_c = [4, 3, 2, 1, 0];
+    {
+	let ac = preorder(&options, &a, &perm_c);
+	let matrix = ac.matrix();
+	assert_eq!(matrix.nrow(), 5);
+	assert_eq!(matrix.nnz(), 12);
+	assert_eq!(ac.etree().len(), 5);
+	assert!(is_permutation(ac.perm_c(), 5));
+
+	// Each column of AC is a column of A
+	for j in 0..5 {
+	    let col = ac.perm_c()[j] as usize;
+	    let len = matrix.colend()[col] - matrix.colbeg()[col];
+	    assert_eq!(len, a.colptr()[j + 1] - a.colptr()[j]);
+	}
+    }
+    assert_eq!(a.nzval()[0], 19.0);
+}
+
+#[test]
+fn factorize_permuted_matrix() {
+    let a = example_matrix();
+    let options = Options::new();
+    let ac = preorder(&options, &a, &[0, 1, 2, 3, 4]);
+    let factors = ac.factorize(&options).expect("matrix is not singular");
+    assert_eq!(factors.l().nrow(), 5);
+    assert_eq!(factors.u().ncol(), 5);
+    assert!(factors.l().num_supernodes() >= 1);
+    assert!(is_permutation(factors.perm_r(), 5));
+    assert_eq!(factors.perm_c(), ac.perm_c());
+}
+
+#[test]
+fn factorize_singular_matrix() {
+    // The second column is zero
+    let a = CompColMatrix::new(2, 2, &[1.0f64, 1.0], &[0, 1], &[0, 2, 2]);
+    let options = Options::new();
+    let ac = preorder(&options, &a, &[0, 1]);
+    match ac.factorize(&options) {
+	Err(SolveError::Singular(_)) => (),
+	other => panic!("expected a singular matrix, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+#[should_panic(expected = "must not repeat")]
+fn invalid_column_permutation() {
+    let a = example_matrix();
+    preorder(&Options::new(), &a, &[0, 1, 1, 3, 4]);
+}
+
+#[test]
+#[should_panic(expected = "A must be square")]
+fn rectangular_matrix() {
+    let a = CompColMatrix::new(3, 2, &[1.0f64, 1.0], &[0, 1], &[0, 1, 2]);
+    preorder(&Options::new(), &a, &[0, 1]);
+}
+
+#[test]
+fn symmetric_mode_keeps_the_permutation() {
+    let a = example_matrix();
+    let options = Options::new().symmetric_mode(true);
+    let ac = preorder(&options, &a, &[4, 3, 2, 1, 0]);
+    assert_eq!(ac.perm_c(), &[4, 3, 2, 1, 0]);
+    assert_eq!(ac.etree().len(), 5);
+}
@@ -0,0 +1,109 @@
+//! Check iterative refinement of solutions computed outside SuperLU.
+
+mod common;
+
+use common::example_matrix;
+use csuperlu_sys::{factorize, solve, CompColMatrix, DenseMatrix, LinearOperator, Options};
+use num::Complex;
+
+#[test]
+fn refines_a_perturbed_solution() {
+    let a = example_matrix();
+    let x_true = [1.0, -2.0, 3.0, 0.5, 10.0];
+    let mut b = vec![0.0; 5];
+    a.apply(&x_true, &mut b);
+    let b = DenseMatrix::new(5, 1, &b);
+    let perturbed: Vec<f64> = x_true.iter().map(|x| x * (1.0 + 1e-4)).collect();
+    let mut x = DenseMatrix::new(5, 1, &perturbed);
+
+    let lu = factorize(&a, &Options::new()).unwrap();
+    let report = lu.refine(&a, &b, &mut x).unwrap();
+    assert!(report.steps()[0] >= 1);
+    assert!(report.berr()[0] < 1e-15);
+    assert!(report.ferr()[0] < 1e-12);
+    for (x, y) in x.values().iter().zip(&x_true) {
+	assert!((x - y).abs() < 1e-13 * y.abs(), "{} != {}", x, y);
+    }
+}
+
+#[test]
+fn several_columns_and_a_nearby_matrix() {
+    // Refine with the factors of A the solutions of a slightly
+    // different matrix
+    let a = example_matrix();
+    let values: Vec<f64> = a.nzval().iter().enumerate()
+	.map(|(k, v)| v * (1.0 + 1e-6 * k as f64)).collect();
+    let nearby = CompColMatrix::new(5, 5, &values, a.rowind(), a.colptr());
+    let b_values: Vec<f64> = (0..10).map(|k| (k as f64).sin()).collect();
+    let b = DenseMatrix::new(5, 2, &b_values);
+    let mut expected = DenseMatrix::new(5, 2, &b_values);
+    solve(&nearby, &mut expected, &Options::new()).unwrap();
+
+    let lu = factorize(&a, &Options::new()).unwrap();
+    let mut x = DenseMatrix::new(5, 2, &b_values);
+    lu.solve_many(&mut x).unwrap();
+    let report = lu.refine(&nearby, &b, &mut x).unwrap();
+    assert_eq!(report.steps().len(), 2);
+    assert!(report.steps().iter().all(|&steps| steps >= 1));
+    for (x, y) in x.values().iter().zip(expected.values()) {
+	assert!((x - y).abs() < 1e-12, "{} != {}", x, y);
+    }
+}
+
+#[test]
+fn equilibrated_transposed_system() {
+    // diag(1e10, 1, 1e-10) [[2, 0, 1], [0, 1, 0], [1, 0, 3]]
+    // diag(1, 1, 1e-6), which is equilibrated, solved as A^T x = b
+    let a = CompColMatrix::new(3, 3, &[2e10, 1e-10, 1.0, 1e4, 3e-16], &[0, 2, 1, 0, 2], &[0, 2, 3, 5]);
+    let x_true = [1e-10, -2.0, 3e10];
+    let mut b = vec![0.0; 3];
+    a.apply_transpose(&x_true, &mut b);
+    let b = DenseMatrix::new(3, 1, &b);
+
+    let lu = factorize(&a.transpose(), &Options::new()).unwrap();
+    assert!(lu.row_scale().is_some() || lu.col_scale().is_some());
+    let start: Vec<f64> = x_true.iter().map(|x| 1.1 * x).collect();
+    let mut x = DenseMatrix::new(3, 1, &start);
+    lu.refine(&a.transpose(), &b, &mut x).unwrap();
+    for (x, y) in x.values().iter().zip(&x_true) {
+	assert!((x - y).abs() < 1e-12 * y.abs(), "{} != {}", x, y);
+    }
+
+    // The same system, given as a compressed-column copy of A^T
+    let mut x = DenseMatrix::new(3, 1, &start);
+    lu.refine(&a.to_transpose(), &b, &mut x).unwrap();
+    for (x, y) in x.values().iter().zip(&x_true) {
+	assert!((x - y).abs() < 1e-12 * y.abs(), "{} != {}", x, y);
+    }
+}
+
+#[test]
+fn complex_single_precision() {
+    let values: Vec<Complex<f32>> = example_matrix().nzval().iter().enumerate()
+	.map(|(k, &v)| Complex::new(v as f32, k as f32)).collect();
+    let a = CompColMatrix::new(5, 5, &values, example_matrix().rowind(), example_matrix().colptr());
+    let x_true: Vec<Complex<f32>> = (0..5).map(|k| Complex::new(1.0, k as f32)).collect();
+    let mut b = vec![Complex::new(0.0, 0.0); 5];
+    a.apply(&x_true, &mut b);
+    let mut x = DenseMatrix::new(5, 1, &b);
+    let b = DenseMatrix::new(5, 1, &b);
+
+    // The simple driver's factors refine its own solution
+    let factors = solve(&a.adjoint(), &mut DenseMatrix::new(5, 1, &[Complex::new(1.0, 0.0); 5]),
+			&Options::new()).unwrap();
+    let mut y = DenseMatrix::new(5, 1, &[Complex::new(0.0, 0.0); 5]);
+    let ones = DenseMatrix::new(5, 1, &[Complex::new(1.0, 0.0); 5]);
+    factors.refine(&a.adjoint(), &ones, &mut y).unwrap();
+    let mut residual = vec![Complex::new(0.0, 0.0); 5];
+    a.to_adjoint().apply(y.values(), &mut residual);
+    for r in &residual {
+	assert!((r - Complex::new(1.0, 0.0)).norm() < 1e-5, "{}", r);
+    }
+
+    let factors = solve(&a, &mut x, &Options::new()).unwrap();
+    let report = factors.refine(&a, &b, &mut x).unwrap();
+    assert!(report.berr()[0] < 1e-6);
+    for (x, y) in x.values().iter().zip(&x_true) {
+	assert!((x - y).norm() < 1e-5 * y.norm(), "{} != {}", x, y);
+    }
+}
@@ -1,22 +1,26 @@
-//! This example is the same as the one in section 2.2 of the SuperLU manual. 
+//! This example is the same as the one in section 2.2 of the SuperLU manual.
 //!
 //! From the original source code:
 //! " This is the small 5x5 example used in the Sections 2 and 3 of the
 //!   Users’ Guide to illustrate how to call a SuperLU routine, and the
 //!   matrix data structures used by SuperLU. "
 //!
-//! Compared to the original C code, this code is much cleaner, and much
-//! safer. However, function names similar to SuperLU have been kept to make
-//! porting code as easy as possible
+//! This version calls the raw SuperLU functions, in the same way as
+//! the C code. See tests/solve.rs for the same example using the safe
+//! solve function.
 //!
 
 use std::mem::MaybeUninit;
 
-use csuperlu_sys::{SuperMatrix, dCreate_CompCol_Matrix, dCreate_Dense_Matrix, superlu_options_t, StatInit, SuperLUStat_t, dgssv, Stype_t_SLU_NC, Dtype_t_SLU_C, Dtype_t_SLU_D, Mtype_t_SLU_GE, colperm_t_NATURAL, set_default_options, Stype_t_SLU_DN};
+use csuperlu_sys::{
+    colperm_t_NATURAL, dCreate_CompCol_Matrix, dCreate_Dense_Matrix, dgssv,
+    set_default_options, superlu_options_t, Destroy_CompCol_Matrix, Destroy_SuperMatrix_Store,
+    Destroy_SuperNode_Matrix, Dtype_t_SLU_D, Mtype_t_SLU_GE, StatFree, StatInit, Stype_t_SLU_DN,
+    Stype_t_SLU_NC, SuperLUStat_t, SuperMatrix,
+};
 
-/// Check the simple example in the SuperLU manual comiles
-/// and runs.
-/// TODO: check the answer is right too
+/// Check the simple example in the SuperLU manual compiles
+/// and runs, and gives the right answer.
 #[test]
 fn simple_example() {
 
@@ -34,7 +38,7 @@ fn simple_example() {
     let e: f64 = 5.0;
     let r: f64 = 18.0;
     let l: f64 = 12.0;
-    
+
     // Vector of doubles of length nnz
     let mut a = vec![s, l, l, u, l, l, u, p, u, e, u, r];
 
@@ -44,57 +48,78 @@ fn simple_example() {
     // Vector of ints of length n+1
     let mut xa = vec![0, 3, 6, 8, 10, 12];
 
-    // Make the matrix
-    let mut A = unsafe {
-	let mut A = MaybeUninit::<SuperMatrix>::uninit();
-	dCreate_CompCol_Matrix(A.as_mut_ptr(), m, n, nnz,
+    // Make the matrix (which uses the vectors above in place)
+    let mut a_mat = unsafe {
+	let mut a_mat = MaybeUninit::<SuperMatrix>::uninit();
+	dCreate_CompCol_Matrix(a_mat.as_mut_ptr(), m, n, nnz,
 			       a.as_mut_ptr(), asub.as_mut_ptr(), xa.as_mut_ptr(),
 			       Stype_t_SLU_NC, Dtype_t_SLU_D, Mtype_t_SLU_GE);
-	A.assume_init()
+	a_mat.assume_init()
     };
 
     // Make the RHS vector
     let nrhs = 1;
     let mut rhs = vec![1.0; m as usize];
-    let mut B = unsafe {
-	let mut B = MaybeUninit::<SuperMatrix>::uninit();
-	dCreate_Dense_Matrix(B.as_mut_ptr(), m, nrhs, rhs.as_mut_ptr(), m,
-			     Stype_t_SLU_DN, Dtype_t_SLU_D, Mtype_t_SLU_GE);	
-	B.assume_init()
+    let mut b_mat = unsafe {
+	let mut b_mat = MaybeUninit::<SuperMatrix>::uninit();
+	dCreate_Dense_Matrix(b_mat.as_mut_ptr(), m, nrhs, rhs.as_mut_ptr(), m,
+			     Stype_t_SLU_DN, Dtype_t_SLU_D, Mtype_t_SLU_GE);
+	b_mat.assume_init()
     };
-    
+
     let mut options = unsafe {
 	let mut options = MaybeUninit::<superlu_options_t>::uninit();
 	set_default_options(options.as_mut_ptr());
 	options.assume_init()
     };
     options.ColPerm = colperm_t_NATURAL;
-    
-    let mut perm_r = Vec::<i32>::with_capacity(m as usize);
-    let mut perm_c = Vec::<i32>::with_capacity(n as usize);
+
+    // dgssv writes the permutations into these, so they must have
+    // the right length (not just the right capacity)
+    let mut perm_r = vec![0; m as usize];
+    let mut perm_c = vec![0; n as usize];
 
     let mut stat = unsafe {
 	let mut stat = MaybeUninit::<SuperLUStat_t>::uninit();
 	StatInit(stat.as_mut_ptr());
-	    stat.assume_init()
+	stat.assume_init()
     };
-    
+
     let mut info = 0;
-    let (mut L, mut U, mut info) = unsafe {
-	let mut L = MaybeUninit::<SuperMatrix>::uninit();
-	let mut U = MaybeUninit::<SuperMatrix>::uninit();
-	
-	dgssv(&mut options, &mut A, perm_c.as_mut_ptr(),
+    let (mut l_mat, mut u_mat) = unsafe {
+	let mut l_mat = MaybeUninit::<SuperMatrix>::uninit();
+	let mut u_mat = MaybeUninit::<SuperMatrix>::uninit();
+
+	dgssv(&mut options, &mut a_mat, perm_c.as_mut_ptr(),
 	      perm_r.as_mut_ptr(),
-	      L.as_mut_ptr(), U.as_mut_ptr(),
-	      &mut B, &mut stat, &mut info);
+	      l_mat.as_mut_ptr(), u_mat.as_mut_ptr(),
+	      &mut b_mat, &mut stat, &mut info);
 	(
-	    L.assume_init(),
-	    U.assume_init(),
-	    info
+	    l_mat.assume_init(),
+	    u_mat.assume_init(),
 	)
     };
+    assert_eq!(info, 0);
 
-    
+    // The vectors belong to Rust, so only free the Stores of A and B,
+    // but L and U were allocated by SuperLU
+    unsafe {
+	Destroy_SuperMatrix_Store(&mut a_mat);
+	Destroy_SuperMatrix_Store(&mut b_mat);
+	Destroy_SuperNode_Matrix(&mut l_mat);
+	Destroy_CompCol_Matrix(&mut u_mat);
+	StatFree(&mut stat);
+    }
 
+    // Check A x = b (b is all ones)
+    let x = rhs;
+    let mut ax = vec![0.0; m as usize];
+    for j in 0..n as usize {
+	for k in xa[j] as usize..xa[j + 1] as usize {
+	    ax[asub[k] as usize] += a[k] * x[j];
+	}
+    }
+    for value in ax {
+	assert!((value - 1.0).abs() < 1e-12);
+    }
 }
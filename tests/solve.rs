@@ -0,0 +1,121 @@
+//! Check the safe simple driver in all four precisions.
+//!
+//! Each test solves a small system and checks the residual, using a
+//! dense copy of the matrix.
+
+mod common;
+
+use common::example_arrays;
+use csuperlu_sys::{solve, CompColMatrix, CompRowMatrix, DenseMatrix, Options, SolveError, Trans};
+use num::Complex;
+
+/// The maximum of |A x - b|, where the non-zeros of A are given
+/// column by column
+fn residual<T>(n: usize, a: &[T], asub: &[i32], xa: &[i32], x: &[T], b: &[T]) -> f64
+where
+    T: Copy + Into<Complex<f64>>,
+{
+    let mut ax = vec![Complex::new(0.0, 0.0); n];
+    for (j, &xj) in x.iter().enumerate() {
+	for k in xa[j] as usize..xa[j + 1] as usize {
+	    ax[asub[k] as usize] += a[k].into() * xj.into();
+	}
+    }
+    ax.iter().zip(b).map(|(lhs, &rhs)| (lhs - rhs.into()).norm()).fold(0.0, f64::max)
+}
+
+#[test]
+fn solve_real_double() {
+    let (a, asub, xa) = example_arrays();
+    let matrix = CompColMatrix::new(5, 5, &a, &asub, &xa);
+    let rhs = vec![1.0; 5];
+    let mut b = DenseMatrix::new(5, 1, &rhs);
+    let factors = solve(&matrix, &mut b, &Options::new()).unwrap();
+    assert_eq!(factors.trans(), Trans::NoTrans);
+    assert_eq!(factors.perm_r().len(), 5);
+    assert_eq!(factors.perm_c().len(), 5);
+    assert!(residual(5, &a, &asub, &xa, b.values(), &rhs) < 1e-12);
+}
+
+#[test]
+fn solve_real_single() {
+    let (a, asub, xa) = example_arrays();
+    let a: Vec<f32> = a.iter().map(|&x| x as f32).collect();
+    let matrix = CompColMatrix::new(5, 5, &a, &asub, &xa);
+    let rhs = vec![1.0f32, 2.0, 3.0, 4.0, 5.0];
+    let mut b = DenseMatrix::new(5, 1, &rhs);
+    solve(&matrix, &mut b, &Options::new()).unwrap();
+    let x: Vec<f64> = b.values().iter().map(|&x| x as f64).collect();
+    let a: Vec<f64> = a.iter().map(|&x| x as f64).collect();
+    let rhs: Vec<f64> = rhs.iter().map(|&x| x as f64).collect();
+    assert!(residual(5, &a, &asub, &xa, &x, &rhs) < 1e-4);
+}
+
+#[test]
+fn solve_complex() {
+    let (a, asub, xa) = example_arrays();
+    let a: Vec<Complex<f64>> = a.iter().enumerate()
+	.map(|(k, &x)| Complex::new(x, k as f64)).collect();
+    let matrix = CompColMatrix::new(5, 5, &a, &asub, &xa);
+    let rhs: Vec<Complex<f64>> = (0..10).map(|k| Complex::new(1.0, k as f64)).collect();
+    let mut b = DenseMatrix::new(5, 2, &rhs);
+    solve(&matrix, &mut b, &Options::new()).unwrap();
+    for col in 0..2 {
+	let (x, rhs) = (&b.values()[5 * col..5 * col + 5], &rhs[5 * col..5 * col + 5]);
+	assert!(residual(5, &a, &asub, &xa, x, rhs) < 1e-12);
+    }
+
+    // The same system in single precision
+    let a: Vec<Complex<f32>> = a.iter().map(|x| Complex::new(x.re as f32, x.im as f32)).collect();
+    let matrix = CompColMatrix::new(5, 5, &a, &asub, &xa);
+    let mut b = DenseMatrix::new(5, 1, &[Complex::new(1.0f32, 0.0); 5]);
+    solve(&matrix, &mut b, &Options::new()).unwrap();
+    let x: Vec<Complex<f64>> = b.values().iter().map(|x| Complex::new(x.re as f64, x.im as f64)).collect();
+    let a: Vec<Complex<f64>> = a.iter().map(|x| Complex::new(x.re as f64, x.im as f64)).collect();
+    assert!(residual(5, &a, &asub, &xa, &x, &[Complex::new(1.0, 0.0); 5]) < 1e-4);
+}
+
+#[test]
+fn solve_compressed_row_and_adjoint() {
+    let (a, asub, xa) = example_arrays();
+    // The arrays, read as compressed-row, describe A^T
+    let at = CompRowMatrix::new(5, 5, &a, &asub, &xa);
+    let rhs = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+    let mut b = DenseMatrix::new(5, 1, &rhs);
+    let factors = solve(&at, &mut b, &Options::new()).unwrap();
+    assert_eq!(factors.trans(), Trans::Trans);
+    let transposed = CompColMatrix::new(5, 5, &a, &asub, &xa).to_transpose();
+    let residual_t = residual(5, transposed.nzval(), transposed.rowind(), transposed.colptr(),
+			      b.values(), &rhs);
+    assert!(residual_t < 1e-12);
+
+    let c: Vec<Complex<f64>> = a.iter().map(|&x| Complex::new(x, 1.0)).collect();
+    let matrix = CompColMatrix::new(5, 5, &c, &asub, &xa);
+    let rhs = vec![Complex::new(1.0, -1.0); 5];
+    let mut b = DenseMatrix::new(5, 1, &rhs);
+    let factors = solve(&matrix.adjoint(), &mut b, &Options::new()).unwrap();
+    assert_eq!(factors.trans(), Trans::Conj);
+    let adjoint = matrix.to_adjoint();
+    assert!(residual(5, adjoint.nzval(), adjoint.rowind(), adjoint.colptr(), b.values(), &rhs) < 1e-12);
+}
+
+#[test]
+fn solve_half_stored_symmetric() {
+    // Lower triangle of [[4, 1, 0], [1, 4, 1], [0, 1, 4]]
+    let matrix = CompColMatrix::symmetric_lower(3, &[4.0, 1.0, 4.0, 1.0, 4.0], &[0, 1, 1, 2, 2], &[0, 2, 4, 5]);
+    let full = matrix.to_general();
+    let mut b = DenseMatrix::new(3, 1, &[1.0, 2.0, 3.0]);
+    solve(&matrix, &mut b, &Options::for_matrix(&matrix)).unwrap();
+    assert!(residual(3, full.nzval(), full.rowind(), full.colptr(), b.values(), &[1.0, 2.0, 3.0]) < 1e-12);
+}
+
+#[test]
+fn solve_singular() {
+    // The second column is zero
+    let matrix = CompColMatrix::new(2, 2, &[1.0f64, 1.0], &[0, 1], &[0, 2, 2]);
+    let mut b = DenseMatrix::new(2, 1, &[1.0, 1.0]);
+    match solve(&matrix, &mut b, &Options::new()) {
+	Err(SolveError::Singular(1)) => (),
+	other => panic!("expected a singular matrix, got {:?}", other.map(|_| ())),
+    }
+}
@@ -0,0 +1,111 @@
+//! Check solving with the transpose and the adjoint of a stored
+//! factorization against explicitly transposed systems.
+
+mod common;
+
+use common::example_matrix;
+use csuperlu_sys::{factorize, solve, CompColMatrix, DenseMatrix, Options, Trans};
+use num::Complex;
+
+/// The example matrix, with complex values
+fn complex_matrix() -> CompColMatrix<Complex<f64>> {
+    let a = example_matrix();
+    let values: Vec<Complex<f64>> = a.nzval().iter().enumerate()
+	.map(|(k, &x)| Complex::new(x, (k % 3) as f64 - 1.0)).collect();
+    CompColMatrix::new(5, 5, &values, a.rowind(), a.colptr())
+}
+
+/// The maximum of |x - y|
+fn distance<T: Copy + Into<Complex<f64>>>(x: &[T], y: &[T]) -> f64 {
+    x.iter().zip(y).map(|(&a, &b)| (a.into() - b.into()).norm()).fold(0.0, f64::max)
+}
+
+#[test]
+fn transpose_matches_explicit_transpose() {
+    let a = example_matrix();
+    let rhs = [1.0, 2.0, 3.0, 4.0, 5.0];
+    let mut x = rhs;
+    factorize(&a, &Options::new()).unwrap().solve_transpose(&mut x).unwrap();
+
+    let mut expected = rhs;
+    factorize(&a.to_transpose(), &Options::new()).unwrap().solve(&mut expected).unwrap();
+    assert!(distance(&x, &expected) < 1e-12);
+}
+
+#[test]
+fn adjoint_matches_explicit_adjoint() {
+    let a = complex_matrix();
+    let rhs: Vec<Complex<f64>> = (0..5).map(|k| Complex::new(k as f64, 1.0)).collect();
+    let lu = factorize(&a, &Options::new()).unwrap();
+    let mut x = rhs.clone();
+    lu.solve_adjoint(&mut x).unwrap();
+    let mut y = rhs.clone();
+    lu.solve_transpose(&mut y).unwrap();
+
+    let mut expected = rhs.clone();
+    factorize(&a.to_adjoint(), &Options::new()).unwrap().solve(&mut expected).unwrap();
+    assert!(distance(&x, &expected) < 1e-12);
+    let mut expected = rhs;
+    factorize(&a.to_transpose(), &Options::new()).unwrap().solve(&mut expected).unwrap();
+    assert!(distance(&y, &expected) < 1e-12);
+}
+
+#[test]
+fn transpose_of_equilibrated_matrix() {
+    // Row and column scalings are both needed for this matrix
+    let a = CompColMatrix::new(3, 3, &[1e10f64, 1.0, 1.0, 1e-10], &[0, 1, 2, 2], &[0, 1, 3, 4]);
+    let rhs = [1.0, 2.0, 3.0];
+    let lu = factorize(&a, &Options::new()).unwrap();
+    let mut x = rhs;
+    lu.solve_transpose(&mut x).unwrap();
+
+    let mut expected = rhs;
+    factorize(&a.to_transpose(), &Options::new()).unwrap().solve(&mut expected).unwrap();
+    for (xi, yi) in x.iter().zip(&expected) {
+	assert!((xi - yi).abs() <= 1e-8 * yi.abs());
+    }
+}
+
+#[test]
+fn transpose_of_adjoint_view() {
+    // The factors are of A, stored for the system A^H, so the
+    // transpose of the system is conj(A)
+    let a = complex_matrix();
+    let rhs: Vec<Complex<f64>> = (0..10).map(|k| Complex::new(1.0, k as f64)).collect();
+    let lu = factorize(&a.adjoint(), &Options::new()).unwrap();
+    let mut b = DenseMatrix::new(5, 2, &rhs);
+    lu.solve_many_trans(Trans::Trans, &mut b).unwrap();
+    let mut adjoint = DenseMatrix::new(5, 2, &rhs);
+    lu.solve_many_trans(Trans::Conj, &mut adjoint).unwrap();
+
+    let conj_values: Vec<Complex<f64>> = a.nzval().iter().map(|x| x.conj()).collect();
+    let conj_a = CompColMatrix::new(5, 5, &conj_values, a.rowind(), a.colptr());
+    let mut expected = DenseMatrix::new(5, 2, &rhs);
+    factorize(&conj_a, &Options::new()).unwrap().solve_many(&mut expected).unwrap();
+    assert!(distance(b.values(), expected.values()) < 1e-12);
+    let mut expected = DenseMatrix::new(5, 2, &rhs);
+    factorize(&a, &Options::new()).unwrap().solve_many(&mut expected).unwrap();
+    assert!(distance(adjoint.values(), expected.values()) < 1e-12);
+}
+
+#[test]
+fn simple_driver_factorization_transpose() {
+    let a = complex_matrix();
+    let rhs: Vec<Complex<f32>> = (0..5).map(|k| Complex::new(1.0, k as f32)).collect();
+    let values: Vec<Complex<f32>> = a.nzval().iter()
+	.map(|x| Complex::new(x.re as f32, x.im as f32)).collect();
+    let a = CompColMatrix::new(5, 5, &values, a.rowind(), a.colptr());
+    let mut b = DenseMatrix::new(5, 1, &rhs);
+    let factorization = solve(&a.transpose(), &mut b, &Options::new()).unwrap();
+    let mut x = DenseMatrix::new(5, 1, &rhs);
+    factorization.solve_adjoint(&mut x).unwrap();
+
+    let mut expected = DenseMatrix::new(5, 1, &rhs);
+    let conj_values: Vec<Complex<f32>> = values.iter().map(|x| x.conj()).collect();
+    let conj_a = CompColMatrix::new(5, 5, &conj_values, a.rowind(), a.colptr());
+    solve(&conj_a, &mut expected, &Options::new()).unwrap();
+    let scale = expected.values().iter().map(|x| x.norm()).fold(0.0, f32::max);
+    for (xi, yi) in x.values().iter().zip(expected.values()) {
+	assert!((xi - yi).norm() < 1e-4 * scale);
+    }
+}
@@ -0,0 +1,85 @@
+//! Check that one symbolic analysis can be reused for several
+//! matrices with the same pattern.
+
+mod common;
+
+use common::{example_arrays, residual};
+use csuperlu_sys::{CompColMatrix, Options, SolveError, SymbolicLu};
+use num::Complex;
+
+/// The pattern of the 5x5 matrix from section 2.2 of the SuperLU manual
+const ROWIND: [i32; 12] = [0, 1, 4, 1, 2, 4, 0, 2, 0, 3, 3, 4];
+const COLPTR: [i32; 6] = [0, 3, 6, 8, 10, 12];
+
+/// The values of the example matrix, changed a little for each step
+fn values(step: usize) -> Vec<f64> {
+    let (values, _, _) = example_arrays();
+    values.iter().enumerate().map(|(k, x)| x + (step * k) as f64 * 0.5).collect()
+}
+
+#[test]
+fn refactor_with_same_pattern() {
+    let a = CompColMatrix::new(5, 5, &values(0), &ROWIND, &COLPTR);
+    let mut symbolic = SymbolicLu::new(&a, &Options::new());
+    let perm_c = symbolic.perm_c().to_vec();
+    assert!(symbolic.factorization().is_none());
+
+    let rhs = [1.0, 2.0, 3.0, 4.0, 5.0];
+    for step in 0..5 {
+	let a = CompColMatrix::new(5, 5, &values(step), &ROWIND, &COLPTR);
+	let lu = symbolic.refactor(&a).unwrap();
+	let mut x = rhs;
+	lu.solve(&mut x).unwrap();
+	assert!(residual(&a, &x, &rhs) < 1e-12);
+    }
+    assert_eq!(symbolic.perm_c(), &perm_c[..]);
+}
+
+#[test]
+fn refactor_with_same_row_permutation() {
+    let a = CompColMatrix::new(5, 5, &values(0), &ROWIND, &COLPTR);
+    let mut symbolic = SymbolicLu::new(&a, &Options::new());
+
+    let rhs = [1.0, 0.0, -1.0, 0.0, 1.0];
+    let mut perm_r = None;
+    for step in 0..5 {
+	let a = CompColMatrix::new(5, 5, &values(step), &ROWIND, &COLPTR);
+	let lu = symbolic.refactor_same_row_perm(&a).unwrap();
+	let mut x = rhs;
+	lu.solve(&mut x).unwrap();
+	assert!(residual(&a, &x, &rhs) < 1e-12);
+
+	// The row permutation is chosen once, and then kept
+	let current = lu.perm_r().to_vec();
+	assert_eq!(perm_r.get_or_insert_with(|| current.clone()), &current);
+    }
+}
+
+#[test]
+fn complex_refactor() {
+    let complex_values = |step: usize| -> Vec<Complex<f64>> {
+	values(step).iter().map(|&x| Complex::new(x, step as f64)).collect()
+    };
+    let a = CompColMatrix::new(5, 5, &complex_values(0), &ROWIND, &COLPTR);
+    let mut symbolic = SymbolicLu::new(&a, &Options::new());
+    for step in 0..3 {
+	let a = CompColMatrix::new(5, 5, &complex_values(step), &ROWIND, &COLPTR);
+	let rhs = [Complex::new(1.0, 1.0); 5];
+	let mut x = rhs;
+	symbolic.refactor_same_row_perm(&a).unwrap().solve(&mut x).unwrap();
+	assert!(residual(&a, &x, &rhs) < 1e-12);
+    }
+}
+
+#[test]
+fn different_pattern_is_rejected() {
+    let a = CompColMatrix::new(5, 5, &values(0), &ROWIND, &COLPTR);
+    let mut symbolic = SymbolicLu::new(&a, &Options::new());
+
+    // Move the entry in row 4 of column 0 to row 3
+    let mut rowind = ROWIND;
+    rowind[2] = 3;
+    let b = CompColMatrix::new(5, 5, &values(0), &rowind, &COLPTR);
+    assert_eq!(symbolic.refactor(&b).err(), Some(SolveError::PatternMismatch));
+    assert_eq!(symbolic.refactor_same_row_perm(&b).err(), Some(SolveError::PatternMismatch));
+}
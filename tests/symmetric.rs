@@ -0,0 +1,64 @@
+//! Check half-stored symmetric and Hermitian matrices are recorded
+//! with the right Mtype, and expanded correctly to full storage.
+
+use csuperlu_sys::{
+    colperm_t_COLAMD, colperm_t_MMD_AT_PLUS_A, yes_no_t_NO, yes_no_t_YES, CompColMatrix,
+    Options, Mtype_t_SLU_GE, Mtype_t_SLU_HEL, Mtype_t_SLU_SYL,
+};
+use num::Complex;
+
+/// Lower triangle of the symmetric matrix
+///
+/// [ 4 1 0 ]
+/// [ 1 5 2 ]
+/// [ 0 2 6 ]
+fn lower_arrays() -> (Vec<f64>, Vec<i32>, Vec<i32>) {
+    (vec![4.0, 1.0, 5.0, 2.0, 6.0], vec![0, 1, 1, 2, 2], vec![0, 2, 4, 5])
+}
+
+#[test]
+fn symmetric_lower_expands_to_full() {
+    let (nzval, rowind, colptr) = lower_arrays();
+    let a = CompColMatrix::symmetric_lower(3, &nzval, &rowind, &colptr);
+    assert_eq!(a.mtype(), Mtype_t_SLU_SYL);
+    assert!(a.is_half_stored());
+
+    let full = a.to_general();
+    assert_eq!(full.mtype(), Mtype_t_SLU_GE);
+    assert!(!full.is_half_stored());
+    assert_eq!(full.colptr(), &[0, 2, 5, 7]);
+    assert_eq!(full.rowind(), &[0, 1, 0, 1, 2, 1, 2]);
+    assert_eq!(full.nzval(), &[4.0, 1.0, 1.0, 5.0, 2.0, 2.0, 6.0]);
+}
+
+#[test]
+fn hermitian_lower_conjugates_upper_triangle() {
+    let nzval = vec![Complex::new(2.0, 0.0), Complex::new(1.0, 3.0), Complex::new(5.0, 0.0)];
+    let a = CompColMatrix::hermitian_lower(2, &nzval, &[0, 1, 1], &[0, 2, 3]);
+    assert_eq!(a.mtype(), Mtype_t_SLU_HEL);
+
+    let full = a.to_general();
+    assert_eq!(full.rowind(), &[0, 1, 0, 1]);
+    assert_eq!(full.nzval(), &[Complex::new(2.0, 0.0), Complex::new(1.0, 3.0),
+			       Complex::new(1.0, -3.0), Complex::new(5.0, 0.0)]);
+}
+
+#[test]
+fn options_follow_matrix_type() {
+    let (nzval, rowind, colptr) = lower_arrays();
+    let symmetric = CompColMatrix::symmetric_lower(3, &nzval, &rowind, &colptr);
+    let options = Options::for_matrix(&symmetric);
+    assert_eq!(options.as_raw().SymmetricMode, yes_no_t_YES);
+    assert_eq!(options.as_raw().ColPerm, colperm_t_MMD_AT_PLUS_A);
+
+    let general = symmetric.to_general();
+    let options = Options::for_matrix(&general);
+    assert_eq!(options.as_raw().SymmetricMode, yes_no_t_NO);
+    assert_eq!(options.as_raw().ColPerm, colperm_t_COLAMD);
+}
+
+#[test]
+#[should_panic(expected = "above the diagonal")]
+fn upper_elements_rejected() {
+    CompColMatrix::symmetric_lower(2, &[1.0, 2.0, 3.0], &[0, 0, 1], &[0, 1, 3]);
+}
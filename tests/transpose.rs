@@ -0,0 +1,89 @@
+//! Check the transposed views and copies of compressed-column
+//! matrices, and solving with them.
+
+mod common;
+
+use common::example_matrix;
+use csuperlu_sys::{CompColMatrix, DenseMatrix, Options, Trans, ValueType};
+use num::Complex;
+
+/// Compute A x
+fn multiply<T>(a: &CompColMatrix<T>, x: &[T]) -> Vec<T>
+where
+    T: ValueType + std::ops::Add<Output = T> + std::ops::Mul<Output = T>,
+{
+    let mut y = vec![T::default(); a.nrow()];
+    for (j, &xj) in x.iter().enumerate() {
+	for k in a.colptr()[j] as usize..a.colptr()[j + 1] as usize {
+	    let i = a.rowind()[k] as usize;
+	    y[i] += a.nzval()[k] * xj;
+	}
+    }
+    y
+}
+
+#[test]
+fn transpose_view_shares_arrays() {
+    // A 3x2 matrix
+    let a = CompColMatrix::new(3, 2, &[1.0f64, 2.0, 3.0], &[0, 2, 1], &[0, 2, 3]);
+    let at = a.transpose();
+    assert_eq!(at.trans(), Trans::Trans);
+    assert_eq!((at.nrow(), at.ncol()), (2, 3));
+    assert_eq!(at.matrix().nzval().as_ptr(), a.nzval().as_ptr());
+    assert_eq!(at.matrix().rowptr(), a.colptr());
+    assert_eq!(at.matrix().colind(), a.rowind());
+}
+
+#[test]
+fn materialised_transpose() {
+    let a = CompColMatrix::new(3, 2, &[1.0f64, 2.0, 3.0], &[0, 2, 1], &[0, 2, 3]);
+    let at = a.to_transpose();
+    assert_eq!((at.nrow(), at.ncol()), (2, 3));
+    assert_eq!(at.colptr(), &[0, 1, 2, 3]);
+    assert_eq!(at.rowind(), &[0, 1, 0]);
+    assert_eq!(at.nzval(), &[1.0, 3.0, 2.0]);
+
+    let att = at.to_transpose();
+    assert_eq!(att.colptr(), a.colptr());
+    assert_eq!(att.rowind(), a.rowind());
+    assert_eq!(att.nzval(), a.nzval());
+}
+
+#[test]
+fn solve_with_transpose_view() {
+    let a = example_matrix();
+    let factors = a.transpose().factorize(&Options::new()).unwrap();
+    assert_eq!(factors.trans(), Trans::Trans);
+
+    let rhs = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+    let mut b = DenseMatrix::new(5, 1, &rhs);
+    factors.solve(&mut b).unwrap();
+    let ax = multiply(&a.to_transpose(), b.values());
+    for (lhs, rhs) in ax.iter().zip(&rhs) {
+	assert!((lhs - rhs).abs() < 1e-10);
+    }
+}
+
+#[test]
+fn solve_with_adjoint_view() {
+    let c = |r, i| Complex::new(r, i);
+    let values = [c(4.0, 1.0), c(1.0, -2.0), c(0.5, 3.0), c(5.0, -1.0), c(2.0, 2.0), c(6.0, 0.5)];
+    let a = CompColMatrix::new(3, 3, &values, &[0, 1, 0, 1, 2, 2], &[0, 2, 5, 6]);
+    let factors = a.adjoint().factorize(&Options::new()).unwrap();
+    assert_eq!(factors.trans(), Trans::Conj);
+
+    let rhs = vec![c(1.0, 0.0), c(0.0, 1.0), c(2.0, -1.0)];
+    let mut b = DenseMatrix::new(3, 1, &rhs);
+    factors.solve(&mut b).unwrap();
+    let ax = multiply(&a.to_adjoint(), b.values());
+    for (lhs, rhs) in ax.iter().zip(&rhs) {
+	assert!((lhs - rhs).norm() < 1e-10);
+    }
+}
+
+#[test]
+#[should_panic(expected = "A must be square")]
+fn factorize_rectangular_view() {
+    let a = CompColMatrix::new(3, 2, &[1.0f64, 1.0], &[0, 1], &[0, 1, 2]);
+    let _ = a.transpose().factorize(&Options::new());
+}
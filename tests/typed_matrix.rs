@@ -0,0 +1,62 @@
+//! Check the typed matrix wrappers set the Stype, Dtype and Mtype
+//! that SuperLU expects, and read back the arrays correctly.
+
+use csuperlu_sys::super_matrix::SuperMatrix;
+use csuperlu_sys::{
+    CompColMatrix, CompRowMatrix, DenseMatrix, Ownership, ValueType, Dtype_t_SLU_C,
+    Dtype_t_SLU_D, Dtype_t_SLU_S, Dtype_t_SLU_Z, Mtype_t_SLU_GE, Stype_t_SLU_DN,
+    Stype_t_SLU_NC, Stype_t_SLU_NR, NC,
+};
+use num::Complex;
+
+/// Only accepts compressed-column matrices (of any precision)
+fn comp_col_dtype<T: ValueType, O: Ownership>(a: &SuperMatrix<NC, T, O>) -> u32 {
+    a.as_raw().Dtype
+}
+
+#[test]
+fn storage_and_precision_recorded() {
+    let a = CompColMatrix::new(2, 2, &[1.0f32, 2.0], &[0, 1], &[0, 1, 2]);
+    assert_eq!(a.as_raw().Stype, Stype_t_SLU_NC);
+    assert_eq!(a.mtype(), Mtype_t_SLU_GE);
+    assert_eq!(comp_col_dtype(&a), Dtype_t_SLU_S);
+
+    let a = CompColMatrix::new(2, 2, &[1.0f64, 2.0], &[0, 1], &[0, 1, 2]);
+    assert_eq!(comp_col_dtype(&a), Dtype_t_SLU_D);
+
+    let values = [Complex::new(1.0f32, 1.0), Complex::new(2.0, -1.0)];
+    let a = CompColMatrix::new(2, 2, &values, &[0, 1], &[0, 1, 2]);
+    assert_eq!(comp_col_dtype(&a), Dtype_t_SLU_C);
+
+    let mut values = [Complex::new(1.0f64, 1.0), Complex::new(2.0, -1.0)];
+    let (mut rowind, mut colptr) = ([0, 1], [0, 1, 2]);
+    let a = CompColMatrix::from_slices(2, 2, &mut values, &mut rowind, &mut colptr);
+    assert_eq!(comp_col_dtype(&a), Dtype_t_SLU_Z);
+    assert_eq!(a.nzval()[1], Complex::new(2.0, -1.0));
+}
+
+#[test]
+fn comp_row_matrix() {
+    // [ 1 0 2 ]
+    // [ 0 3 0 ]
+    let a = CompRowMatrix::new(2, 3, &[1.0, 2.0, 3.0], &[0, 2, 1], &[0, 2, 3]);
+    assert_eq!(a.as_raw().Stype, Stype_t_SLU_NR);
+    assert_eq!((a.nrow(), a.ncol(), a.nnz()), (2, 3, 3));
+    assert_eq!(a.colind(), &[0, 2, 1]);
+    assert_eq!(a.rowptr(), &[0, 2, 3]);
+    assert_eq!(a.nzval(), &[1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn dense_matrix() {
+    let b = DenseMatrix::new(2, 2, &[1.0f64, 2.0, 3.0, 4.0]);
+    assert_eq!(b.as_raw().Stype, Stype_t_SLU_DN);
+    assert_eq!(b.as_raw().Dtype, Dtype_t_SLU_D);
+    assert_eq!(b.values(), &[1.0, 2.0, 3.0, 4.0]);
+}
+
+#[test]
+#[should_panic(expected = "index out of range")]
+fn comp_row_column_index_checked() {
+    CompRowMatrix::new(2, 3, &[1.0, 2.0, 3.0], &[0, 3, 1], &[0, 2, 3]);
+}